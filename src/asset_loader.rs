@@ -0,0 +1,193 @@
+// asset_loader.rs - Carga de assets en segundo plano
+
+use raylib::prelude::*;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread::{self, JoinHandle};
+use crate::texture_manager::{RealTextureInfo, TextureManager, LEVEL_THEME_FILES, REGION_TEXTURE_FILES, WALL_TEXTURE_FILES};
+use crate::sprite_manager::{RealSpriteInfo, SpriteManager, SpriteType};
+use crate::audio::{AudioManager, AUDIO_FILES, FOOTSTEP_VARIANT_FILES};
+
+/// Resultado de decodificar un asset en el hilo de carga
+pub enum LoadedAsset {
+    WallTexture(char, Option<RealTextureInfo>),
+    RegionTexture(char, char, Option<RealTextureInfo>),
+    ThemeTexture(usize, Option<RealTextureInfo>),
+    Sprite(SpriteType, Option<RealSpriteInfo>),
+    Sound(&'static str, Option<Vec<u8>>),
+}
+
+// Avisa por consola del asset que no se pudo cargar; el juego sigue con el fallback
+fn warn_if_missing<T>(result: Result<T, String>) -> Option<T> {
+    result.map_err(|error| eprintln!("Aviso: {}", error)).ok()
+}
+
+/// Ruta del asset que llegó sin datos. Los sprites no cuentan: los reporta
+/// `SpriteManager::missing_sprites`
+fn failed_path(asset: &LoadedAsset) -> Option<&'static str> {
+    match asset {
+        LoadedAsset::WallTexture(wall_char, None) => WALL_TEXTURE_FILES
+            .iter()
+            .find(|(c, _, _)| c == wall_char)
+            .map(|(_, path, _)| *path),
+        LoadedAsset::RegionTexture(region, wall_char, None) => REGION_TEXTURE_FILES
+            .iter()
+            .find(|(r, c, _)| r == region && c == wall_char)
+            .map(|(_, _, path)| *path),
+        LoadedAsset::ThemeTexture(level, None) => LEVEL_THEME_FILES
+            .iter()
+            .find(|(l, _)| l == level)
+            .map(|(_, path)| *path),
+        LoadedAsset::Sound(name, None) => AUDIO_FILES
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, path)| *path),
+        _ => None,
+    }
+}
+
+/// Decodifica texturas, sprites y audio en un hilo aparte y entrega los
+/// resultados al hilo principal a través de un canal
+pub struct AssetLoader {
+    receiver: Receiver<LoadedAsset>,
+    worker: Option<JoinHandle<()>>,
+    total_assets: usize,
+    loaded_assets: usize,
+    // Texturas y sonidos que no se pudieron cargar
+    failed_assets: Vec<&'static str>,
+}
+
+impl AssetLoader {
+    pub fn spawn() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let total_assets = WALL_TEXTURE_FILES.len() + REGION_TEXTURE_FILES.len() + LEVEL_THEME_FILES.len() +
+            SpriteType::ALL.len() + AUDIO_FILES.len() + FOOTSTEP_VARIANT_FILES.len();
+
+        let worker = thread::spawn(move || {
+            for (wall_char, path, frames) in WALL_TEXTURE_FILES.iter() {
+                // El color de fallback real lo asigna el TextureManager al recibirla
+                let texture = warn_if_missing(RealTextureInfo::from_png_strip(path, *frames, Color::GRAY));
+                if sender.send(LoadedAsset::WallTexture(*wall_char, texture)).is_err() {
+                    return;
+                }
+            }
+
+            for (region, wall_char, path) in REGION_TEXTURE_FILES.iter() {
+                let texture = warn_if_missing(RealTextureInfo::from_png(path, Color::GRAY));
+                if sender.send(LoadedAsset::RegionTexture(*region, *wall_char, texture)).is_err() {
+                    return;
+                }
+            }
+
+            for (level, path) in LEVEL_THEME_FILES.iter() {
+                let texture = warn_if_missing(RealTextureInfo::from_png(path, Color::GRAY));
+                if sender.send(LoadedAsset::ThemeTexture(*level, texture)).is_err() {
+                    return;
+                }
+            }
+
+            for sprite_type in SpriteType::ALL.iter() {
+                let sprite = warn_if_missing(RealSpriteInfo::from_png_strip(
+                    sprite_type.get_file_path(),
+                    sprite_type.frame_count(),
+                    sprite_type.get_fallback_color(),
+                ));
+                if sender.send(LoadedAsset::Sprite(*sprite_type, sprite)).is_err() {
+                    return;
+                }
+            }
+
+            for (name, path) in AUDIO_FILES.iter() {
+                let data = warn_if_missing(
+                    std::fs::read(path).map_err(|error| format!("Error loading sound {}: {}", path, error)),
+                );
+                if sender.send(LoadedAsset::Sound(name, data)).is_err() {
+                    return;
+                }
+            }
+
+            // Opcionales: sin archivo se usa el paso normal, sin aviso
+            for (name, path) in FOOTSTEP_VARIANT_FILES.iter() {
+                if sender.send(LoadedAsset::Sound(name, std::fs::read(path).ok())).is_err() {
+                    return;
+                }
+            }
+        });
+
+        AssetLoader {
+            receiver,
+            worker: Some(worker),
+            total_assets,
+            loaded_assets: 0,
+            failed_assets: Vec::new(),
+        }
+    }
+
+    /// Entrega a los managers todo lo que el hilo haya terminado de decodificar.
+    /// Se llama una vez por frame desde el hilo principal.
+    pub fn poll(
+        &mut self,
+        texture_manager: &mut TextureManager,
+        sprite_manager: &mut SpriteManager,
+        audio_manager: &mut AudioManager,
+    ) {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(asset) => {
+                    self.loaded_assets += 1;
+                    self.failed_assets.extend(failed_path(&asset));
+                    match asset {
+                        LoadedAsset::WallTexture(wall_char, Some(texture)) => {
+                            texture_manager.insert_real_texture(wall_char, texture);
+                        },
+                        LoadedAsset::RegionTexture(region, wall_char, Some(texture)) => {
+                            texture_manager.insert_region_texture(region, wall_char, texture);
+                        },
+                        LoadedAsset::ThemeTexture(level, Some(texture)) => {
+                            texture_manager.insert_theme_texture(level, texture);
+                        },
+                        LoadedAsset::Sprite(sprite_type, Some(sprite)) => {
+                            sprite_manager.insert_real_sprite(sprite_type, sprite);
+                        },
+                        LoadedAsset::Sound(name, data) => {
+                            audio_manager.insert_sound_data(name, data);
+                        },
+                        // Sin PNG se usan los colores/patrones de fallback
+                        _ => {}
+                    }
+                },
+                Err(TryRecvError::Empty) => {
+                    if self.is_finished() {
+                        if let Some(worker) = self.worker.take() {
+                            let _ = worker.join();
+                        }
+                    }
+                    break;
+                },
+                Err(TryRecvError::Disconnected) => {
+                    // El hilo terminó (o falló): no esperar assets que nunca llegarán
+                    self.loaded_assets = self.total_assets;
+                    if let Some(worker) = self.worker.take() {
+                        let _ = worker.join();
+                    }
+                    break;
+                },
+            }
+        }
+    }
+
+    pub fn progress(&self) -> f32 {
+        if self.total_assets == 0 {
+            1.0
+        } else {
+            self.loaded_assets as f32 / self.total_assets as f32
+        }
+    }
+
+    pub fn failed_assets(&self) -> &[&'static str] {
+        &self.failed_assets
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.loaded_assets >= self.total_assets
+    }
+}
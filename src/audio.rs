@@ -1,24 +1,255 @@
 // audio.rs - Sistema de audio optimizado
 
+use raylib::prelude::Vector2;
+use rodio::source::{ChannelVolume, SineWave};
 use rodio::{Decoder, OutputStream, Sink, Source};
 use std::collections::HashMap;
 use std::io::Cursor;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
+use crate::caster::{DEFAULT_FOG_DENSITY, MAX_DISTANCE};
+use crate::maze::{Maze, TileProperties, DOOR_CLOSED, DOOR_OPEN};
+use crate::player::Player;
+
+// Archivos de audio por nombre (se leen en el AssetLoader)
+pub const AUDIO_FILES: [(&str, &str); 8] = [
+    ("menu_music", "assets/sounds/music/menu_sound.ogg"),
+    ("background_music", "assets/sounds/music/background_ambience.ogg"),
+    ("key_pickup", "assets/sounds/sfx/key_pickup.ogg"),
+    ("footsteps", "assets/sounds/sfx/footsteps.ogg"),
+    ("trap_trigger", "assets/sounds/sfx/trap_trigger.ogg"),
+    ("player_hurt", "assets/sounds/sfx/player_hurt.ogg"),
+    ("portal_reached", "assets/sounds/sfx/portal_reached.ogg"),
+    ("level_complete", "assets/sounds/sfx/level_complete.ogg"),
+];
+
+// Variantes de pasos (recortes de footsteps.ogg; las metálicas con
+// resonancias de rejilla). Si falta alguna no se avisa y se usa `footsteps`
+pub const FOOTSTEP_VARIANT_FILES: [(&str, &str); 4] = [
+    ("footsteps_floor_2", "assets/sounds/sfx/footsteps_2.wav"),
+    ("footsteps_floor_3", "assets/sounds/sfx/footsteps_3.wav"),
+    ("footsteps_metal_1", "assets/sounds/sfx/footsteps_metal_1.wav"),
+    ("footsteps_metal_2", "assets/sounds/sfx/footsteps_metal_2.wav"),
+];
+
+/// Superficie bajo los pies del jugador, que elige el sonido de los pasos
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FootstepSurface {
+    Floor,
+    // Junto a trampas y puertas el suelo es de rejilla metálica
+    Metal,
+}
+
+impl FootstepSurface {
+    /// Superficie en la casilla de `position`: metal si ella o alguna vecina
+    /// es una trampa o una puerta
+    pub fn at(maze: &Maze, position: Vector2, block_size: usize) -> Self {
+        if position.x < 0.0 || position.y < 0.0 || block_size == 0 {
+            return FootstepSurface::Floor;
+        }
+
+        let (x, y) = ((position.x / block_size as f32) as usize, (position.y / block_size as f32) as usize);
+        let is_metal = |x: usize, y: usize| {
+            maze.get(y)
+                .and_then(|row| row.get(x))
+                .is_some_and(|&cell| TileProperties::of(cell).is_hazard || cell == DOOR_CLOSED || cell == DOOR_OPEN)
+        };
+
+        let near_metal = [(x, y), (x + 1, y), (x, y + 1), (x.wrapping_sub(1), y), (x, y.wrapping_sub(1))]
+            .into_iter()
+            .any(|(x, y)| is_metal(x, y));
+        if near_metal { FootstepSurface::Metal } else { FootstepSurface::Floor }
+    }
+
+    fn clips(&self) -> &'static [&'static str] {
+        match self {
+            FootstepSurface::Floor => &["footsteps", "footsteps_floor_2", "footsteps_floor_3"],
+            FootstepSurface::Metal => &["footsteps_metal_1", "footsteps_metal_2"],
+        }
+    }
+}
 
 pub struct AudioManager {
-    _stream: OutputStream,
-    stream_handle: rodio::OutputStreamHandle,
+    // None en modo silencioso: no hay dispositivo de salida
+    output: Option<(OutputStream, rodio::OutputStreamHandle)>,
     music_sink: Arc<Mutex<Option<Sink>>>,
     music_playing: bool,
-    music_volume: f32,
     current_music_type: Option<MusicType>,
-    sfx_volume: f32,
+    settings: AudioSettings,
     sound_data: HashMap<String, Vec<u8>>,
     footsteps_timer: f32,
     footsteps_interval: f32,
     is_walking: bool,
     last_footstep_time: Instant,
+    footstep_surface: FootstepSurface,
+    last_footstep_clip: &'static str,
+    pending_music_resume: Option<MusicResume>,
+    ambient_sources: Vec<AmbientSource>,
+    // Bucles ambientales sonando, por posición de su fuente
+    ambient_loops: HashMap<(i32, i32), Sink>,
+}
+
+// Archivo donde se guardaban antes los volúmenes; ahora viven en settings.toml
+// y este solo se lee si settings.toml todavía no los tiene
+pub const AUDIO_SETTINGS_FILE: &str = "assets/config/audio.toml";
+
+/// Volúmenes y silencio general; se guardan con el resto de los Settings
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioSettings {
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    pub muted: bool,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        AudioSettings {
+            music_volume: 0.2,
+            sfx_volume: 0.8,
+            muted: false,
+        }
+    }
+}
+
+impl AudioSettings {
+    /// Lee líneas `clave = valor`; los volúmenes inválidos y las claves de
+    /// otros settings se ignoran
+    pub fn parse(text: &str) -> Self {
+        let mut settings = AudioSettings::default();
+        let volume = |value: &str, current: f32| {
+            value.parse::<f32>()
+                .ok()
+                .filter(|volume| volume.is_finite())
+                .map(|volume| volume.clamp(0.0, 1.0))
+                .unwrap_or(current)
+        };
+
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let value = value.trim();
+
+            match key.trim() {
+                "music_volume" => settings.music_volume = volume(value, settings.music_volume),
+                "sfx_volume" => settings.sfx_volume = volume(value, settings.sfx_volume),
+                "muted" => settings.muted = value == "true",
+                _ => {}
+            }
+        }
+
+        settings
+    }
+
+    pub fn to_toml(&self) -> String {
+        format!(
+            "music_volume = {:.2}\nsfx_volume = {:.2}\nmuted = {}\n",
+            self.music_volume,
+            self.sfx_volume,
+            self.muted,
+        )
+    }
+}
+
+// Segundos entre pasos caminando y corriendo
+const WALK_FOOTSTEP_INTERVAL: f32 = 0.4;
+const SPRINT_FOOTSTEP_INTERVAL: f32 = 0.25;
+
+// Sonidos ambientales: cuántos suenan a la vez y desde qué distancia se oyen
+pub const MAX_AMBIENT_SOURCES: usize = 4;
+pub const AMBIENT_HEARING_DISTANCE: f32 = 600.0;
+const AMBIENT_MAX_VOLUME: f32 = 0.5;
+
+/// Zumbido en bucle que emite un objeto del nivel
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AmbientSound {
+    PortalHum,
+    LightBuzz,
+}
+
+impl AmbientSound {
+    // Se sintetizan con dos senoidales, así no dependen de archivos de audio
+    fn source(&self) -> impl Source<Item = f32> + Send + 'static {
+        let (base, overtone) = match self {
+            AmbientSound::PortalHum => (55.0, 110.0),
+            AmbientSound::LightBuzz => (120.0, 240.0),
+        };
+
+        SineWave::new(base).mix(SineWave::new(overtone).amplify(0.3)).amplify(0.4)
+    }
+}
+
+/// Fuente de sonido ambiental en una posición del mundo
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AmbientSource {
+    pub sound: AmbientSound,
+    pub x: f32,
+    pub y: f32,
+}
+
+impl AmbientSource {
+    fn key(&self) -> (i32, i32) {
+        (self.x as i32, self.y as i32)
+    }
+}
+
+/// Volumen de una fuente ambiental según la distancia al jugador (0 fuera de alcance)
+pub fn ambient_volume(distance: f32) -> f32 {
+    let closeness = (1.0 - distance / AMBIENT_HEARING_DISTANCE).clamp(0.0, 1.0);
+    closeness * closeness * AMBIENT_MAX_VOLUME
+}
+
+/// Fuentes que se oyen desde `listener` con su volumen, de la más cercana a la
+/// más lejana y como mucho `max_sources`
+pub fn audible_ambients(sources: &[AmbientSource], listener: Vector2, max_sources: usize) -> Vec<(AmbientSource, f32)> {
+    let mut audible: Vec<(AmbientSource, f32)> = sources
+        .iter()
+        .map(|source| (*source, (Vector2::new(source.x, source.y) - listener).length()))
+        .filter(|(_, distance)| *distance < AMBIENT_HEARING_DISTANCE)
+        .collect();
+
+    audible.sort_by(|a, b| a.1.total_cmp(&b.1));
+    audible.truncate(max_sources);
+    audible.into_iter().map(|(source, distance)| (source, ambient_volume(distance))).collect()
+}
+
+// Canal del lado contrario a la fuente: nunca se apaga del todo
+const POSITIONAL_MIN_CHANNEL_GAIN: f32 = 0.2;
+
+/// Ganancia (izquierda, derecha) de un sonido que sale de `source`: se panea
+/// según el ángulo respecto a la mirada y se apaga con la distancia igual que
+/// la niebla del render, en silencio más allá de MAX_DISTANCE
+pub fn stereo_gains(source: Vector2, player: &Player) -> (f32, f32) {
+    let offset = source - player.pos;
+    let distance = offset.length();
+    if distance >= MAX_DISTANCE {
+        return (0.0, 0.0);
+    }
+
+    let gain = (-distance * DEFAULT_FOG_DENSITY).exp();
+    if distance < 1.0 {
+        return (gain, gain);
+    }
+
+    // Ángulo relativo positivo = a la derecha de la pantalla, como los sprites
+    let pan = (offset.y.atan2(offset.x) - player.a).sin();
+    let fade = 1.0 - POSITIONAL_MIN_CHANNEL_GAIN;
+    (gain * (1.0 - fade * pan.max(0.0)), gain * (1.0 + fade * pan.min(0.0)))
+}
+
+// Segundos que la música de juego queda pausada tras la victoria
+const VICTORY_MUSIC_RESUME_DELAY: f32 = 4.0;
+
+/// Reanudación de música pendiente, avanzada por `AudioManager::update`.
+/// Cualquier cambio explícito de música la cancela.
+#[derive(Debug, Clone, Copy)]
+struct MusicResume {
+    remaining: f32,
+    music_type: MusicType,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -27,72 +258,86 @@ pub enum MusicType {
     Background,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum GameAudioEvent {
     KeyPickup,
-    TrapTriggered,
+    // Posición en el mundo de la trampa, para oírla desde su lado
+    TrapTriggered { x: f32, y: f32 },
+    // Una entidad despertó y empieza a perseguir al jugador
+    EnemyAlerted { x: f32, y: f32 },
     PlayerHurt,
+    // Una trampa de teletransporte movió al jugador
+    Teleported,
     CheckpointReached,
     LevelComplete,
 }
 
 impl AudioManager {
-    pub fn new() -> Self {
-        let (_stream, stream_handle) = OutputStream::try_default()
-            .expect("Failed to initialize audio output stream");
-        
-        let mut audio_manager = AudioManager {
-            _stream,
-            stream_handle,
+    /// Sin dispositivo de salida arranca en modo silencioso, donde todo lo
+    /// que se reproduce se ignora
+    pub fn new(settings: &AudioSettings) -> Self {
+        match OutputStream::try_default() {
+            Ok(output) => Self::with_output(settings, Some(output)),
+            Err(err) => {
+                eprintln!("Sin salida de audio ({}), el juego sigue sin sonido", err);
+                Self::silent(settings)
+            }
+        }
+    }
+
+    /// Manager que nunca suena, para máquinas sin audio
+    pub fn silent(settings: &AudioSettings) -> Self {
+        Self::with_output(settings, None)
+    }
+
+    fn with_output(settings: &AudioSettings, output: Option<(OutputStream, rodio::OutputStreamHandle)>) -> Self {
+        // Los datos de audio llegan luego desde el AssetLoader
+        AudioManager {
+            output,
             music_sink: Arc::new(Mutex::new(None)),
             music_playing: false,
-            music_volume: 0.2,
             current_music_type: None,
-            sfx_volume: 0.8,
+            settings: settings.clone(),
             sound_data: HashMap::new(),
             footsteps_timer: 0.0,
-            footsteps_interval: 0.4,
+            footsteps_interval: WALK_FOOTSTEP_INTERVAL,
             is_walking: false,
             last_footstep_time: Instant::now(),
-        };
-        
-        audio_manager.load_all_audio();
-        audio_manager
+            footstep_surface: FootstepSurface::Floor,
+            last_footstep_clip: "footsteps",
+            pending_music_resume: None,
+            ambient_sources: Vec::new(),
+            ambient_loops: HashMap::new(),
+        }
     }
 
-    fn load_all_audio(&mut self) {
-        // Cargar mÃºsica de fondo
-        self.load_audio_file("menu_music", "assets/sounds/music/menu_sound.ogg");
-        self.load_audio_file("background_music", "assets/sounds/music/background_ambience.ogg");
-        
-        // Cargar efectos de sonido
-        let sound_files = [
-            ("key_pickup", "assets/sounds/sfx/key_pickup.ogg"),
-            ("footsteps", "assets/sounds/sfx/footsteps.ogg"),
-            ("trap_trigger", "assets/sounds/sfx/trap_trigger.ogg"),
-            ("player_hurt", "assets/sounds/sfx/player_hurt.ogg"),
-            ("portal_reached", "assets/sounds/sfx/portal_reached.ogg"),
-            ("level_complete", "assets/sounds/sfx/level_complete.ogg"),
-        ];
-
-        for (name, path) in sound_files.iter() {
-            self.load_audio_file(name, path);
-        }
+    // Todo lo que suena pasa por aquí; en modo silencioso no hay sink
+    fn new_sink(&self) -> Option<Sink> {
+        let (_, handle) = self.output.as_ref()?;
+        Sink::try_new(handle).ok()
     }
 
-    fn load_audio_file(&mut self, name: &str, path: &str) {
-        match std::fs::read(path) {
-            Ok(data) => {
+    /// Registra los bytes de un archivo de audio leído en segundo plano. Una
+    /// variante de pasos que falta no se registra, así se elige otra
+    pub fn insert_sound_data(&mut self, name: &str, data: Option<Vec<u8>>) {
+        let is_footstep_variant = FOOTSTEP_VARIANT_FILES.iter().any(|(variant, _)| *variant == name);
+        match data {
+            Some(data) => {
                 self.sound_data.insert(name.to_string(), data);
-            }
-            Err(_) => {
-                // Crear datos silenciosos como fallback
+            },
+            // Crear datos silenciosos como fallback
+            None if !is_footstep_variant => {
                 self.sound_data.insert(name.to_string(), vec![0; 1024]);
-            }
+            },
+            None => {},
         }
     }
 
-    pub fn update(&mut self, delta_time: f32) {
+    /// `listener` es la posición del jugador mientras se juega; con None se
+    /// silencian los sonidos ambientales
+    pub fn update(&mut self, delta_time: f32, listener: Option<Vector2>) {
+        self.update_ambients(listener);
+
         // Actualizar timer de pasos
         if self.is_walking {
             self.footsteps_timer += delta_time;
@@ -104,6 +349,16 @@ impl AudioManager {
             self.footsteps_timer = 0.0;
         }
 
+        // Reanudación de música programada tras un SFX importante o la victoria
+        if let Some(resume) = self.pending_music_resume.as_mut() {
+            resume.remaining -= delta_time;
+            if resume.remaining <= 0.0 {
+                let music_type = resume.music_type;
+                self.pending_music_resume = None;
+                self.resume_music(music_type);
+            }
+        }
+
         // Verificar si la mÃºsica de fondo necesita reiniciarse (loop)
         if self.music_playing {
             let music_sink = self.music_sink.clone();
@@ -118,6 +373,39 @@ impl AudioManager {
         }
     }
 
+    /// Reemplaza las fuentes ambientales del nivel; los bucles de fuentes que
+    /// siguen existiendo no se reinician
+    pub fn set_ambient_sources(&mut self, sources: Vec<AmbientSource>) {
+        self.ambient_sources = sources;
+    }
+
+    fn update_ambients(&mut self, listener: Option<Vector2>) {
+        let audible = match listener {
+            Some(position) => audible_ambients(&self.ambient_sources, position, MAX_AMBIENT_SOURCES),
+            None => Vec::new(),
+        };
+
+        // Cortar los bucles que quedaron lejos o cuya fuente desapareció
+        self.ambient_loops.retain(|key, sink| {
+            let still_audible = audible.iter().any(|(source, _)| source.key() == *key);
+            if !still_audible {
+                sink.stop();
+            }
+            still_audible
+        });
+
+        for (source, volume) in audible {
+            let volume = volume * self.effective_sfx_volume();
+            if let Some(sink) = self.ambient_loops.get(&source.key()) {
+                sink.set_volume(volume);
+            } else if let Some(sink) = self.new_sink() {
+                sink.set_volume(volume);
+                sink.append(source.sound.source());
+                self.ambient_loops.insert(source.key(), sink);
+            }
+        }
+    }
+
     pub fn play_menu_music(&mut self) {
         self.play_music("menu_music", MusicType::Menu);
     }
@@ -127,6 +415,9 @@ impl AudioManager {
     }
 
     fn play_music(&mut self, music_key: &str, music_type: MusicType) {
+        // Un cambio explícito de música invalida cualquier reanudación pendiente
+        self.pending_music_resume = None;
+
         // Si ya estÃ¡ sonando la misma mÃºsica, no hacer nada
         if self.music_playing && self.current_music_type == Some(music_type) {
             return;
@@ -138,8 +429,8 @@ impl AudioManager {
             let cursor = Cursor::new(music_data.clone());
             
             if let Ok(source) = Decoder::new(cursor) {
-                if let Ok(sink) = Sink::try_new(&self.stream_handle) {
-                    sink.set_volume(self.music_volume);
+                if let Some(sink) = self.new_sink() {
+                    sink.set_volume(self.effective_music_volume());
                     sink.append(source);
                     
                     *self.music_sink.lock().unwrap() = Some(sink);
@@ -166,7 +457,7 @@ impl AudioManager {
             if let Ok(source) = Decoder::new(cursor) {
                 if let Ok(sink_option) = self.music_sink.try_lock() {
                     if let Some(ref sink) = *sink_option {
-                        sink.set_volume(self.music_volume);
+                        sink.set_volume(self.effective_music_volume());
                         sink.append(source);
                     }
                 }
@@ -175,6 +466,7 @@ impl AudioManager {
     }
 
     pub fn stop_background_music(&mut self) {
+        self.pending_music_resume = None;
         if let Ok(mut sink_option) = self.music_sink.lock() {
             if let Some(sink) = sink_option.take() {
                 sink.stop();
@@ -206,24 +498,38 @@ impl AudioManager {
         }
     }
 
-    pub fn set_music_volume(&mut self, volume: f32) {
-        self.music_volume = volume.clamp(0.0, 1.0);
-        
+    /// Toma los volúmenes y el silencio elegidos; la música sonando cambia
+    /// al momento y los efectos desde el próximo que suene
+    pub fn apply_settings(&mut self, settings: &AudioSettings) {
+        self.settings = settings.clone();
+        self.apply_music_volume();
+    }
+
+    fn apply_music_volume(&mut self) {
         if let Ok(sink_option) = self.music_sink.try_lock() {
             if let Some(ref sink) = *sink_option {
-                sink.set_volume(self.music_volume);
+                sink.set_volume(self.effective_music_volume());
             }
         }
     }
 
+    // Volúmenes que suenan de verdad: cero mientras esté silenciado
+    fn effective_music_volume(&self) -> f32 {
+        if self.settings.muted { 0.0 } else { self.settings.music_volume }
+    }
+
+    fn effective_sfx_volume(&self) -> f32 {
+        if self.settings.muted { 0.0 } else { self.settings.sfx_volume }
+    }
+
     pub fn play_sound_effect(&self, sound_name: &str) {
         if let Some(sound_data) = self.sound_data.get(sound_name) {
             let cursor = Cursor::new(sound_data.clone());
             
             if let Ok(source) = Decoder::new(cursor) {
                 // Crear un nuevo sink temporal para cada SFX
-                if let Ok(temp_sink) = Sink::try_new(&self.stream_handle) {
-                    temp_sink.set_volume(self.sfx_volume);
+                if let Some(temp_sink) = self.new_sink() {
+                    temp_sink.set_volume(self.effective_sfx_volume());
                     temp_sink.append(source);
                     temp_sink.detach(); // Importante: dejar que se reproduzca independientemente
                 }
@@ -231,25 +537,47 @@ impl AudioManager {
         }
     }
 
+    /// Reproduce un SFX como si saliera de un punto del mundo, con paneo y
+    /// atenuación respecto al jugador (ver `stereo_gains`)
+    pub fn play_positional_sound(&self, sound_name: &str, world_x: f32, world_y: f32, player: &Player) {
+        let Some(sound_data) = self.sound_data.get(sound_name) else {
+            return;
+        };
+
+        if let Ok(source) = Decoder::new(Cursor::new(sound_data.clone())) {
+            self.play_positional_source(source.convert_samples(), Vector2::new(world_x, world_y), player);
+        }
+    }
+
+    fn play_positional_source(&self, source: impl Source<Item = f32> + Send + 'static, position: Vector2, player: &Player) {
+        let (left, right) = stereo_gains(position, player);
+        if left <= 0.0 && right <= 0.0 {
+            return;
+        }
+
+        // ChannelVolume mezcla la fuente a mono y la reparte en dos canales
+        if let Some(temp_sink) = self.new_sink() {
+            temp_sink.set_volume(self.effective_sfx_volume());
+            temp_sink.append(ChannelVolume::new(source, vec![left, right]));
+            temp_sink.detach();
+        }
+    }
+
     // SFX importante que detiene mÃºsica temporalmente
     pub fn play_important_sfx(&mut self, sound_name: &str) {
+        const SFX_MUSIC_RESUME_DELAY: f32 = 3.0;
+
         self.stop_music_for_sfx();
         self.play_sound_effect(sound_name);
         
         // Programar reanudaciÃ³n de mÃºsica despuÃ©s de un delay
-        let music_sink = self.music_sink.clone();
-        std::thread::spawn(move || {
-            std::thread::sleep(Duration::from_secs(3));
-            if let Ok(sink_option) = music_sink.try_lock() {
-                if let Some(ref sink) = *sink_option {
-                    sink.play();
-                }
-            }
-        });
+        if let Some(music_type) = self.current_music_type {
+            self.schedule_music_resume(music_type, SFX_MUSIC_RESUME_DELAY);
+        }
     }
 
     pub fn set_sfx_volume(&mut self, volume: f32) {
-        self.sfx_volume = volume.clamp(0.0, 1.0);
+        self.settings.sfx_volume = volume.clamp(0.0, 1.0);
     }
 
     // Funciones especÃ­ficas de eventos del juego
@@ -257,8 +585,17 @@ impl AudioManager {
         self.play_sound_effect("key_pickup");
     }
 
-    pub fn play_trap_sound(&self) {
-        self.play_sound_effect("trap_trigger");
+    pub fn play_trap_sound(&self, x: f32, y: f32, player: &Player) {
+        self.play_positional_sound("trap_trigger", x, y, player);
+    }
+
+    // Gruñido grave sintetizado, no depende de archivos de audio
+    pub fn play_enemy_alert_sound(&self, x: f32, y: f32, player: &Player) {
+        let growl = SineWave::new(70.0)
+            .mix(SineWave::new(93.0).amplify(0.5))
+            .amplify(0.5)
+            .take_duration(Duration::from_millis(700));
+        self.play_positional_source(growl, Vector2::new(x, y), player);
     }
 
     pub fn play_damage_sound(&self) {
@@ -277,31 +614,7 @@ impl AudioManager {
 
     // Reanudar mÃºsica del juego despuÃ©s de victoria
     pub fn resume_game_music_after_victory(&mut self) {
-        std::thread::spawn({
-            let music_sink = self.music_sink.clone();
-            let stream_handle = self.stream_handle.clone();
-            let music_volume = self.music_volume;
-            let sound_data = self.sound_data.get("background_music").cloned();
-            
-            move || {
-                std::thread::sleep(Duration::from_secs(4));
-                
-                if let Some(music_data) = sound_data {
-                    let cursor = Cursor::new(music_data);
-                    
-                    if let Ok(source) = Decoder::new(cursor) {
-                        if let Ok(new_sink) = Sink::try_new(&stream_handle) {
-                            new_sink.set_volume(music_volume);
-                            new_sink.append(source);
-                            
-                            if let Ok(mut sink_option) = music_sink.lock() {
-                                *sink_option = Some(new_sink);
-                            }
-                        }
-                    }
-                }
-            }
-        });
+        self.schedule_music_resume(MusicType::Background, VICTORY_MUSIC_RESUME_DELAY);
     }
 
     // Sistema de pasos mejorado
@@ -313,6 +626,36 @@ impl AudioManager {
         }
     }
 
+    /// Al correr los pasos suenan más seguidos
+    pub fn set_sprinting(&mut self, sprinting: bool) {
+        self.footsteps_interval = if sprinting { SPRINT_FOOTSTEP_INTERVAL } else { WALK_FOOTSTEP_INTERVAL };
+    }
+
+    pub fn set_footstep_surface(&mut self, surface: FootstepSurface) {
+        self.footstep_surface = surface;
+    }
+
+    // Variante de paso para la superficie actual, distinta de la anterior si
+    // hay más de una cargada; sin ninguna se usa `footsteps`
+    fn next_footstep_clip(&mut self, roll: f32) -> &'static str {
+        let mut loaded: Vec<&'static str> = self.footstep_surface
+            .clips()
+            .iter()
+            .copied()
+            .filter(|clip| self.sound_data.contains_key(*clip))
+            .collect();
+        if loaded.len() > 1 {
+            loaded.retain(|clip| *clip != self.last_footstep_clip);
+        }
+
+        let clip = match loaded.len() {
+            0 => "footsteps",
+            len => loaded[((roll * len as f32) as usize).min(len - 1)],
+        };
+        self.last_footstep_clip = clip;
+        clip
+    }
+
     pub fn stop_walking(&mut self) {
         self.is_walking = false;
         self.footsteps_timer = 0.0;
@@ -320,21 +663,22 @@ impl AudioManager {
 
     fn play_footstep_sound(&mut self) {
         // Evitar spam de pasos
-        if self.last_footstep_time.elapsed() < Duration::from_millis(300) {
+        if self.last_footstep_time.elapsed() < Duration::from_millis(200) {
             return;
         }
         
         self.last_footstep_time = Instant::now();
 
-        if let Some(footstep_data) = self.sound_data.get("footsteps") {
+        let clip = self.next_footstep_clip(rand::random::<f32>());
+        if let Some(footstep_data) = self.sound_data.get(clip) {
             let cursor = Cursor::new(footstep_data.clone());
             
             if let Ok(source) = Decoder::new(cursor) {
                 // Variar pitch ligeramente para mÃ¡s realismo
                 let pitch_variation = 0.9 + (rand::random::<f32>() * 0.2);
-                let volume_variation = self.sfx_volume * 0.4; // Pasos mÃ¡s suaves
+                let volume_variation = self.effective_sfx_volume() * 0.4; // Pasos mÃ¡s suaves
                 
-                if let Ok(temp_sink) = Sink::try_new(&self.stream_handle) {
+                if let Some(temp_sink) = self.new_sink() {
                     // Truncar el sonido a solo 1-2 segundos
                     let adjusted_source = source
                         .amplify(volume_variation)
@@ -357,12 +701,8 @@ impl AudioManager {
         self.current_music_type
     }
 
-    pub fn get_music_volume(&self) -> f32 {
-        self.music_volume
-    }
-
     pub fn get_sfx_volume(&self) -> f32 {
-        self.sfx_volume
+        self.settings.sfx_volume
     }
 
     // Control general de audio
@@ -392,57 +732,182 @@ impl AudioManager {
         }
     }
 
-    // Para eventos del juego
-    pub fn play_game_event(&mut self, event: GameAudioEvent) {
+    // Para eventos del juego; los que tienen posición se oyen desde ella
+    pub fn play_game_event(&mut self, event: GameAudioEvent, player: &Player) {
         match event {
             GameAudioEvent::KeyPickup => self.play_key_pickup_sound(),
-            GameAudioEvent::TrapTriggered => self.play_trap_sound(),
+            GameAudioEvent::TrapTriggered { x, y } => self.play_trap_sound(x, y, player),
+            GameAudioEvent::EnemyAlerted { x, y } => self.play_enemy_alert_sound(x, y, player),
             GameAudioEvent::PlayerHurt => self.play_damage_sound(),
+            GameAudioEvent::Teleported => self.play_portal_sound(),
             GameAudioEvent::CheckpointReached => self.play_key_pickup_sound(),
             GameAudioEvent::LevelComplete => {
                 self.play_victory_sound();
-                self.schedule_music_resume();
+                self.resume_game_music_after_victory();
             }
         }
     }
 
-    // Programar reanudaciÃ³n de mÃºsica despuÃ©s de victoria
-    fn schedule_music_resume(&mut self) {
-        let music_sink = self.music_sink.clone();
-        let stream_handle = self.stream_handle.clone();
-        let music_volume = self.music_volume;
-        let sound_data = self.sound_data.get("background_music").cloned();
-        
-        std::thread::spawn(move || {
-            std::thread::sleep(Duration::from_secs(4));
-            
-            if let Some(music_data) = sound_data {
-                let cursor = Cursor::new(music_data);
-                
-                if let Ok(source) = Decoder::new(cursor) {
-                    if let Ok(new_sink) = Sink::try_new(&stream_handle) {
-                        new_sink.set_volume(music_volume);
-                        new_sink.append(source);
-                        
-                        if let Ok(mut sink_option) = music_sink.lock() {
-                            *sink_option = Some(new_sink);
-                        }
-                    }
-                }
+    // Programar reanudación de música; se cancela si la música cambia antes
+    fn schedule_music_resume(&mut self, music_type: MusicType, delay: f32) {
+        self.pending_music_resume = Some(MusicResume { remaining: delay, music_type });
+    }
+
+    fn cancel_music_resume(&mut self) {
+        self.pending_music_resume = None;
+    }
+
+    // Despausa la música si ya es la pedida; si no, la inicia
+    fn resume_music(&mut self, music_type: MusicType) {
+        if self.music_playing && self.current_music_type == Some(music_type) {
+            self.resume_music_after_sfx();
+        } else {
+            match music_type {
+                MusicType::Menu => self.play_menu_music(),
+                MusicType::Background => self.play_background_music(),
             }
-        });
+        }
     }
 
     // Forzar reanudaciÃ³n inmediata de mÃºsica de juego
     pub fn resume_background_music_now(&mut self) {
-        if !self.music_playing || self.current_music_type != Some(MusicType::Background) {
-            self.play_background_music();
-        }
+        self.cancel_music_resume();
+        self.resume_music(MusicType::Background);
     }
 }
 
 impl Drop for AudioManager {
     fn drop(&mut self) {
         self.stop_background_music();
+        for (_, sink) in self.ambient_loops.drain() {
+            sink.stop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn footsteps_pick_a_surface_variant_and_fall_back_to_the_default_clip() {
+        let maze: Maze = ["#####", "#   #", "#  t#", "#####"].iter().map(|row| row.chars().collect()).collect();
+        assert_eq!(FootstepSurface::at(&maze, Vector2::new(150.0, 150.0), 100), FootstepSurface::Floor);
+        assert_eq!(FootstepSurface::at(&maze, Vector2::new(250.0, 250.0), 100), FootstepSurface::Metal);
+        assert_eq!(FootstepSurface::at(&maze, Vector2::new(350.0, 150.0), 100), FootstepSurface::Metal);
+
+        // Sin variantes de metal cargadas se usa el paso de siempre
+        let mut audio = AudioManager::silent(&AudioSettings::default());
+        audio.insert_sound_data("footsteps", Some(vec![1]));
+        audio.insert_sound_data("footsteps_metal_1", None);
+        audio.set_footstep_surface(FootstepSurface::Metal);
+        assert_eq!(audio.next_footstep_clip(0.5), "footsteps");
+
+        // Con dos variantes nunca repite la misma dos veces seguidas
+        audio.insert_sound_data("footsteps_metal_1", Some(vec![1]));
+        audio.insert_sound_data("footsteps_metal_2", Some(vec![1]));
+        let clips: Vec<&str> = (0..6).map(|_| audio.next_footstep_clip(0.0)).collect();
+        assert!(clips.windows(2).all(|pair| pair[0] != pair[1]));
+        assert!(clips.iter().all(|clip| clip.starts_with("footsteps_metal")));
+
+        // Los demás sonidos que faltan siguen quedando como silencio
+        audio.insert_sound_data("key_pickup", None);
+        assert_eq!(audio.sound_data.get("key_pickup").map(Vec::len), Some(1024));
+    }
+
+    #[test]
+    fn every_footstep_variant_ships_with_the_game() {
+        for (name, path) in FOOTSTEP_VARIANT_FILES {
+            let data = std::fs::read(path).unwrap_or_else(|_| panic!("falta {}", path));
+            assert!(Decoder::new(Cursor::new(data)).is_ok(), "{} no se puede decodificar", name);
+        }
+    }
+
+    #[test]
+    fn music_resume_waits_for_its_timer_and_is_dropped_when_the_music_changes() {
+        let mut audio = AudioManager::silent(&AudioSettings::default());
+
+        audio.resume_game_music_after_victory();
+        audio.update(VICTORY_MUSIC_RESUME_DELAY - 0.5, None);
+        assert!(audio.pending_music_resume.is_some());
+        audio.update(1.0, None);
+        assert!(audio.pending_music_resume.is_none());
+
+        // Volver al menú detiene la música y con ella la reanudación pendiente
+        audio.resume_game_music_after_victory();
+        audio.stop_background_music();
+        assert!(audio.pending_music_resume.is_none());
+
+        audio.resume_game_music_after_victory();
+        audio.play_menu_music();
+        assert!(audio.pending_music_resume.is_none());
+    }
+
+    #[test]
+    fn silent_manager_plays_nothing() {
+        let mut audio = AudioManager::silent(&AudioSettings::default());
+        audio.insert_sound_data("background_music", None);
+        audio.play_background_music();
+        audio.play_sound_effect("background_music");
+        audio.set_ambient_sources(vec![source_at(0.0, 0.0)]);
+        audio.update(0.1, Some(Vector2::new(0.0, 0.0)));
+
+        assert!(!audio.is_music_playing());
+        assert!(audio.ambient_loops.is_empty());
+    }
+
+    fn source_at(x: f32, y: f32) -> AmbientSource {
+        AmbientSource { sound: AmbientSound::LightBuzz, x, y }
+    }
+
+    #[test]
+    fn ambient_volume_fades_with_distance() {
+        assert_eq!(ambient_volume(0.0), AMBIENT_MAX_VOLUME);
+        assert!(ambient_volume(100.0) > ambient_volume(300.0));
+        assert_eq!(ambient_volume(AMBIENT_HEARING_DISTANCE), 0.0);
+        assert_eq!(ambient_volume(AMBIENT_HEARING_DISTANCE * 3.0), 0.0);
+    }
+
+    #[test]
+    fn only_the_nearest_sources_within_range_are_audible() {
+        let sources: Vec<AmbientSource> = (0..8)
+            .map(|i| source_at(i as f32 * 100.0, 0.0))
+            .chain(std::iter::once(source_at(5000.0, 5000.0)))
+            .collect();
+
+        let audible = audible_ambients(&sources, Vector2::new(0.0, 0.0), 3);
+        let positions: Vec<f32> = audible.iter().map(|(source, _)| source.x).collect();
+        assert_eq!(positions, vec![0.0, 100.0, 200.0]);
+        assert!(audible[0].1 > audible[2].1);
+
+        let far_away = audible_ambients(&sources, Vector2::new(-5000.0, 0.0), 3);
+        assert!(far_away.is_empty());
+    }
+
+    #[test]
+    fn audio_settings_survive_a_round_trip_and_ignore_bad_values() {
+        let settings = AudioSettings { music_volume: 0.5, sfx_volume: 0.3, muted: true };
+        assert_eq!(AudioSettings::parse(&settings.to_toml()), settings);
+
+        let broken = AudioSettings::parse("music_volume = loud\nsfx_volume = 7\nmuted\n# muted = true");
+        assert_eq!(broken, AudioSettings { sfx_volume: 1.0, ..AudioSettings::default() });
+        assert_eq!(AudioSettings::parse("music_volume = NaN"), AudioSettings::default());
+    }
+
+    #[test]
+    fn positional_sounds_pan_to_their_side_and_fade_like_the_fog() {
+        // Mirando hacia +x: +y queda a la derecha de la pantalla
+        let player = Player::new_with_pos(0.0, 0.0, 0.0);
+
+        let (left, right) = stereo_gains(Vector2::new(0.0, 300.0), &player);
+        assert!(right > left && left > 0.0);
+        let (left, right) = stereo_gains(Vector2::new(0.0, -300.0), &player);
+        assert!(left > right && right > 0.0);
+
+        let (near, _) = stereo_gains(Vector2::new(200.0, 0.0), &player);
+        let (far_left, far_right) = stereo_gains(Vector2::new(800.0, 0.0), &player);
+        assert!((far_left - far_right).abs() < 1e-6);
+        assert!(near > far_left);
+        assert_eq!(stereo_gains(Vector2::new(MAX_DISTANCE, 0.0), &player), (0.0, 0.0));
     }
 }
\ No newline at end of file
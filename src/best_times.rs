@@ -0,0 +1,98 @@
+// best_times.rs - Mejores tiempos por nivel guardados entre partidas
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+pub const BEST_TIMES_FILE: &str = "assets/config/besttimes.json";
+
+/// Récord en segundos de cada nivel completado al menos una vez
+#[derive(Debug, Clone, Default)]
+pub struct BestTimes {
+    times: BTreeMap<usize, f32>,
+}
+
+impl BestTimes {
+    /// Carga los récords; un archivo ausente o con errores empieza sin récords
+    pub fn load() -> Self {
+        fs::read_to_string(BEST_TIMES_FILE)
+            .map(|text| BestTimes::parse(&text))
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        if let Some(dir) = Path::new(BEST_TIMES_FILE).parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+
+        if let Err(e) = fs::write(BEST_TIMES_FILE, self.to_json()) {
+            eprintln!("No se pudieron guardar los mejores tiempos: {}", e);
+        }
+    }
+
+    /// Lee un objeto plano `{"nivel": segundos, ...}`; ignora entradas inválidas
+    pub fn parse(text: &str) -> Self {
+        let mut best_times = BestTimes::default();
+        let body = text.trim().trim_start_matches('{').trim_end_matches('}');
+
+        for entry in body.split(',') {
+            let Some((level, seconds)) = entry.split_once(':') else {
+                continue;
+            };
+            let level = level.trim().trim_matches('"').parse::<usize>();
+            let seconds = seconds.trim().parse::<f32>();
+
+            if let (Ok(level), Ok(seconds)) = (level, seconds) {
+                if seconds.is_finite() && seconds > 0.0 {
+                    best_times.times.insert(level, seconds);
+                }
+            }
+        }
+
+        best_times
+    }
+
+    pub fn to_json(&self) -> String {
+        let entries: Vec<String> = self.times
+            .iter()
+            .map(|(level, seconds)| format!("  \"{}\": {:.3}", level, seconds))
+            .collect();
+        format!("{{\n{}\n}}\n", entries.join(",\n"))
+    }
+
+    pub fn best(&self, level: usize) -> Option<f32> {
+        self.times.get(&level).copied()
+    }
+
+    /// Guarda el tiempo si mejora el récord del nivel; devuelve si fue récord
+    pub fn record(&mut self, level: usize, seconds: f32) -> bool {
+        if self.best(level).is_some_and(|best| best <= seconds) {
+            return false;
+        }
+
+        self.times.insert(level, seconds);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_faster_times_replace_the_record_and_survive_a_round_trip() {
+        let mut best_times = BestTimes::default();
+        assert!(best_times.record(1, 83.25));
+        assert!(!best_times.record(1, 90.0));
+        assert!(best_times.record(1, 61.5));
+        assert!(best_times.record(3, 200.125));
+
+        let loaded = BestTimes::parse(&best_times.to_json());
+        assert_eq!(loaded.best(1), Some(61.5));
+        assert_eq!(loaded.best(2), None);
+        assert_eq!(loaded.best(3), Some(200.125));
+
+        let broken = BestTimes::parse("{\"1\": fast, \"x\": 3.0, \"2\": -1, \"3\": 12.5");
+        assert_eq!((broken.best(1), broken.best(2), broken.best(3)), (None, None, Some(12.5)));
+    }
+}
@@ -2,10 +2,11 @@
 
 use raylib::color::Color;
 use crate::framebuffer::Framebuffer;
-use crate::maze::Maze;
+use crate::maze::{Maze, RegionMap, TileProperties, region_at, DOOR_CLOSED};
 use crate::player::Player;
 use crate::texture_manager::TextureManager;
 use crate::sprite_manager::SpriteManager;
+use crate::translucency::{QueuedWallColumn, TranslucentKind, TranslucentQueue};
 use std::f32::consts::PI;
 
 #[derive(Debug, Clone)]
@@ -15,6 +16,15 @@ pub struct Intersect {
     pub texture_x: f32,
     pub texture_y: f32,
     pub side: WallSide,
+    pub region: Option<char>,
+    // Punto de impacto en coordenadas de mundo
+    pub impact_x: f32,
+    pub impact_y: f32,
+    // Altura de la pared tocada respecto a una normal (TileProperties::height)
+    pub height: f32,
+    // Si el borde de la cara en texture_x = 0 / 1 forma esquina interior con
+    // otra pared
+    pub corners: [bool; 2],
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -25,153 +35,368 @@ pub enum WallSide {
     West,
 }
 
+impl WallSide {
+    // Normal de la cara tocada, hacia el lado desde el que llegó el rayo
+    fn normal(self) -> (f32, f32) {
+        match self {
+            WallSide::West => (-1.0, 0.0),
+            WallSide::East => (1.0, 0.0),
+            WallSide::North => (0.0, -1.0),
+            WallSide::South => (0.0, 1.0),
+        }
+    }
+}
+
+/// Luz puntual de color (portal de salida, vidas extra) que tiñe las paredes
+/// cercanas que la miran
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PointLight {
+    pub x: f32,
+    pub y: f32,
+    pub color: Color,
+    // Distancia en unidades de mundo a la que la luz ya no llega
+    pub radius: f32,
+    // Brillo que suma en el centro (1 = duplica el color de la pared)
+    pub intensity: f32,
+}
+
+// Oclusión ambiental falsa: las paredes se oscurecen cerca de los bordes de
+// cada casilla y más en las esquinas interiores. El ancho va en fracción de
+// la cara y las fuerzas en cuánto brillo quitan justo en el borde
+pub const AO_EDGE_WIDTH: f32 = 0.15;
+pub const AO_EDGE_STRENGTH: f32 = 0.1;
+pub const AO_CORNER_STRENGTH: f32 = 0.25;
+
+/// Factor de brillo (0 a 1) de un punto de la cara según su cercanía a los
+/// bordes; se multiplica con la atenuación por distancia y por lado
+#[inline]
+pub fn ambient_occlusion(texture_x: f32, corners: [bool; 2]) -> f32 {
+    let edge_darkening = |distance_to_edge: f32, corner: bool| {
+        let closeness = (1.0 - distance_to_edge / AO_EDGE_WIDTH).max(0.0);
+        let strength = AO_EDGE_STRENGTH + if corner { AO_CORNER_STRENGTH } else { 0.0 };
+        closeness * closeness * strength
+    };
+
+    let texture_x = texture_x.clamp(0.0, 1.0);
+    1.0 - edge_darkening(texture_x, corners[0]).max(edge_darkening(1.0 - texture_x, corners[1]))
+}
+
+// Esquinas interiores de la cara `side` de la casilla (x, y): hay esquina si
+// la casilla libre frente a la cara tiene una pared al lado de ese borde
+fn inside_corners(maze: &Maze, x: i64, y: i64, side: WallSide) -> [bool; 2] {
+    let blocks = |x: i64, y: i64| {
+        if x < 0 || y < 0 {
+            return true;
+        }
+        maze.get(y as usize)
+            .and_then(|row| row.get(x as usize))
+            .is_none_or(|&cell| TileProperties::of(cell).blocks_sight)
+    };
+
+    let (normal_x, normal_y) = side.normal();
+    let (front_x, front_y) = (x + normal_x as i64, y + normal_y as i64);
+    // texture_x crece con y en las caras este/oeste y con x en las norte/sur
+    match side {
+        WallSide::West | WallSide::East => [blocks(front_x, front_y - 1), blocks(front_x, front_y + 1)],
+        WallSide::North | WallSide::South => [blocks(front_x - 1, front_y), blocks(front_x + 1, front_y)],
+    }
+}
+
+// Luces que se evalúan por columna como mucho: las más cercanas al jugador
+pub const MAX_POINT_LIGHTS: usize = 6;
+
 // Constantes de renderizado
-const MAX_DISTANCE: f32 = 1000.0;
-const STEP_SIZE: f32 = 1.5;
-const MAX_ITERATIONS: i32 = 400;
+pub const MAX_DISTANCE: f32 = 1000.0;
 const PROJECTION_PLANE_DISTANCE: f32 = 100.0;
 
-pub fn cast_ray_textured(maze: &Maze, player: &Player, angle: f32, block_size: usize) -> Intersect {
-    if maze.is_empty() {
-        return create_default_intersect();
+// Altura de una pared en unidades de mundo (igual al tamaño de bloque)
+pub const WALL_WORLD_HEIGHT: f32 = 100.0;
+
+/// Altura en pantalla de un objeto de `world_height` unidades a `distance`.
+/// Paredes y sprites usan la misma proyección para que sus tamaños cuadren.
+#[inline]
+pub fn project_height(world_height: f32, distance: f32, screen_height: u32) -> f32 {
+    let half_height = screen_height as f32 * 0.5;
+    (half_height / distance.max(1.0)) * PROJECTION_PLANE_DISTANCE * (world_height / WALL_WORLD_HEIGHT)
+}
+
+/// Fila de pantalla donde toca el suelo a `distance`: la base de las paredes,
+/// donde también se apoyan los sprites
+#[inline]
+pub fn floor_line(horizon: f32, distance: f32, screen_height: u32) -> f32 {
+    horizon + project_height(WALL_WORLD_HEIGHT, distance, screen_height) * 0.5
+}
+
+/// Borde superior sin recortar y altura en pantalla de una pared de
+/// `height_factor` veces la altura normal; todas se apoyan en el suelo, así
+/// que un muro bajo solo baja su borde superior
+#[inline]
+pub fn wall_column_span(horizon: f32, distance: f32, height_factor: f32, screen_height: u32) -> (f32, f32) {
+    let height = project_height(WALL_WORLD_HEIGHT * height_factor, distance, screen_height);
+    (floor_line(horizon, distance, screen_height) - height, height)
+}
+
+// Densidad con la que a MAX_DISTANCE queda ~90% de niebla: exp(-d * 1000) = 0.1
+pub const DEFAULT_FOG_DENSITY: f32 = std::f32::consts::LN_10 / MAX_DISTANCE;
+
+/// Niebla por distancia: paredes y sprites lejanos se funden con `color`
+#[derive(Debug, Clone, Copy)]
+pub struct FogSettings {
+    pub color: Color,
+    pub density: f32,
+}
+
+impl Default for FogSettings {
+    fn default() -> Self {
+        FogSettings {
+            color: Color::new(20, 20, 30, 255),
+            density: DEFAULT_FOG_DENSITY,
+        }
     }
+}
 
-    let maze_height = maze.len();
-    let maze_width = maze.get(0).map_or(0, |row| row.len());
-    
-    if maze_width == 0 {
-        return create_default_intersect();
+/// Lo que cambia en cada frame y comparten paredes, vidrios y sprites
+#[derive(Debug, Clone, Copy)]
+pub struct FrameContext<'a> {
+    pub fog: FogSettings,
+    // Pocas luces por frame; cada columna solo mira su punto de impacto
+    pub lights: &'a [PointLight],
+    // Segundos de juego, para el frame de las texturas animadas
+    pub animation_time: f32,
+}
+
+impl Default for FrameContext<'_> {
+    fn default() -> Self {
+        FrameContext {
+            fog: FogSettings::default(),
+            lights: &[],
+            animation_time: 0.0,
+        }
     }
+}
+
+/// Nivel que se dibuja: la rejilla con sus regiones de textura, el tamaño de
+/// casilla y cuánto están abiertas las puertas
+#[derive(Debug, Clone, Copy)]
+pub struct LevelView<'a> {
+    pub maze: &'a Maze,
+    pub regions: Option<&'a RegionMap>,
+    pub block_size: usize,
+    pub door_open: f32,
+}
 
+impl FogSettings {
+    /// Color de niebla propio de cada nivel para darle ambiente
+    pub fn for_level(level: usize) -> Self {
+        let color = match level {
+            2 => Color::new(70, 12, 12, 255),
+            3 => Color::new(40, 15, 50, 255),
+            _ => return FogSettings::default(),
+        };
+        FogSettings { color, ..FogSettings::default() }
+    }
+
+    /// Fracción del color original que sigue visible a `distance`
+    #[inline]
+    pub fn visibility(&self, distance: f32) -> f32 {
+        (-self.density * distance.max(0.0)).exp()
+    }
+
+    /// Mezcla el color hacia la niebla; conserva el alfa para el vidrio y los sprites
+    #[inline]
+    pub fn apply(&self, color: Color, distance: f32) -> Color {
+        let visibility = self.visibility(distance);
+        let mix = |c: u8, f: u8| (c as f32 * visibility + f as f32 * (1.0 - visibility)) as u8;
+        Color::new(
+            mix(color.r, self.color.r),
+            mix(color.g, self.color.g),
+            mix(color.b, self.color.b),
+            color.a,
+        )
+    }
+}
+
+/// Distancia perpendicular al plano de la cámara. Con la distancia del rayo
+/// tal cual, las paredes planas se abombarían hacia los bordes (ojo de pez).
+#[inline]
+pub fn perpendicular_distance(ray_distance: f32, ray_angle: f32, view_angle: f32) -> f32 {
+    (ray_distance * (ray_angle - view_angle).cos()).max(1.0)
+}
+
+// Recorre la rejilla con DDA: salta de línea de rejilla en línea de rejilla,
+// así que ninguna pared fina se escapa y el punto de impacto es exacto.
+// Las casillas translúcidas y los muros bajos no detienen el rayo: se guarda
+// el impacto con cada una en `translucent_hits` y se sigue hasta una pared
+// que tape la vista.
+// `door_open` (0 a 1) es cuánto se ha deslizado la hoja de las puertas.
+pub fn cast_ray_textured(
+    maze: &Maze,
+    regions: Option<&RegionMap>,
+    player: &Player,
+    angle: f32,
+    block_size: usize,
+    door_open: f32,
+    translucent_hits: &mut Vec<Intersect>,
+) -> Intersect {
     let cos_a = angle.cos();
     let sin_a = angle.sin();
-    let mut distance = STEP_SIZE;
-    
-    for _ in 0..MAX_ITERATIONS {
-        if distance > MAX_DISTANCE {
-            break;
-        }
+    let far_x = player.pos.x + MAX_DISTANCE * cos_a;
+    let far_y = player.pos.y + MAX_DISTANCE * sin_a;
 
-        let ray_x = player.pos.x + distance * cos_a;
-        let ray_y = player.pos.y + distance * sin_a;
+    // Cada fila se mide por separado más abajo: las filas cortas terminan en pared
+    if maze.iter().all(|row| row.is_empty()) || block_size == 0 {
+        return create_default_intersect(far_x, far_y);
+    }
+
+    let block = block_size as f32;
+    let mut map_x = (player.pos.x / block).floor() as i64;
+    let mut map_y = (player.pos.y / block).floor() as i64;
+    let step_x: i64 = if cos_a < 0.0 { -1 } else { 1 };
+    let step_y: i64 = if sin_a < 0.0 { -1 } else { 1 };
+
+    // Distancia recorrida por el rayo entre dos líneas de rejilla de cada eje
+    // y distancia hasta la primera de ellas
+    let delta_x = if cos_a == 0.0 { f32::INFINITY } else { (block / cos_a).abs() };
+    let delta_y = if sin_a == 0.0 { f32::INFINITY } else { (block / sin_a).abs() };
+    let mut side_dist_x = if cos_a < 0.0 {
+        (player.pos.x - map_x as f32 * block) / -cos_a
+    } else {
+        ((map_x + 1) as f32 * block - player.pos.x) / cos_a.max(f32::MIN_POSITIVE)
+    };
+    let mut side_dist_y = if sin_a < 0.0 {
+        (player.pos.y - map_y as f32 * block) / -sin_a
+    } else {
+        ((map_y + 1) as f32 * block - player.pos.y) / sin_a.max(f32::MIN_POSITIVE)
+    };
+
+    loop {
+        // Cruzar la línea de rejilla más cercana
+        let crossed_vertical_line = side_dist_x < side_dist_y;
+        let distance = if crossed_vertical_line {
+            let distance = side_dist_x;
+            side_dist_x += delta_x;
+            map_x += step_x;
+            distance
+        } else {
+            let distance = side_dist_y;
+            side_dist_y += delta_y;
+            map_y += step_y;
+            distance
+        };
 
-        // Verificación de bounds
-        if ray_x < 0.0 || ray_y < 0.0 {
-            return create_wall_intersect(distance);
+        if distance > MAX_DISTANCE {
+            return create_default_intersect(far_x, far_y);
         }
 
-        let grid_x = (ray_x / block_size as f32) as usize;
-        let grid_y = (ray_y / block_size as f32) as usize;
+        let hit_x = player.pos.x + distance * cos_a;
+        let hit_y = player.pos.y + distance * sin_a;
 
-        if grid_y >= maze_height || grid_x >= maze_width {
-            return create_wall_intersect(distance);
+        // Fuera del laberinto cuenta como pared
+        if map_x < 0 || map_y < 0 {
+            return create_wall_intersect(distance, hit_x, hit_y);
         }
+        let Some(row) = maze.get(map_y as usize) else {
+            return create_wall_intersect(distance, hit_x, hit_y);
+        };
+        let Some(&cell) = row.get(map_x as usize) else {
+            return create_wall_intersect(distance, hit_x, hit_y);
+        };
 
-        // Acceso seguro a la celda
-        let current_cell = maze.get(grid_y)
-            .and_then(|row| row.get(grid_x))
-            .copied()
-            .unwrap_or('#');
-
-        if is_wall_cell(current_cell) {
-            // Calcular coordenadas de textura
-            let cell_x = ray_x % block_size as f32;
-            let cell_y = ray_y % block_size as f32;
-            
-            let norm_x = (cell_x / block_size as f32).clamp(0.0, 1.0);
-            let norm_y = (cell_y / block_size as f32).clamp(0.0, 1.0);
-            
-            let side = determine_wall_side(norm_x, norm_y);
-            let (texture_x, texture_y) = calculate_texture_coordinates(side, norm_x, norm_y);
-
-            return Intersect {
-                distance,
-                impact: current_cell,
-                texture_x: texture_x.clamp(0.0, 1.0),
-                texture_y: texture_y.clamp(0.0, 1.0),
-                side,
-            };
+        let tile = TileProperties::of(cell);
+        if !tile.blocks_sight && !tile.translucent && !tile.is_low_wall() {
+            continue;
         }
 
-        distance += STEP_SIZE;
-    }
+        // Cada caso de DDA corresponde a una cara: al cruzar una línea vertical
+        // se toca la cara oeste o este de la casilla, si no la norte o sur
+        let (side, texture_x) = if crossed_vertical_line {
+            let side = if step_x > 0 { WallSide::West } else { WallSide::East };
+            (side, (hit_y / block).rem_euclid(1.0))
+        } else {
+            let side = if step_y > 0 { WallSide::North } else { WallSide::South };
+            (side, (hit_x / block).rem_euclid(1.0))
+        };
 
-    create_default_intersect()
+        // Puerta abriéndose: el rayo pasa por el hueco y la hoja se ve corrida
+        let texture_x = if cell == DOOR_CLOSED {
+            let slid = texture_x - door_open;
+            if slid < 0.0 {
+                continue;
+            }
+            slid
+        } else {
+            texture_x
+        };
+
+        let hit = Intersect {
+            distance,
+            impact: cell,
+            texture_x,
+            texture_y: 0.0,
+            side,
+            region: regions.and_then(|regions| region_at(regions, map_x as usize, map_y as usize)),
+            impact_x: hit_x,
+            impact_y: hit_y,
+            height: tile.height,
+            corners: inside_corners(maze, map_x, map_y, side),
+        };
+
+        if tile.blocks_sight {
+            return hit;
+        }
+        translucent_hits.push(hit);
+    }
 }
 
 #[inline]
-fn create_default_intersect() -> Intersect {
-    Intersect {
-        distance: MAX_DISTANCE,
-        impact: '#',
-        texture_x: 0.0,
-        texture_y: 0.0,
-        side: WallSide::North,
-    }
+fn create_default_intersect(impact_x: f32, impact_y: f32) -> Intersect {
+    create_wall_intersect(MAX_DISTANCE, impact_x, impact_y)
 }
 
 #[inline]
-fn create_wall_intersect(distance: f32) -> Intersect {
+fn create_wall_intersect(distance: f32, impact_x: f32, impact_y: f32) -> Intersect {
     Intersect {
         distance,
         impact: '#',
         texture_x: 0.0,
         texture_y: 0.0,
         side: WallSide::North,
-    }
-}
-
-#[inline]
-fn is_wall_cell(cell: char) -> bool {
-    matches!(cell, '#' | '+' | '-' | '|' | 'r' | 'b' | 'g')
-}
-
-#[inline]
-fn determine_wall_side(norm_x: f32, norm_y: f32) -> WallSide {
-    let distances = [
-        (norm_x, WallSide::West),
-        (1.0 - norm_x, WallSide::East),
-        (norm_y, WallSide::North),
-        (1.0 - norm_y, WallSide::South),
-    ];
-    
-    distances.iter()
-        .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap())
-        .map(|(_, side)| *side)
-        .unwrap_or(WallSide::North)
-}
-
-#[inline]
-fn calculate_texture_coordinates(side: WallSide, norm_x: f32, norm_y: f32) -> (f32, f32) {
-    match side {
-        WallSide::North | WallSide::South => (norm_x, norm_y),
-        WallSide::East | WallSide::West => (norm_y, norm_x),
+        region: None,
+        impact_x,
+        impact_y,
+        height: 1.0,
+        corners: [false; 2],
     }
 }
 
 // Función principal optimizada con sprites
 pub fn render_world_with_sprites(
     framebuffer: &mut Framebuffer,
-    maze: &Maze,
+    level: &LevelView,
     texture_manager: &TextureManager,
     sprite_manager: &mut SpriteManager,
-    block_size: usize,
     player: &Player,
+    frame: &FrameContext,
 ) {
-    if maze.is_empty() {
+    if level.maze.is_empty() {
         return;
     }
 
     let screen_width = framebuffer.width;
     let screen_height = framebuffer.height;
-    let half_height = screen_height as f32 * 0.5;
+    // El horizonte se desplaza con la mirada vertical del jugador
+    let horizon = player.horizon(screen_height);
 
     // Renderizar cielo y suelo
-    render_sky_and_floor(framebuffer, half_height);
+    render_sky_and_floor(framebuffer, horizon);
 
     // Vector para z-buffer (distancias de paredes)
     let mut wall_distances = vec![MAX_DISTANCE; screen_width as usize];
 
+    // Sprites y vidrios se componen juntos tras las paredes opacas
+    let mut translucent_queue = TranslucentQueue::new();
+    let mut glass_hits = Vec::new();
+
     // Calcular incremento de ángulo
     let angle_increment = player.fov / screen_width as f32;
     let start_angle = player.a - player.fov * 0.5;
@@ -179,29 +404,51 @@ pub fn render_world_with_sprites(
     // Renderizar paredes
     for column in 0..screen_width {
         let ray_angle = start_angle + column as f32 * angle_increment;
-        let intersect = cast_ray_textured(maze, player, ray_angle, block_size);
+        glass_hits.clear();
+        let intersect = cast_ray_textured(level.maze, level.regions, player, ray_angle, level.block_size, level.door_open, &mut glass_hits);
         
-        let distance = intersect.distance.max(1.0);
+        // El z-buffer guarda la misma distancia corregida que usan los sprites
+        let distance = perpendicular_distance(intersect.distance, ray_angle, player.a);
         wall_distances[column as usize] = distance;
 
         // Calcular altura de la columna de pared
-        let wall_height = (half_height / distance) * PROJECTION_PLANE_DISTANCE;
-        let wall_top = ((half_height - wall_height * 0.5).max(0.0)) as u32;
-        let wall_bottom = ((half_height + wall_height * 0.5).min(screen_height as f32)) as u32;
-
-        render_wall_column(
-            framebuffer,
-            texture_manager,
-            column,
-            wall_top,
-            wall_bottom,
-            &intersect,
-            distance,
-        );
+        let span = wall_column_span(horizon, distance, intersect.height, screen_height);
+
+        render_wall_column(framebuffer, texture_manager, column, span, &intersect, distance, frame);
+
+        // Vidrios y muros bajos se pintan luego, por orden de profundidad
+        for glass in &glass_hits {
+            let glass_distance = perpendicular_distance(glass.distance, ray_angle, player.a);
+            let (top, height) = wall_column_span(horizon, glass_distance, glass.height, screen_height);
+
+            translucent_queue.push(glass_distance, TranslucentKind::WallColumn(QueuedWallColumn {
+                column,
+                top,
+                height,
+                impact: glass.impact,
+                region: glass.region,
+                texture_x: glass.texture_x,
+                brightness: calculate_distance_attenuation(glass_distance)
+                    * calculate_side_attenuation(glass.side)
+                    * ambient_occlusion(glass.texture_x, glass.corners),
+                opaque: !TileProperties::of(glass.impact).translucent,
+            }));
+        }
     }
 
-    // Renderizar sprites usando z-buffer
-    sprite_manager.render_sprites(framebuffer, player, &wall_distances);
+    // Encolar sprites (con z-buffer de paredes opacas) y componer de atrás hacia adelante
+    sprite_manager.queue_visible_sprites(framebuffer, player, &wall_distances, &mut translucent_queue);
+
+    for item in translucent_queue.drain_back_to_front() {
+        match item.kind {
+            TranslucentKind::Sprite(sprite_index) => {
+                sprite_manager.render_queued_sprite(framebuffer, player, sprite_index, item.distance, frame);
+            },
+            TranslucentKind::WallColumn(wall) => {
+                render_queued_wall_column(framebuffer, texture_manager, &wall, item.distance, frame);
+            },
+        }
+    }
 }
 
 // Función optimizada sin sprites
@@ -218,42 +465,33 @@ pub fn render_world_textured(
 
     let screen_width = framebuffer.width;
     let screen_height = framebuffer.height;
-    let half_height = screen_height as f32 * 0.5;
+    let horizon = player.horizon(screen_height);
 
-    render_sky_and_floor(framebuffer, half_height);
+    render_sky_and_floor(framebuffer, horizon);
 
     let angle_increment = player.fov / screen_width as f32;
     let start_angle = player.a - player.fov * 0.5;
 
     for column in 0..screen_width {
         let ray_angle = start_angle + column as f32 * angle_increment;
-        let intersect = cast_ray_textured(maze, player, ray_angle, block_size);
+        let intersect = cast_ray_textured(maze, None, player, ray_angle, block_size, 0.0, &mut Vec::new());
         
-        let distance = intersect.distance.max(1.0);
-        let wall_height = (half_height / distance) * PROJECTION_PLANE_DISTANCE;
-        let wall_top = ((half_height - wall_height * 0.5).max(0.0)) as u32;
-        let wall_bottom = ((half_height + wall_height * 0.5).min(screen_height as f32)) as u32;
-
-        render_wall_column(
-            framebuffer,
-            texture_manager,
-            column,
-            wall_top,
-            wall_bottom,
-            &intersect,
-            distance,
-        );
+        let distance = perpendicular_distance(intersect.distance, ray_angle, player.a);
+        let span = wall_column_span(horizon, distance, intersect.height, screen_height);
+
+        render_wall_column(framebuffer, texture_manager, column, span, &intersect, distance, &FrameContext::default());
     }
 }
 
 #[inline]
-fn render_sky_and_floor(framebuffer: &mut Framebuffer, half_height: f32) {
+fn render_sky_and_floor(framebuffer: &mut Framebuffer, horizon: f32) {
     let screen_height = framebuffer.height;
-    let half_height_u32 = half_height as u32;
+    let horizon_u32 = (horizon.max(0.0) as u32).min(screen_height);
+    let floor_span = (screen_height as f32 - horizon).max(1.0);
 
     // Renderizar cielo (parte superior)
-    for y in 0..half_height_u32.min(screen_height) {
-        let depth_factor = y as f32 / half_height;
+    for y in 0..horizon_u32 {
+        let depth_factor = y as f32 / horizon.max(1.0);
         let sky_color = Color::new(
             (10.0 + depth_factor * 15.0) as u8,
             (10.0 + depth_factor * 15.0) as u8,
@@ -268,8 +506,8 @@ fn render_sky_and_floor(framebuffer: &mut Framebuffer, half_height: f32) {
     }
 
     // Renderizar suelo (parte inferior)
-    for y in half_height_u32..screen_height {
-        let depth_factor = (y as f32 - half_height) / half_height;
+    for y in horizon_u32..screen_height {
+        let depth_factor = (y as f32 - horizon) / floor_span;
         let floor_color = Color::new(
             (40.0 + depth_factor * 60.0) as u8,
             (30.0 + depth_factor * 45.0) as u8,
@@ -284,45 +522,88 @@ fn render_sky_and_floor(framebuffer: &mut Framebuffer, half_height: f32) {
     }
 }
 
+// Rango visible de una columna cuyo borde superior sin recortar es `top`
+#[inline]
+fn visible_rows(top: f32, height: f32, screen_height: u32) -> std::ops::Range<u32> {
+    let start = top.max(0.0) as u32;
+    let end = (top + height).clamp(0.0, screen_height as f32) as u32;
+    start..end.max(start)
+}
+
+// `wall_top` puede quedar fuera de pantalla: la textura se mapea sobre la
+// altura completa de la pared y solo se dibujan las filas visibles
 #[inline]
 fn render_wall_column(
     framebuffer: &mut Framebuffer,
     texture_manager: &TextureManager,
     column: u32,
-    wall_top: u32,
-    wall_bottom: u32,
+    (wall_top, wall_height): (f32, f32),
     intersect: &Intersect,
     distance: f32,
+    frame: &FrameContext,
 ) {
-    if wall_bottom <= wall_top || wall_top >= framebuffer.height {
+    let rows = visible_rows(wall_top, wall_height, framebuffer.height);
+    if rows.is_empty() {
         return;
     }
 
-    let column_height = (wall_bottom - wall_top) as f32;
-    let texture_y_step = 1.0 / column_height.max(1.0);
-
     // Calcular factores de iluminación
     let distance_attenuation = calculate_distance_attenuation(distance);
     let side_attenuation = calculate_side_attenuation(intersect.side);
-    let final_brightness = distance_attenuation * side_attenuation;
+    let occlusion = ambient_occlusion(intersect.texture_x, intersect.corners);
+    let final_brightness = distance_attenuation * side_attenuation * occlusion;
+    // Las esquinas oscurecen también la luz de color que reciben
+    let tint = point_light_tint(frame.lights, intersect.impact_x, intersect.impact_y, intersect.side)
+        .map(|channel| channel * occlusion);
 
     // Renderizar cada pixel de la columna
-    for y in wall_top..wall_bottom.min(framebuffer.height) {
-        let texture_y = (y - wall_top) as f32 * texture_y_step;
+    for y in rows {
+        let texture_y = wall_texture_y(y, wall_top, wall_height);
 
-        let base_color = texture_manager.get_wall_color_textured(
+        let base_color = texture_manager.get_wall_color_in_region(
             intersect.impact,
+            intersect.region,
             intersect.texture_x,
             texture_y,
+            frame.animation_time,
         );
 
-        let final_color = apply_lighting(base_color, final_brightness);
+        let final_color = frame.fog.apply(apply_colored_lighting(base_color, final_brightness, tint), distance);
         
         framebuffer.set_current_color(final_color);
         framebuffer.set_pixel(column, y);
     }
 }
 
+/// Coordenada vertical de textura respecto a la altura proyectada completa,
+/// así una pared más alta que la pantalla se recorta en vez de comprimirse
+#[inline]
+fn wall_texture_y(y: u32, wall_top: f32, wall_height: f32) -> f32 {
+    ((y as f32 - wall_top) / wall_height.max(1.0)).clamp(0.0, 1.0)
+}
+
+// Columna de vidrio mezclada sobre lo que ya hay en pantalla, o de muro bajo
+// pintada encima sin mezclar
+fn render_queued_wall_column(
+    framebuffer: &mut Framebuffer,
+    texture_manager: &TextureManager,
+    wall: &QueuedWallColumn,
+    distance: f32,
+    frame: &FrameContext,
+) {
+    for y in visible_rows(wall.top, wall.height, framebuffer.height) {
+        let texture_y = wall_texture_y(y, wall.top, wall.height);
+        let base_color = texture_manager.get_wall_color_in_region(wall.impact, wall.region, wall.texture_x, texture_y, frame.animation_time);
+
+        framebuffer.set_current_color(frame.fog.apply(apply_lighting(base_color, wall.brightness), distance));
+        if wall.opaque {
+            framebuffer.set_pixel(wall.column, y);
+        } else {
+            framebuffer.blend_pixel(wall.column, y);
+        }
+    }
+}
+
 #[inline]
 fn calculate_distance_attenuation(distance: f32) -> f32 {
     let max_distance = 600.0;
@@ -339,6 +620,38 @@ fn calculate_side_attenuation(side: WallSide) -> f32 {
     }
 }
 
+/// Brillo extra por canal que dejan las luces en un punto de pared. Solo
+/// cuentan las que están delante de la cara, así no atraviesan los muros
+fn point_light_tint(lights: &[PointLight], hit_x: f32, hit_y: f32, side: WallSide) -> [f32; 3] {
+    let (normal_x, normal_y) = side.normal();
+    let mut tint = [0.0; 3];
+
+    for light in lights {
+        let (dx, dy) = (light.x - hit_x, light.y - hit_y);
+        let distance = (dx * dx + dy * dy).sqrt();
+        if distance >= light.radius || dx * normal_x + dy * normal_y <= 0.0 {
+            continue;
+        }
+
+        let falloff = (1.0 - distance / light.radius).powi(2) * light.intensity;
+        tint[0] += falloff * light.color.r as f32 / 255.0;
+        tint[1] += falloff * light.color.g as f32 / 255.0;
+        tint[2] += falloff * light.color.b as f32 / 255.0;
+    }
+
+    tint
+}
+
+#[inline]
+fn apply_colored_lighting(color: Color, brightness: f32, tint: [f32; 3]) -> Color {
+    Color::new(
+        ((color.r as f32) * (brightness + tint[0])).min(255.0) as u8,
+        ((color.g as f32) * (brightness + tint[1])).min(255.0) as u8,
+        ((color.b as f32) * (brightness + tint[2])).min(255.0) as u8,
+        color.a,
+    )
+}
+
 #[inline]
 fn apply_lighting(color: Color, brightness: f32) -> Color {
     Color::new(
@@ -377,7 +690,7 @@ pub fn render_world_objects(
 
 #[inline]
 fn is_object_cell(cell: char) -> bool {
-    matches!(cell, 'k' | 'c' | 'l' | 't')
+    matches!(cell, 'k' | 'c' | 'l' | 't' | 'p' | 'x')
 }
 
 fn render_simple_sprite(
@@ -448,11 +761,17 @@ fn render_sprite_shape(
     let final_color = apply_lighting(base_color, brightness);
     framebuffer.set_current_color(final_color);
     
-    let half_size = size as i32 / 2;
-    let start_x = (center_x - half_size).max(0) as u32;
-    let end_x = (center_x + half_size).min(framebuffer.width as i32) as u32;
-    let start_y = (center_y - half_size).max(0) as u32;
-    let end_y = (center_y + half_size).min(framebuffer.height as i32) as u32;
+    // Recortar en i32 antes de convertir: un centro fuera de pantalla no debe
+    // producir rangos invertidos ni valores negativos convertidos a u32
+    let half_size = (size.min(1 << 20) / 2) as i32;
+    let start_x = center_x.saturating_sub(half_size).clamp(0, framebuffer.width as i32) as u32;
+    let end_x = center_x.saturating_add(half_size).clamp(0, framebuffer.width as i32) as u32;
+    let start_y = center_y.saturating_sub(half_size).clamp(0, framebuffer.height as i32) as u32;
+    let end_y = center_y.saturating_add(half_size).clamp(0, framebuffer.height as i32) as u32;
+    
+    if sprite_type != 'l' && (start_x >= end_x || start_y >= end_y) {
+        return;
+    }
     
     match sprite_type {
         'k' => render_key_shape(framebuffer, start_x, start_y, end_x, end_y),
@@ -470,11 +789,17 @@ fn get_sprite_color(sprite_type: char) -> Color {
         'c' => Color::CYAN,
         'l' => Color::PURPLE,
         't' => Color::RED,
+        'p' => Color::LIME,
+        'x' => Color::SKYBLUE,
         _ => Color::WHITE,
     }
 }
 
 fn render_key_shape(framebuffer: &mut Framebuffer, start_x: u32, start_y: u32, end_x: u32, end_y: u32) {
+    if start_x >= end_x || start_y >= end_y {
+        return;
+    }
+    
     let mid_y = (start_y + end_y) / 2;
     let quarter_x = start_x + (end_x - start_x) / 4;
     
@@ -531,13 +856,17 @@ fn render_circle_shape(framebuffer: &mut Framebuffer, center_x: i32, center_y: i
 }
 
 fn render_triangle_shape(framebuffer: &mut Framebuffer, start_x: u32, start_y: u32, end_x: u32, end_y: u32) {
+    if start_x >= end_x || start_y >= end_y {
+        return;
+    }
+    
     let mid_x = (start_x + end_x) / 2;
     let height = end_y - start_y;
     
     for y in start_y..end_y {
         let row_progress = (y - start_y) as f32 / height as f32;
         let row_width = ((end_x - start_x) as f32 * (1.0 - row_progress)) as u32;
-        let row_start = mid_x - row_width / 2;
+        let row_start = mid_x.saturating_sub(row_width / 2);
         let row_end = mid_x + row_width / 2;
         
         for x in row_start..row_end {
@@ -558,7 +887,7 @@ fn render_square_shape(framebuffer: &mut Framebuffer, start_x: u32, start_y: u32
 
 // Funciones de compatibilidad
 pub fn cast_ray_safe(maze: &Maze, player: &Player, angle: f32, block_size: usize) -> Intersect {
-    cast_ray_textured(maze, player, angle, block_size)
+    cast_ray_textured(maze, None, player, angle, block_size, 0.0, &mut Vec::new())
 }
 
 pub fn render_world_safe(
@@ -569,4 +898,253 @@ pub fn render_world_safe(
     player: &Player,
 ) {
     render_world_textured(framebuffer, maze, texture_manager, block_size, player);
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn maze(rows: &[&str]) -> Maze {
+        rows.iter().map(|row| row.chars().collect()).collect()
+    }
+
+    fn cast(maze: &Maze, x: f32, y: f32, angle: f32) -> (Intersect, Vec<Intersect>) {
+        let player = Player::new_with_pos(x, y, angle);
+        let mut glass = Vec::new();
+        let hit = cast_ray_textured(maze, None, &player, angle, 100, 0.0, &mut glass);
+        (hit, glass)
+    }
+
+    #[test]
+    fn dda_hits_the_exact_wall_face_and_texture_coordinate() {
+        let room = maze(&["#####", "#   #", "#   #", "#####"]);
+
+        // Hacia el este desde x = 150 la pared empieza en x = 400
+        let (hit, _) = cast(&room, 150.0, 125.0, 0.0);
+        assert!((hit.distance - 250.0).abs() < 1e-3);
+        assert!(matches!(hit.side, WallSide::West));
+        assert!((hit.texture_x - 0.25).abs() < 1e-4);
+
+        // Hacia el norte la pared de arriba termina en y = 100
+        let (hit, _) = cast(&room, 130.0, 250.0, -PI / 2.0);
+        assert!((hit.distance - 150.0).abs() < 1e-3);
+        assert!(matches!(hit.side, WallSide::South));
+        assert!((hit.texture_x - 0.3).abs() < 1e-4);
+    }
+
+    #[test]
+    fn fog_reaches_ninety_percent_at_max_distance() {
+        let fog = FogSettings::for_level(2);
+        assert!((fog.visibility(MAX_DISTANCE) - 0.1).abs() < 1e-3);
+
+        let wall = Color::new(200, 200, 200, 128);
+        assert_eq!(fog.apply(wall, 0.0), wall);
+        let far = fog.apply(wall, 100_000.0);
+        assert_eq!((far.r, far.g, far.b, far.a), (fog.color.r, fog.color.g, fog.color.b, 128));
+    }
+
+    #[test]
+    fn tall_walls_are_cropped_instead_of_squashed() {
+        // Pared de 3000 px en pantalla de 600: sólo se ve la franja central
+        let wall_top = 300.0 - 1500.0;
+        assert!((wall_texture_y(0, wall_top, 3000.0) - 0.4).abs() < 1e-4);
+        assert!((wall_texture_y(300, wall_top, 3000.0) - 0.5).abs() < 1e-4);
+        assert!((wall_texture_y(599, wall_top, 3000.0) - 0.5997).abs() < 1e-3);
+    }
+
+    #[test]
+    fn low_walls_are_seen_over_and_tall_walls_rise_from_the_same_floor() {
+        // El rayo pasa sobre el muro bajo y termina en la pared alta de detrás
+        let room = maze(&["######", "# w  W", "######"]);
+        let (hit, over) = cast(&room, 150.0, 150.0, 0.0);
+        assert_eq!((hit.impact, hit.height), ('W', 2.0));
+        assert!((hit.distance - 350.0).abs() < 1e-3);
+        assert_eq!(over.len(), 1);
+        assert_eq!((over[0].impact, over[0].height), ('w', 0.5));
+        assert!((over[0].distance - 50.0).abs() < 1e-3);
+
+        // Una pared normal ocupa lo mismo de siempre, centrada en el horizonte
+        let (top, height) = wall_column_span(300.0, 200.0, 1.0, 600);
+        assert!((top - 225.0).abs() < 1e-3 && (height - 150.0).abs() < 1e-3);
+
+        // El muro bajo y la pared alta comparten la base, solo cambia el borde superior
+        let floor = floor_line(300.0, 200.0, 600);
+        let (low_top, low_height) = wall_column_span(300.0, 200.0, 0.5, 600);
+        let (tall_top, tall_height) = wall_column_span(300.0, 200.0, 2.0, 600);
+        assert!((low_top + low_height - floor).abs() < 1e-3 && (low_top - 300.0).abs() < 1e-3);
+        assert!((tall_top + tall_height - floor).abs() < 1e-3 && (tall_top - 75.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn wall_hits_carry_the_region_of_the_cell_they_hit() {
+        let room = maze(&["bbbbb", "b   b", "bbbbb"]);
+        let regions = maze(&[".....", ".....", ".222."]);
+        let player = Player::new_with_pos(150.0, 150.0, 0.0);
+        let mut glass = Vec::new();
+
+        // Hacia el sur cae en la región 2, hacia el norte en ninguna
+        let south = cast_ray_textured(&room, Some(&regions), &player, PI / 2.0, 100, 0.0, &mut glass);
+        let north = cast_ray_textured(&room, Some(&regions), &player, -PI / 2.0, 100, 0.0, &mut glass);
+        assert_eq!((south.impact, south.region), ('b', Some('2')));
+        assert_eq!((north.impact, north.region), ('b', None));
+
+        // Sin mapa de regiones ninguna pared tiene región
+        let plain = cast_ray_textured(&room, None, &player, PI / 2.0, 100, 0.0, &mut glass);
+        assert_eq!(plain.region, None);
+    }
+
+    #[test]
+    fn flat_wall_has_the_same_depth_across_the_fov() {
+        let room = maze(&["#######", "#     #", "#     #", "#     #", "#######"]);
+
+        for offset in [-PI / 12.0, -PI / 20.0, 0.0, PI / 16.0, PI / 12.0] {
+            let (hit, _) = cast(&room, 150.0, 250.0, offset);
+            let depth = perpendicular_distance(hit.distance, offset, 0.0);
+            assert!((depth - 450.0).abs() < 1e-2, "ángulo {}: {}", offset, depth);
+        }
+    }
+
+    #[test]
+    fn short_rows_of_a_jagged_maze_end_in_a_wall() {
+        let jagged = maze(&["########", "#     ", "#      #", "###"]);
+
+        // La fila 1 termina en x = 600 y la 3 solo cubre las tres primeras casillas
+        let (hit, _) = cast(&jagged, 150.0, 150.0, 0.0);
+        assert!((hit.distance - 450.0).abs() < 1e-3);
+        let (hit, _) = cast(&jagged, 150.0, 250.0, 0.0);
+        assert!((hit.distance - 550.0).abs() < 1e-3);
+        let (hit, _) = cast(&jagged, 450.0, 250.0, PI / 2.0);
+        assert!((hit.distance - 50.0).abs() < 1e-3);
+
+        // Ningún ángulo sale del laberinto ni se queda sin pared
+        for step in 0..64 {
+            let angle = step as f32 * PI / 32.0;
+            for (x, y) in [(150.0, 150.0), (550.0, 150.0), (650.0, 250.0)] {
+                let (hit, _) = cast(&jagged, x, y, angle);
+                assert!(hit.distance < MAX_DISTANCE, "({}, {}) a {}", x, y, angle);
+            }
+        }
+
+        // Las colisiones del jugador usan el mismo largo por fila
+        assert!(crate::player::can_move_to_safe(550.0, 150.0, &jagged, 100, 10.0));
+        assert!(!crate::player::can_move_to_safe(650.0, 150.0, &jagged, 100, 10.0));
+        assert!(crate::player::can_move_to_safe(650.0, 250.0, &jagged, 100, 10.0));
+    }
+
+    #[test]
+    fn grazing_rays_do_not_slip_through_wall_corners() {
+        // Dos paredes que solo se tocan en una esquina en (200, 200)
+        let corner = maze(&["#####", "# # #", "#  ##", "#####"]);
+        let angle = (PI / 4.0) - 1e-4;
+
+        let (hit, _) = cast(&corner, 150.0, 150.0, angle);
+        assert!(hit.distance < 75.0, "el rayo atravesó la esquina: {}", hit.distance);
+    }
+
+    #[test]
+    fn glass_is_recorded_once_and_far_rays_use_the_default() {
+        let hall = maze(&["#########", "#  v    #", "#########"]);
+        let (hit, glass) = cast(&hall, 150.0, 150.0, 0.0);
+        assert_eq!(glass.len(), 1);
+        assert!((glass[0].distance - 150.0).abs() < 1e-3);
+        assert!((hit.distance - 650.0).abs() < 1e-3);
+
+        let open = maze(&[" "; 1]);
+        let (hit, _) = cast(&open, 50.0, 50.0, 0.0);
+        assert_eq!(hit.distance, 50.0);
+
+        let long: Vec<String> = vec!["#".to_string() + &" ".repeat(30) + "#"; 3];
+        let long: Vec<&str> = long.iter().map(|row| row.as_str()).collect();
+        let (hit, _) = cast(&maze(&long), 150.0, 150.0, 0.0);
+        assert_eq!(hit.distance, MAX_DISTANCE);
+    }
+
+    #[test]
+    fn point_lights_tint_the_wall_faces_that_look_at_them() {
+        let portal = PointLight { x: 150.0, y: 150.0, color: Color::new(0, 255, 0, 255), radius: 300.0, intensity: 1.0 };
+
+        // La cara oeste de una pared en x = 300 mira hacia la luz
+        let near = point_light_tint(&[portal], 300.0, 150.0, WallSide::West);
+        assert_eq!(near[0], 0.0);
+        assert!((near[1] - 0.25).abs() < 1e-4, "{:?}", near);
+        let farther = point_light_tint(&[portal], 300.0, 350.0, WallSide::West);
+        assert!(farther[1] > 0.0 && farther[1] < near[1]);
+
+        // Detrás del muro, fuera del radio o sin luces no se suma nada
+        assert_eq!(point_light_tint(&[portal], 300.0, 150.0, WallSide::East), [0.0; 3]);
+        assert_eq!(point_light_tint(&[portal], 500.0, 150.0, WallSide::West), [0.0; 3]);
+        assert_eq!(point_light_tint(&[], 300.0, 150.0, WallSide::West), [0.0; 3]);
+
+        let wall = Color::new(100, 100, 100, 255);
+        assert_eq!(apply_colored_lighting(wall, 0.5, near), Color::new(50, 75, 50, 255));
+        assert_eq!(apply_colored_lighting(wall, 0.5, [0.0; 3]), apply_lighting(wall, 0.5));
+    }
+
+    #[test]
+    fn walls_darken_towards_cell_edges_and_more_in_inside_corners() {
+        let room = maze(&["#####", "#   #", "#   #", "#####"]);
+
+        // La cara este de la sala toca la pared de arriba (esquina) pero abajo sigue libre
+        let (hit, _) = cast(&room, 150.0, 125.0, 0.0);
+        assert_eq!(hit.corners, [true, false]);
+
+        assert_eq!(ambient_occlusion(0.5, [true, true]), 1.0);
+        let plain_edge = ambient_occlusion(0.01, [false, false]);
+        let corner_edge = ambient_occlusion(0.01, [true, false]);
+        assert!(plain_edge < 1.0 && corner_edge < plain_edge);
+        assert!(corner_edge > 0.6, "el efecto debe ser sutil");
+        assert_eq!(ambient_occlusion(0.99, [true, false]), plain_edge);
+    }
+
+    /// Pixeles que dejaron de estar en negro
+    fn lit_pixels(framebuffer: &mut Framebuffer) -> Vec<(u32, u32)> {
+        let mut cells = Vec::new();
+        for y in 0..framebuffer.height {
+            for x in 0..framebuffer.width {
+                if framebuffer.get_pixel(x, y) != Some(Color::BLACK) {
+                    cells.push((x, y));
+                }
+            }
+        }
+        cells
+    }
+
+    #[test]
+    fn triangle_at_screen_edge_is_clipped_to_the_frame() {
+        // Pegado al borde izquierdo: solo queda la mitad derecha
+        let mut framebuffer = Framebuffer::new(64, 48);
+        render_sprite_shape(&mut framebuffer, 0, 24, 30, 't', 1.0);
+        let cells = lit_pixels(&mut framebuffer);
+        assert!(!cells.is_empty());
+        assert!(cells.iter().all(|&(x, y)| x < 15 && (9..39).contains(&y)));
+
+        // En la esquina inferior derecha: solo el cuarto que cae dentro
+        let mut framebuffer = Framebuffer::new(64, 48);
+        render_sprite_shape(&mut framebuffer, 63, 47, 30, 't', 1.0);
+        let cells = lit_pixels(&mut framebuffer);
+        assert!(!cells.is_empty());
+        assert!(cells.iter().all(|&(x, y)| x >= 48 && y >= 32));
+
+        let mut framebuffer = Framebuffer::new(64, 48);
+        render_sprite_shape(&mut framebuffer, -40, 24, 30, 't', 1.0);
+        render_sprite_shape(&mut framebuffer, 200, 200, 30, 't', 1.0);
+        assert!(lit_pixels(&mut framebuffer).is_empty());
+    }
+
+    #[test]
+    fn shapes_outside_screen_are_skipped() {
+        let mut framebuffer = Framebuffer::new(64, 48);
+
+        for shape in ['k', 'c', 'l', 't', '?'] {
+            render_sprite_shape(&mut framebuffer, -100, -100, 10, shape, 1.0);
+            render_sprite_shape(&mut framebuffer, 500, 500, 10, shape, 1.0);
+        }
+        assert!(lit_pixels(&mut framebuffer).is_empty());
+
+        // De un pixel solo puede quedar el centro
+        for shape in ['k', 'c', 'l', 't', '?'] {
+            render_sprite_shape(&mut framebuffer, 32, 24, 1, shape, 1.0);
+        }
+        assert!(lit_pixels(&mut framebuffer).iter().all(|&cell| cell == (32, 24)));
+    }
+}
@@ -1,6 +1,6 @@
 // collision.rs - Sistema de colisiones 
 
-use crate::maze::Maze;
+use crate::maze::{Maze, TileProperties};
 
 pub struct CollisionSystem {
     maze: Maze,
@@ -12,9 +12,10 @@ impl CollisionSystem {
         CollisionSystem { maze, block_size }
     }
 
-    /// Verifica si una posición está dentro de los límites del maze
+    /// Verifica si una posición está dentro de los límites del maze, con el
+    /// largo de su propia fila (las filas cortas terminan en pared)
     fn is_within_bounds(&self, grid_x: usize, grid_y: usize) -> bool {
-        grid_y < self.maze.len() && grid_x < self.maze.get(0).map_or(0, |row| row.len())
+        self.maze.get(grid_y).is_some_and(|row| grid_x < row.len())
     }
 
     /// Convierte coordenadas del mundo a coordenadas de grid de forma segura
@@ -45,9 +46,7 @@ impl CollisionSystem {
     pub fn can_move_to(&self, x: f32, y: f32) -> bool {
         match self.world_to_grid(x, y) {
             Some((grid_x, grid_y)) => {
-                let cell = self.maze[grid_y][grid_x];
-                // Permitir movimiento a través de espacios vacíos y elementos interactivos
-                matches!(cell, ' ' | 'k' | 't' | 'l' | 'c' | 'e')
+                TileProperties::of(self.maze[grid_y][grid_x]).walkable
             }
             None => false, // No permitir movimiento fuera de bounds
         }
@@ -80,7 +79,7 @@ impl CollisionSystem {
     /// Obtiene las dimensiones del maze
     pub fn get_dimensions(&self) -> (usize, usize) {
         let height = self.maze.len();
-        let width = self.maze.get(0).map_or(0, |row| row.len());
+        let width = self.maze.iter().map(|row| row.len()).max().unwrap_or(0);
         (width, height)
     }
 
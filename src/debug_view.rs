@@ -0,0 +1,121 @@
+// debug_view.rs - Vista cenital de depuración con los rayos del caster
+
+use raylib::prelude::*;
+use crate::caster::cast_ray_textured;
+use crate::framebuffer::Framebuffer;
+use crate::line::line;
+use crate::maze::{Maze, RegionMap};
+use crate::palette::Palette;
+use crate::player::Player;
+
+// Se dibuja un rayo cada tantas columnas de pantalla para que se distingan
+const DEBUG_RAY_SPACING: u32 = 8;
+const DEBUG_MARGIN: f32 = 20.0;
+
+/// Dibuja el laberinto visto desde arriba con los mismos rayos que usa el
+/// render 3D, cada uno hasta su punto de impacto. Los impactos con vidrio se
+/// marcan aparte porque el rayo los atraviesa.
+pub fn render_debug_view(
+    framebuffer: &mut Framebuffer,
+    maze: &Maze,
+    regions: Option<&RegionMap>,
+    player: &Player,
+    block_size: usize,
+    door_open: f32,
+    palette: &Palette,
+) {
+    framebuffer.clear();
+
+    let maze_height = maze.len();
+    let maze_width = maze.iter().map(|row| row.len()).max().unwrap_or(0);
+    if maze_width == 0 || maze_height == 0 {
+        return;
+    }
+
+    // Escala de mundo a pantalla para que todo el laberinto quepa centrado
+    let world_width = (maze_width * block_size) as f32;
+    let world_height = (maze_height * block_size) as f32;
+    let scale = ((framebuffer.width as f32 - DEBUG_MARGIN * 2.0) / world_width)
+        .min((framebuffer.height as f32 - DEBUG_MARGIN * 2.0) / world_height)
+        .max(0.01);
+    let origin_x = (framebuffer.width as f32 - world_width * scale) * 0.5;
+    let origin_y = (framebuffer.height as f32 - world_height * scale) * 0.5;
+    let to_screen = |x: f32, y: f32| Vector2::new(origin_x + x * scale, origin_y + y * scale);
+
+    // Casillas, con un pixel de separación como rejilla
+    let cell_size = block_size as f32 * scale;
+    for (row_index, row) in maze.iter().enumerate() {
+        for (col_index, &cell) in row.iter().enumerate() {
+            let Some(color) = palette.minimap_cell_color(cell) else {
+                continue;
+            };
+
+            let top_left = to_screen((col_index * block_size) as f32, (row_index * block_size) as f32);
+            framebuffer.set_current_color(color);
+            for dy in 0..(cell_size as u32).saturating_sub(1) {
+                for dx in 0..(cell_size as u32).saturating_sub(1) {
+                    framebuffer.set_pixel(top_left.x as u32 + dx, top_left.y as u32 + dy);
+                }
+            }
+        }
+    }
+
+    // Rayos con los mismos ángulos que las columnas del render 3D
+    let player_screen = to_screen(player.pos.x, player.pos.y);
+    let angle_increment = player.fov / framebuffer.width as f32;
+    let start_angle = player.a - player.fov * 0.5;
+    let mut glass_hits = Vec::new();
+
+    for column in (0..framebuffer.width).step_by(DEBUG_RAY_SPACING as usize) {
+        let ray_angle = start_angle + column as f32 * angle_increment;
+        glass_hits.clear();
+        let hit = cast_ray_textured(maze, regions, player, ray_angle, block_size, door_open, &mut glass_hits);
+
+        framebuffer.set_current_color(palette.caution);
+        line(framebuffer, player_screen, to_screen(hit.impact_x, hit.impact_y));
+
+        framebuffer.set_current_color(palette.glass);
+        for glass in &glass_hits {
+            let point = to_screen(glass.impact_x, glass.impact_y);
+            render_marker(framebuffer, point, 1);
+        }
+
+        framebuffer.set_current_color(palette.danger);
+        render_marker(framebuffer, to_screen(hit.impact_x, hit.impact_y), 1);
+    }
+
+    // Jugador: círculo con su radio de colisión y dirección
+    framebuffer.set_current_color(palette.player);
+    let radius = (player.radius * scale).max(2.0);
+    let segments = 32;
+    for i in 0..segments {
+        let a0 = i as f32 / segments as f32 * std::f32::consts::TAU;
+        let a1 = (i + 1) as f32 / segments as f32 * std::f32::consts::TAU;
+        line(
+            framebuffer,
+            player_screen + Vector2::new(a0.cos(), a0.sin()) * radius,
+            player_screen + Vector2::new(a1.cos(), a1.sin()) * radius,
+        );
+    }
+
+    framebuffer.set_current_color(palette.player_direction);
+    let facing = Vector2::new(player.a.cos(), player.a.sin()) * (radius * 2.5);
+    line(framebuffer, player_screen, player_screen + facing);
+
+    framebuffer.set_current_color(palette.text);
+    crate::ui::render_text(framebuffer, "DEBUG: VISTA CENITAL (F4)", 10, 10);
+    let ray_count = framebuffer.width.div_ceil(DEBUG_RAY_SPACING);
+    crate::ui::render_text(framebuffer, &format!("RAYOS: {}", ray_count), 10, 22);
+}
+
+fn render_marker(framebuffer: &mut Framebuffer, center: Vector2, half_size: i32) {
+    for dy in -half_size..=half_size {
+        for dx in -half_size..=half_size {
+            let x = center.x as i32 + dx;
+            let y = center.y as i32 + dy;
+            if x >= 0 && y >= 0 {
+                framebuffer.set_pixel(x as u32, y as u32);
+            }
+        }
+    }
+}
@@ -0,0 +1,148 @@
+// editor.rs - Editor de niveles dentro del juego (solo con --editor)
+
+use crate::game_state::{GameData, SPAWN_MARKER};
+use crate::maze::{save_maze, Maze};
+
+// Casillas que se pueden pintar, con el nombre que se muestra en el HUD
+const BRUSHES: [(char, &str); 15] = [
+    ('#', "PARED"),
+    (' ', "SUELO"),
+    ('k', "LLAVE"),
+    ('c', "CHECKPOINT"),
+    ('l', "VIDA EXTRA"),
+    ('t', "TRAMPA"),
+    ('p', "VENENO"),
+    ('x', "TELETRANSPORTE"),
+    ('e', "SALIDA"),
+    ('D', "PUERTA"),
+    ('w', "MURO BAJO"),
+    ('W', "PARED ALTA"),
+    ('v', "VIDRIO"),
+    ('r', "PARED ROJA"),
+    ('P', "MARCO PORTAL"),
+];
+
+/// Pinta casillas del nivel en juego y las exporta a un archivo de texto.
+/// Guarda aparte el mapa tal como se diseñó: con el inicio y las entidades
+/// en su sitio y sin los objetos que el jugador ya recogió
+pub struct LevelEditor {
+    layout: Maze,
+    brush: usize,
+}
+
+impl LevelEditor {
+    /// Editor activo si se pasó `--editor` al abrir el juego
+    pub fn enabled_by_args<I: Iterator<Item = String>>(mut args: I) -> bool {
+        args.any(|arg| arg == "--editor")
+    }
+
+    /// Toma el nivel recién cargado como punto de partida, tal como está en
+    /// el archivo (sin las trampas que quita la dificultad). Si el archivo no
+    /// marca el inicio, se pone donde apareció el jugador
+    pub fn from_level(data: &GameData, block_size: usize) -> Self {
+        let mut layout = data.level_layout.clone();
+        if !layout.iter().flatten().any(|&cell| cell == SPAWN_MARKER) {
+            let (x, y) = (data.player.pos.x / block_size as f32, data.player.pos.y / block_size as f32);
+            if let Some(cell) = layout.get_mut(y as usize).and_then(|row| row.get_mut(x as usize)) {
+                *cell = SPAWN_MARKER;
+            }
+        }
+
+        LevelEditor { layout, brush: 0 }
+    }
+
+    pub fn brush_label(&self) -> &'static str {
+        BRUSHES[self.brush].1
+    }
+
+    pub fn next_brush(&mut self) {
+        self.brush = (self.brush + 1) % BRUSHES.len();
+    }
+
+    /// Pinta la casilla que está justo delante del jugador, en el nivel en
+    /// juego y en el diseño. Devuelve la casilla pintada, si está en el mapa
+    /// y no es la del propio jugador
+    pub fn paint_ahead(&mut self, data: &mut GameData, block_size: usize) -> Option<(usize, usize)> {
+        let block = block_size as f32;
+        let player = &data.player;
+        let ahead_x = player.pos.x + player.a.cos() * block;
+        let ahead_y = player.pos.y + player.a.sin() * block;
+        if ahead_x < 0.0 || ahead_y < 0.0 {
+            return None;
+        }
+
+        let target = ((ahead_x / block) as usize, (ahead_y / block) as usize);
+        let own_cell = ((player.pos.x / block) as usize, (player.pos.y / block) as usize);
+        if target == own_cell {
+            return None;
+        }
+
+        let brush = BRUSHES[self.brush].0;
+        let (x, y) = target;
+        *data.maze.get_mut(y)?.get_mut(x)? = brush;
+        if let Some(cell) = self.layout.get_mut(y).and_then(|row| row.get_mut(x)) {
+            *cell = brush;
+        }
+        Some(target)
+    }
+
+    /// Guarda el diseño junto a los niveles sin pisar el original
+    pub fn export(&self, level: usize) -> std::io::Result<String> {
+        let path = format!("assets/levels/level{}_edited.txt", level);
+        save_maze(&self.layout, &path)?;
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enemies::take_enemies_from_maze;
+    use crate::game_state::{Difficulty, GameState};
+    use crate::maze::load_maze;
+    use raylib::prelude::Vector2;
+
+    #[test]
+    fn painted_cells_are_exported_with_spawn_and_entities_restored() {
+        let mut state = GameState::new();
+        state.data.maze = ["#####", "#   #", "# km#", "#####"].iter().map(|row| row.chars().collect()).collect();
+        state.data.level_layout = state.data.maze.clone();
+        state.data.enemies = take_enemies_from_maze(&mut state.data.maze, 100);
+        state.data.player.pos = Vector2::new(150.0, 150.0);
+        state.data.player.a = 0.0;
+
+        let mut editor = LevelEditor::from_level(&state.data, 100);
+        // La llave recogida durante la prueba sigue en el diseño
+        state.data.maze[2][2] = ' ';
+        assert_eq!(editor.paint_ahead(&mut state.data, 100), Some((2, 1)));
+        editor.next_brush();
+        editor.next_brush();
+        assert_eq!(editor.brush_label(), "LLAVE");
+        state.data.player.a = std::f32::consts::FRAC_PI_2;
+        assert_eq!(editor.paint_ahead(&mut state.data, 100), Some((1, 2)));
+
+        assert_eq!(state.data.maze[1][2], '#');
+        let expected: Maze = ["#####", "#S# #", "#kkm#", "#####"].iter().map(|row| row.chars().collect()).collect();
+        assert_eq!(editor.layout, expected);
+
+        let path = std::env::temp_dir().join("editor_export_test.txt");
+        save_maze(&editor.layout, path.to_str().unwrap()).unwrap();
+        assert_eq!(load_maze(path.to_str().unwrap()), Ok(expected));
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn easy_difficulty_does_not_strip_traps_from_the_export() {
+        let mut state = GameState::new();
+        state.data.difficulty = Difficulty::Easy;
+        state.load_level(2);
+        let traps = |maze: &Maze| maze.iter().flatten().filter(|&&cell| cell == 't').count();
+
+        let editor = LevelEditor::from_level(&state.data, 100);
+
+        let on_disk = load_maze("assets/levels/level2.txt").unwrap();
+        assert_eq!(traps(&on_disk), 2);
+        assert_eq!(traps(&state.data.maze), 1);
+        assert_eq!(traps(&editor.layout), 2);
+    }
+}
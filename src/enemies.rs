@@ -0,0 +1,193 @@
+// enemies.rs - Entidades que persiguen al jugador por el laberinto
+
+use raylib::prelude::*;
+use std::collections::VecDeque;
+use crate::maze::{Maze, TileProperties};
+use crate::pathfinding::a_star;
+use crate::player::can_move_to_safe;
+
+/// Carácter del nivel donde aparece una entidad; al cargar se cambia por suelo
+pub const ENEMY_MARKER: char = 'm';
+
+pub const ENEMY_RADIUS: f32 = 25.0;
+// Unidades de mundo por segundo: más lenta que el jugador caminando
+const ENEMY_SPEED: f32 = 540.0;
+// Cada cuánto se recalcula el camino hacia el jugador
+const ENEMY_REPATH_SECONDS: f32 = 0.5;
+// Distancia máxima, en casillas, a la que una entidad dormida ve al jugador
+const ENEMY_SIGHT_CELLS: f32 = 6.0;
+// Tras golpear, la entidad se queda quieta este tiempo antes de volver a atacar
+pub const ENEMY_HIT_COOLDOWN: f32 = 1.5;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EnemyState {
+    // Quieta hasta ver al jugador
+    Idle,
+    Chasing,
+}
+
+#[derive(Debug, Clone)]
+pub struct Enemy {
+    pub pos: Vector2,
+    pub speed: f32,
+    pub state: EnemyState,
+    // Casillas que faltan hasta el jugador, la siguiente primero
+    path: VecDeque<(usize, usize)>,
+    repath_timer: f32,
+    pub hit_cooldown: f32,
+}
+
+impl Enemy {
+    pub fn new(x: f32, y: f32) -> Self {
+        Enemy {
+            pos: Vector2::new(x, y),
+            speed: ENEMY_SPEED,
+            state: EnemyState::Idle,
+            path: VecDeque::new(),
+            repath_timer: 0.0,
+            hit_cooldown: 0.0,
+        }
+    }
+
+    /// Despierta al ver al jugador y desde entonces lo persigue por el camino
+    /// más corto de casillas (A*), recalculado cada ENEMY_REPATH_SECONDS
+    pub fn update(&mut self, player_pos: Vector2, maze: &Maze, block_size: usize, delta_time: f32) {
+        self.hit_cooldown = (self.hit_cooldown - delta_time).max(0.0);
+
+        if self.state == EnemyState::Idle {
+            let in_range = self.pos.distance_to(player_pos) <= ENEMY_SIGHT_CELLS * block_size as f32;
+            if !in_range || !has_line_of_sight(maze, self.pos, player_pos, block_size) {
+                return;
+            }
+            self.state = EnemyState::Chasing;
+            self.repath_timer = 0.0;
+        }
+
+        if self.hit_cooldown > 0.0 {
+            return;
+        }
+
+        self.repath_timer -= delta_time;
+        if self.repath_timer <= 0.0 {
+            self.repath_timer = ENEMY_REPATH_SECONDS;
+            self.path = match (cell_of(self.pos, maze, block_size), cell_of(player_pos, maze, block_size)) {
+                // Sin la casilla de partida, en la que ya está
+                (Some(start), Some(goal)) => a_star(maze, start, goal).unwrap_or_default().into_iter().skip(1).collect(),
+                _ => VecDeque::new(),
+            };
+        }
+
+        // Siguiente casilla del camino; en la casilla del jugador va directo a él
+        let half_block = block_size as f32 * 0.5;
+        let target = match self.path.front() {
+            Some(&(x, y)) => Vector2::new(
+                (x * block_size) as f32 + half_block,
+                (y * block_size) as f32 + half_block,
+            ),
+            None => player_pos,
+        };
+
+        let to_target = target - self.pos;
+        let step = self.speed * delta_time;
+        if to_target.length() <= step {
+            self.move_to(target, maze, block_size);
+            self.path.pop_front();
+        } else {
+            self.move_to(self.pos + to_target.normalized() * step, maze, block_size);
+        }
+    }
+
+    // Avanza con las mismas colisiones que el jugador, deslizando por un eje si choca
+    fn move_to(&mut self, target: Vector2, maze: &Maze, block_size: usize) {
+        if can_move_to_safe(target.x, target.y, maze, block_size, ENEMY_RADIUS) {
+            self.pos = target;
+        } else if can_move_to_safe(target.x, self.pos.y, maze, block_size, ENEMY_RADIUS) {
+            self.pos.x = target.x;
+        } else if can_move_to_safe(self.pos.x, target.y, maze, block_size, ENEMY_RADIUS) {
+            self.pos.y = target.y;
+        }
+    }
+
+    /// El círculo de la entidad toca el del jugador
+    pub fn touches(&self, player_pos: Vector2, player_radius: f32) -> bool {
+        self.pos.distance_to(player_pos) < ENEMY_RADIUS + player_radius
+    }
+}
+
+/// Saca las entidades del laberinto y deja suelo en su lugar
+pub fn take_enemies_from_maze(maze: &mut Maze, block_size: usize) -> Vec<Enemy> {
+    let half_block = block_size as f32 * 0.5;
+    let mut enemies = Vec::new();
+
+    for (y, row) in maze.iter_mut().enumerate() {
+        for (x, cell) in row.iter_mut().enumerate() {
+            if *cell == ENEMY_MARKER {
+                *cell = ' ';
+                enemies.push(Enemy::new((x * block_size) as f32 + half_block, (y * block_size) as f32 + half_block));
+            }
+        }
+    }
+
+    enemies
+}
+
+// Casilla que contiene un punto del mundo, si está dentro del laberinto
+fn cell_of(pos: Vector2, maze: &Maze, block_size: usize) -> Option<(usize, usize)> {
+    if pos.x < 0.0 || pos.y < 0.0 {
+        return None;
+    }
+
+    let x = (pos.x / block_size as f32) as usize;
+    let y = (pos.y / block_size as f32) as usize;
+    maze.get(y)?.get(x)?;
+    Some((x, y))
+}
+
+// Recorre el segmento a pasos de un cuarto de casilla; lo corta cualquier
+// casilla que tape la vista (el vidrio deja ver a través)
+fn has_line_of_sight(maze: &Maze, from: Vector2, to: Vector2, block_size: usize) -> bool {
+    let step_length = block_size as f32 * 0.25;
+    let steps = (from.distance_to(to) / step_length).ceil() as usize;
+
+    (0..=steps).all(|i| {
+        let t = if steps == 0 { 0.0 } else { i as f32 / steps as f32 };
+        let point = from + (to - from) * t;
+        cell_of(point, maze, block_size)
+            .is_some_and(|(x, y)| !TileProperties::of(maze[y][x]).blocks_sight)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn maze(rows: &[&str]) -> Maze {
+        rows.iter().map(|row| row.chars().collect()).collect()
+    }
+
+    #[test]
+    fn enemies_sleep_until_they_see_the_player_then_chase() {
+        // Pared entre la entidad y el jugador, con un hueco al final
+        let mut level = maze(&["#######", "#m    #", "##### #", "#     #", "#######"]);
+        let mut enemies = take_enemies_from_maze(&mut level, 100);
+        assert_eq!(level[1][1], ' ');
+        let enemy = &mut enemies[0];
+
+        let hidden_player = Vector2::new(150.0, 350.0);
+        for _ in 0..30 {
+            enemy.update(hidden_player, &level, 100, 0.016);
+        }
+        assert_eq!(enemy.state, EnemyState::Idle);
+        assert_eq!(enemy.pos, Vector2::new(150.0, 150.0));
+
+        // Visible en el mismo pasillo: despierta, y luego lo sigue por el hueco
+        let visible_player = Vector2::new(450.0, 150.0);
+        enemy.update(visible_player, &level, 100, 0.016);
+        assert_eq!(enemy.state, EnemyState::Chasing);
+
+        for _ in 0..200 {
+            enemy.update(hidden_player, &level, 100, 0.016);
+        }
+        assert!(enemy.touches(hidden_player, 20.0), "la entidad quedó en {:?}", enemy.pos);
+    }
+}
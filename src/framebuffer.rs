@@ -1,6 +1,7 @@
 // framebuffer.rs - Sistema de framebuffer 
 
 use raylib::prelude::*;
+use image::RgbaImage;
 
 pub struct Framebuffer {
     pub width: u32,
@@ -36,6 +37,74 @@ impl Framebuffer {
         }
     }
 
+    /// Mezcla el color actual sobre el pixel existente según su alfa
+    pub fn blend_pixel(&mut self, x: u32, y: u32) {
+        if x < self.width && y < self.height {
+            let src = self.current_color;
+            let dst = self.color_buffer.get_color(x as i32, y as i32);
+            let alpha = src.a as f32 / 255.0;
+            let mix = |s: u8, d: u8| (s as f32 * alpha + d as f32 * (1.0 - alpha)) as u8;
+
+            let blended = Color::new(mix(src.r, dst.r), mix(src.g, dst.g), mix(src.b, dst.b), 255);
+            self.color_buffer.draw_pixel(x as i32, y as i32, blended);
+        }
+    }
+
+    /// Oscurece todo el frame hacia negro; `amount` va de 0 (nada) a 1 (negro)
+    pub fn fade_to_black(&mut self, amount: f32) {
+        let alpha = (amount.clamp(0.0, 1.0) * 255.0) as u8;
+        if alpha == 0 {
+            return;
+        }
+
+        let previous_color = self.current_color;
+        self.current_color = Color::new(0, 0, 0, alpha);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                self.blend_pixel(x, y);
+            }
+        }
+        self.current_color = previous_color;
+    }
+
+    /// Color de un pixel del frame actual; None fuera de pantalla
+    pub fn get_pixel(&mut self, x: u32, y: u32) -> Option<Color> {
+        if x < self.width && y < self.height {
+            Some(self.color_buffer.get_color(x as i32, y as i32))
+        } else {
+            None
+        }
+    }
+
+    /// Copia del frame actual como imagen RGBA (capturas, miniaturas, tests)
+    pub fn to_image(&self) -> RgbaImage {
+        let mut bytes = Vec::with_capacity((self.width * self.height * 4) as usize);
+        for color in self.color_buffer.get_image_data().iter() {
+            bytes.extend_from_slice(&[color.r, color.g, color.b, color.a]);
+        }
+
+        RgbaImage::from_raw(self.width, self.height, bytes)
+            .unwrap_or_else(|| RgbaImage::new(self.width, self.height))
+    }
+
+    /// Copia otro framebuffer estirado a este tamaño (vecino más cercano);
+    /// así el mundo se renderiza a menor resolución que el HUD
+    pub fn blit_scaled(&mut self, source: &Framebuffer) {
+        if source.width == self.width && source.height == self.height {
+            self.color_buffer = source.color_buffer.clone();
+            return;
+        }
+
+        let pixels = source.color_buffer.get_image_data();
+        for y in 0..self.height {
+            let row = (y * source.height / self.height) * source.width;
+            for x in 0..self.width {
+                let color = pixels[(row + x * source.width / self.width) as usize];
+                self.color_buffer.draw_pixel(x as i32, y as i32, color);
+            }
+        }
+    }
+
     pub fn set_background_color(&mut self, color: Color) {
         self.background_color = color;
     }
@@ -54,4 +123,64 @@ impl Framebuffer {
             renderer.draw_texture(&texture, 0, 0, Color::WHITE);
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pixels_can_be_read_back_and_exported() {
+        let mut framebuffer = Framebuffer::new(8, 4);
+        framebuffer.set_current_color(Color::new(10, 20, 30, 255));
+        framebuffer.set_pixel(5, 2);
+
+        assert_eq!(framebuffer.get_pixel(5, 2), Some(Color::new(10, 20, 30, 255)));
+        assert_eq!(framebuffer.get_pixel(8, 0), None);
+        assert_eq!(framebuffer.get_pixel(0, 4), None);
+
+        let image = framebuffer.to_image();
+        assert_eq!(image.dimensions(), (8, 4));
+        assert_eq!(image.get_pixel(5, 2).0, [10, 20, 30, 255]);
+        assert_eq!(image.get_pixel(0, 0).0, [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn fading_darkens_every_pixel_and_keeps_the_drawing_color() {
+        let mut framebuffer = Framebuffer::new(4, 4);
+        framebuffer.set_background_color(Color::new(200, 100, 50, 255));
+        framebuffer.clear();
+        framebuffer.set_current_color(Color::WHITE);
+
+        framebuffer.fade_to_black(0.0);
+        assert_eq!(framebuffer.get_pixel(3, 3), Some(Color::new(200, 100, 50, 255)));
+
+        framebuffer.fade_to_black(0.5);
+        let faded = framebuffer.get_pixel(3, 3).unwrap();
+        assert!(faded.r < 110 && faded.r > 90);
+
+        framebuffer.fade_to_black(1.0);
+        assert_eq!(framebuffer.get_pixel(0, 0), Some(Color::BLACK));
+
+        framebuffer.set_pixel(1, 1);
+        assert_eq!(framebuffer.get_pixel(1, 1), Some(Color::WHITE));
+    }
+
+    #[test]
+    fn half_resolution_frames_are_stretched_to_fill_the_screen() {
+        let red = Color::new(200, 0, 0, 255);
+        let mut world = Framebuffer::new(4, 2);
+        world.set_current_color(red);
+        world.set_pixel(3, 1);
+
+        let mut screen = Framebuffer::new(8, 4);
+        screen.blit_scaled(&world);
+
+        // El pixel de la esquina ocupa un bloque de 2x2 en pantalla
+        for (x, y) in [(6, 2), (7, 2), (6, 3), (7, 3)] {
+            assert_eq!(screen.get_pixel(x, y), Some(red));
+        }
+        assert_eq!(screen.get_pixel(5, 3), Some(Color::BLACK));
+        assert_eq!(screen.get_pixel(7, 1), Some(Color::BLACK));
+    }
+}
@@ -1,37 +1,292 @@
 // game_state.rs - Sistema de estado de juego optimizado
 
 use raylib::prelude::*;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use std::collections::HashSet;
 use std::f32::consts::PI;
-use crate::maze::{Maze, load_maze};
-use crate::player::Player;
-use crate::audio::{AudioManager, GameAudioEvent};
+use crate::maze::{DOOR_CLOSED, DOOR_OPEN, Maze, RegionMap, TileProperties, generate_maze, load_maze, load_region_map, validate_maze, GENERATED_MAZE_KEYS};
+use crate::level_meta::{LevelMeta, load_level_meta};
+use crate::player::{Player, InputState, apply_input, can_move_to_safe, process_mouse_input_safe};
+use crate::caster::FogSettings;
+use crate::enemies::{Enemy, EnemyState, ENEMY_HIT_COOLDOWN, take_enemies_from_maze};
+use crate::audio::GameAudioEvent;
 use crate::notification::{NotificationManager};
+use crate::pathfinding::reachable_cells;
+use crate::score::LevelScore;
+use crate::sprite_manager::{Sprite, SpriteType};
+
+// Casilla que marca dónde aparece el jugador en los archivos de nivel
+pub const SPAWN_MARKER: char = 'S';
+
+// Segundos entre rescates consecutivos del jugador atascado en una pared
+const UNSTUCK_COOLDOWN: f32 = 0.5;
+
+// Segundos entre pisar una trampa de veneno 'p' y perder la vida
+pub const POISON_DELAY_SECONDS: f32 = 5.0;
+
+// Duración del destello rojo al perder una vida
+pub const DAMAGE_FLASH_SECONDS: f32 = 0.4;
+
+// Cuenta atrás al empezar un nivel y tiempo que se muestra el "GO!" después
+pub const LEVEL_INTRO_SECONDS: f32 = 3.0;
+const INTRO_GO_SECONDS: f32 = 0.7;
+
+// Estamina (0 a 1): correr con la barra llena dura 3 s y recargarla, 5 s
+const STAMINA_DRAIN_PER_SECOND: f32 = 1.0 / 3.0;
+const STAMINA_REGEN_PER_SECOND: f32 = 1.0 / 5.0;
+// Tras agotarse no se vuelve a correr hasta recuperar este nivel
+const STAMINA_RECOVERY_THRESHOLD: f32 = 0.3;
+
+// Alcance extra del imán de objetos: con el jugador en el centro de una
+// casilla recoge lo que haya en las vecinas. 0 = hay que tocar el objeto
+pub const PICKUP_MAGNET_RADIUS: f32 = 50.0;
+
+// Segundos que tarda una puerta en deslizarse del todo
+const DOOR_OPEN_SECONDS: f32 = 0.5;
+
+// Vidas al empezar una partida desde el menú
+pub const STARTING_LIVES: i32 = 3;
+pub const HARDCORE_LIVES: i32 = 1;
+// Vidas que se pueden acumular como mucho
+pub const MAX_LIVES: i32 = 6;
+
+/// Reglas de vidas de la partida. Las vidas duran toda la partida: cargar un
+/// nivel no las toca. `starting` es la base en dificultad normal (la
+/// dificultad la sube o baja), pasar de nivel devuelve una vida si
+/// `life_per_level` y nunca se supera `max`, ni con vidas extra
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LifeRules {
+    pub starting: i32,
+    pub max: i32,
+    pub life_per_level: bool,
+}
+
+impl Default for LifeRules {
+    fn default() -> Self {
+        LifeRules { starting: STARTING_LIVES, max: MAX_LIVES, life_per_level: true }
+    }
+}
+
+impl LifeRules {
+    /// Vidas al empezar una partida; hardcore siempre empieza con una
+    pub fn starting_lives(&self, difficulty: Difficulty, hardcore: bool) -> i32 {
+        if hardcore {
+            return HARDCORE_LIVES;
+        }
+        difficulty.starting_lives(self.starting).clamp(1, self.max)
+    }
+}
+
+// Nivel extra del menú: un laberinto generado a partir de `random_seed`
+pub const RANDOM_LEVEL: usize = 4;
+// Tamaño en celdas de pasillo del laberinto generado
+const RANDOM_LEVEL_CELLS: (usize, usize) = (12, 12);
+
+/// Dificultad elegida en el menú; dura toda la partida hasta volver al menú
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub fn next(self) -> Self {
+        match self {
+            Difficulty::Easy => Difficulty::Normal,
+            Difficulty::Normal => Difficulty::Hard,
+            Difficulty::Hard => Difficulty::Easy,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "FACIL",
+            Difficulty::Normal => "NORMAL",
+            Difficulty::Hard => "DIFICIL",
+        }
+    }
+
+    /// Vidas iniciales a partir de las de dificultad normal
+    pub fn starting_lives(self, normal_lives: i32) -> i32 {
+        match self {
+            Difficulty::Easy => normal_lives + 2,
+            Difficulty::Normal => normal_lives,
+            Difficulty::Hard => normal_lives - 1,
+        }
+    }
+
+    /// Vidas que quita cada trampa
+    pub fn trap_damage(self) -> i32 {
+        match self {
+            Difficulty::Hard => 2,
+            _ => 1,
+        }
+    }
+
+    pub fn enemy_speed_multiplier(self) -> f32 {
+        match self {
+            Difficulty::Hard => 1.3,
+            _ => 1.0,
+        }
+    }
+}
 
 #[derive(Clone, PartialEq)]
 pub enum GameMode {
     Welcome,
+    Loading(usize),
     Playing,
+    // Nivel congelado detrás del menú de pausa
+    Paused,
+    // El archivo del nivel tiene errores; se listan en vez de jugarlo
+    LevelError,
     GameOver,
     Victory,
 }
 
+impl GameMode {
+    /// ESC cierra el juego salvo donde tiene otro uso: pausar y reanudar el
+    /// nivel, y volver al menú desde la pantalla de errores
+    pub fn escape_quits(&self) -> bool {
+        !matches!(self, GameMode::Playing | GameMode::Paused | GameMode::LevelError)
+    }
+}
+
 #[derive(Clone)]
 pub struct GameData {
     pub maze: Maze,
+    // Mapa tal como se leyó del archivo, antes de aplicar la dificultad y de
+    // sacar el inicio y las entidades (lo que exporta el editor)
+    pub level_layout: Maze,
+    pub regions: Option<RegionMap>,
+    pub level_meta: LevelMeta,
     pub player: Player,
     pub current_level: usize,
     pub lives: i32,
+    pub life_rules: LifeRules,
     pub has_key: bool,
     pub keys_collected: i32,
     pub keys_needed: i32,
     pub visited_checkpoints: Vec<(usize, usize)>,
+    pub collected_items: Vec<SpriteType>,
+    // Casillas cuyo objeto se consumió este frame; main quita sus sprites
+    pub consumed_cells: Vec<(usize, usize)>,
+    // Trampas disparadas que se rearman: casilla y segundos que faltan
+    pub disarmed_traps: Vec<((usize, usize), f32)>,
     pub animation_time: f32,
     pub notification_manager: NotificationManager,
+    pub unstuck_cooldown: f32,
+    // Partida hardcore: una sola vida para los tres niveles y sin vidas extra
+    pub hardcore: bool,
+    pub difficulty: Difficulty,
+    // Segundos de cuenta atrás restantes; negativo mientras se muestra el "GO!"
+    pub intro_timer: f32,
+    // Estamina para correr (0 a 1); agotada obliga a caminar hasta recuperarse
+    pub stamina: f32,
+    pub stamina_exhausted: bool,
+    // El jugador corrió en el último frame (para el ritmo de los pasos)
+    pub sprinting: bool,
+    // Niebla por distancia con el color del nivel actual
+    pub fog: FogSettings,
+    // Apertura de las puertas 'D' (0 cerrada, 1 abierta); avanza al tener la llave
+    pub door_open: f32,
+    // Segundos jugados en el nivel actual, sin contar la cuenta atrás ni la pausa
+    pub level_time: f32,
+    // Entidades del nivel ('m' en el mapa), que persiguen al jugador
+    pub enemies: Vec<Enemy>,
+    // Casillas que pisó el jugador en este nivel (rastro del minimapa)
+    pub visited_cells: HashSet<(usize, usize)>,
+    // Casilla del centro del jugador en el tick anterior; los avisos de
+    // casilla (salida bloqueada) solo salen al entrar en ella
+    pub last_cell: Option<(usize, usize)>,
+    // Problemas del archivo del nivel encontrados por validate_maze
+    pub level_errors: Vec<String>,
+    // Semilla del nivel aleatorio; la misma semilla repite el mismo laberinto
+    pub random_seed: u64,
+    // Azar de la lógica del juego (trampas de teletransporte); se vuelve a
+    // sembrar con `random_seed` al cargar el nivel para que los replays coincidan
+    pub rng: StdRng,
+    // Casilla donde aparece el jugador al cargar el nivel
+    pub spawn_cell: (usize, usize),
+    // Puntos acumulados en la partida y trampas pisadas en el nivel actual
+    pub score: i32,
+    pub traps_triggered: u32,
+    // Desglose del último nivel completado (pantalla de victoria)
+    pub last_level_score: Option<LevelScore>,
+    // Segundos hasta que el veneno quite una vida; None si no está envenenado
+    pub poison_timer: Option<f32>,
+    // Segundos que le quedan al destello rojo de daño
+    pub damage_flash_timer: f32,
+}
+
+/// Lo que le falta al jugador para poder usar la salida
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExitRequirements {
+    pub keys_missing: i32,
+    pub checkpoints_missing: usize,
+}
+
+impl ExitRequirements {
+    pub fn is_met(&self) -> bool {
+        self.keys_missing <= 0 && self.checkpoints_missing == 0
+    }
+
+    /// Texto para mostrar junto al portal, o None si la salida está abierta
+    pub fn lock_label(&self) -> Option<String> {
+        if self.is_met() {
+            return None;
+        }
+
+        let mut parts = Vec::new();
+        if self.keys_missing > 0 {
+            let noun = if self.keys_missing == 1 { "LLAVE" } else { "LLAVES" };
+            parts.push(format!("{} {}", self.keys_missing, noun));
+        }
+        if self.checkpoints_missing > 0 {
+            let noun = if self.checkpoints_missing == 1 { "CHECKPOINT" } else { "CHECKPOINTS" };
+            parts.push(format!("{} {}", self.checkpoints_missing, noun));
+        }
+
+        Some(format!("FALTAN {}", parts.join(" - ")))
+    }
+}
+
+pub fn checkpoints_needed(level: usize) -> usize {
+    match level {
+        1 => 0,
+        2 => 1,
+        3 => 2,
+        _ => 0,
+    }
+}
+
+/// Regla de victoria sin estado: parado sobre la salida, con la llave y con
+/// los checkpoints que pide el nivel
+pub fn wins_at_cell(cell: char, has_key: bool, visited_checkpoints: usize, level: usize) -> bool {
+    cell == 'e' && has_key && visited_checkpoints >= checkpoints_needed(level)
+}
+
+impl GameData {
+    pub fn exit_requirements(&self) -> ExitRequirements {
+        ExitRequirements {
+            keys_missing: if self.has_key { 0 } else { (self.keys_needed - self.keys_collected).max(1) },
+            checkpoints_missing: checkpoints_needed(self.current_level)
+                .saturating_sub(self.visited_checkpoints.len()),
+        }
+    }
 }
 
 pub struct GameState {
     pub mode: GameMode,
     pub data: GameData,
+    pub menu_selection: usize,
+    // Opción resaltada en el menú de pausa
+    pub pause_selection: usize,
+    // Opción resaltada en la pantalla de ajustes de la pausa; None si está cerrada
+    pub settings_selection: Option<usize>,
 }
 
 impl GameState {
@@ -40,22 +295,108 @@ impl GameState {
             mode: GameMode::Welcome,
             data: GameData {
                 maze: vec![],
+                level_layout: vec![],
+                regions: None,
+                level_meta: LevelMeta::default(),
                 player: Player::new(),
                 current_level: 1,
-                lives: 3,
+                lives: STARTING_LIVES,
+                life_rules: LifeRules::default(),
                 has_key: false,
                 keys_collected: 0,
                 keys_needed: 1,
                 visited_checkpoints: vec![],
+                collected_items: vec![],
+                consumed_cells: vec![],
+                disarmed_traps: vec![],
                 animation_time: 0.0,
                 notification_manager: NotificationManager::new(),
+                unstuck_cooldown: 0.0,
+                hardcore: false,
+                difficulty: Difficulty::Normal,
+                intro_timer: -INTRO_GO_SECONDS,
+                stamina: 1.0,
+                stamina_exhausted: false,
+                sprinting: false,
+                fog: FogSettings::default(),
+                door_open: 0.0,
+                level_time: 0.0,
+                enemies: vec![],
+                visited_cells: HashSet::new(),
+                last_cell: None,
+                level_errors: vec![],
+                random_seed: 0,
+                rng: StdRng::seed_from_u64(0),
+                spawn_cell: (1, 1),
+                score: 0,
+                traps_triggered: 0,
+                last_level_score: None,
+                poison_timer: None,
+                damage_flash_timer: 0.0,
             },
+            menu_selection: 0,
+            pause_selection: 0,
+            settings_selection: None,
+        }
+    }
+
+    /// Empieza una partida nueva desde el menú. Las vidas son de toda la
+    /// partida y dependen de la dificultad (ver LifeRules); en hardcore se
+    /// empieza siempre por el nivel 1 con una vida.
+    pub fn begin_run(&mut self, level: usize) {
+        self.data.lives = self.data.life_rules.starting_lives(self.data.difficulty, self.data.hardcore);
+        let level = if self.data.hardcore { 1 } else { level };
+        self.data.score = 0;
+        self.data.last_level_score = None;
+        self.mode = GameMode::Loading(level);
+    }
+
+    /// Número de la cuenta atrás de inicio de nivel (0 para el "GO!"), o None
+    /// si ya terminó
+    pub fn intro_countdown(&self) -> Option<u32> {
+        let remaining = self.data.intro_timer;
+        if remaining > 0.0 {
+            Some(remaining.ceil() as u32)
+        } else if remaining > -INTRO_GO_SECONDS {
+            Some(0)
+        } else {
+            None
+        }
+    }
+
+    /// Congela el nivel; `tick` no avanza nada mientras dure la pausa
+    pub fn pause(&mut self) {
+        if self.mode == GameMode::Playing {
+            self.mode = GameMode::Paused;
+            self.pause_selection = 0;
+            self.settings_selection = None;
+        }
+    }
+
+    pub fn resume(&mut self) {
+        if self.mode == GameMode::Paused {
+            self.mode = GameMode::Playing;
+        }
+    }
+
+    /// Se completó el último nivel en una partida hardcore
+    pub fn hardcore_run_completed(&self) -> bool {
+        self.data.hardcore && self.mode == GameMode::Victory && self.data.current_level >= 3
+    }
+
+    /// Pasa al nivel siguiente tras la pantalla de victoria, con un frame de
+    /// carga. Devuelve la vida del nivel superado si las reglas lo piden,
+    /// salvo en hardcore
+    pub fn advance_level(&mut self) {
+        if self.data.life_rules.life_per_level && !self.data.hardcore {
+            self.gain_life();
         }
+        self.mode = GameMode::Loading(self.data.current_level + 1);
     }
 
     pub fn start_game(&mut self, level: usize) {
-        self.mode = GameMode::Playing;
         self.load_level(level);
+        self.mode = if self.data.level_errors.is_empty() { GameMode::Playing } else { GameMode::LevelError };
     }
 
     pub fn load_level(&mut self, level: usize) {
@@ -65,55 +406,87 @@ impl GameState {
             3 => "assets/levels/level3.txt",
             _ => "assets/levels/level1.txt",
         };
-        
-        self.data.maze = load_maze(level_file);
-        
-        if self.data.maze.is_empty() || self.data.maze.iter().any(|row| row.is_empty()) {
-            self.data.maze = self.create_emergency_maze();
+        self.load_level_from(level, level_file);
+    }
+
+    // Un archivo que no se puede cargar deja el nivel vacío con su error en
+    // `level_errors`, que start_game muestra en la pantalla de errores
+    fn load_level_from(&mut self, level: usize, level_file: &str) {
+        if level == RANDOM_LEVEL {
+            let (width, height) = RANDOM_LEVEL_CELLS;
+            self.data.maze = generate_maze(width, height, self.data.random_seed);
+            self.data.regions = None;
+            self.data.level_meta = LevelMeta::default();
+        } else {
+            self.data.maze = match load_maze(level_file) {
+                Ok(maze) => maze,
+                Err(error) => {
+                    self.data.maze.clear();
+                    self.data.level_layout.clear();
+                    self.data.enemies.clear();
+                    self.data.current_level = level;
+                    self.data.level_errors = vec![error];
+                    return;
+                }
+            };
+            // Capa de regiones opcional junto al nivel (levelN_regions.txt)
+            self.data.regions = load_region_map(&level_file.replace(".txt", "_regions.txt"));
+            // Radio y velocidad del jugador propios del nivel (levelN_meta.txt)
+            self.data.level_meta = load_level_meta(&level_file.replace(".txt", "_meta.txt"));
         }
         
+        self.data.level_layout = self.data.maze.clone();
+        self.data.enemies = take_enemies_from_maze(&mut self.data.maze, 100);
+        self.apply_difficulty();
+        
         self.data.current_level = level;
+        self.data.rng = StdRng::seed_from_u64(self.data.random_seed);
         self.data.has_key = false;
         self.data.keys_collected = 0;
         self.data.visited_checkpoints.clear();
+        self.data.collected_items.clear();
+        self.data.consumed_cells.clear();
+        self.data.disarmed_traps.clear();
+        self.data.visited_cells.clear();
+        self.data.last_cell = None;
+        self.data.intro_timer = LEVEL_INTRO_SECONDS;
+        self.data.fog = FogSettings::for_level(level);
+        self.data.stamina = 1.0;
+        self.data.stamina_exhausted = false;
+        self.data.sprinting = false;
+        self.data.door_open = 0.0;
+        self.data.level_time = 0.0;
+        self.data.traps_triggered = 0;
+        self.data.poison_timer = None;
+        self.data.damage_flash_timer = 0.0;
         
         self.data.keys_needed = match level {
             1 => 1,
             2 => 2,
             3 => 3,
+            RANDOM_LEVEL => GENERATED_MAZE_KEYS as i32,
             _ => 1,
         };
 
         self.set_player_start_position(level);
         self.validate_player_position();
-    }
 
-    fn create_emergency_maze(&self) -> Maze {
-        vec![
-            "###################".chars().collect(),
-            "#                 #".chars().collect(),
-            "# ############### #".chars().collect(),
-            "#                 #".chars().collect(),
-            "# ####### # # ####".chars().collect(),
-            "# #     # # #   #".chars().collect(),
-            "# # ### # # ### #".chars().collect(),
-            "# #   # # #   # #".chars().collect(),
-            "# ### # # ### # #".chars().collect(),
-            "# #k  # #   # # #".chars().collect(),
-            "# ##### ### # # #".chars().collect(),
-            "#           # # #".chars().collect(),
-            "############# # #".chars().collect(),
-            "#             # #".chars().collect(),
-            "#           l  ##".chars().collect(),
-            "##############e##".chars().collect(),
-        ]
+        let spawn = (
+            (self.data.player.pos.x / 100.0) as usize,
+            (self.data.player.pos.y / 100.0) as usize,
+        );
+        self.data.spawn_cell = spawn;
+        self.data.level_errors = match validate_maze(&self.data.maze, spawn, self.data.keys_needed as usize) {
+            Ok(()) => vec![],
+            Err(errors) => errors,
+        };
     }
 
     fn set_player_start_position(&mut self, level: usize) {
         let spawn_pos = self.find_spawn_position();
         
-        let (start_pos, start_angle) = if let Some((x, y)) = spawn_pos {
-            (Vector2::new(x, y), PI / 4.0)
+        let (start_pos, start_angle) = if let Some((x, y, angle)) = spawn_pos {
+            (Vector2::new(x, y), angle)
         } else {
             match level {
                 1 => (Vector2::new(150.0, 150.0), PI / 4.0),
@@ -126,8 +499,12 @@ impl GameState {
         self.data.player = Player {
             pos: start_pos,
             a: start_angle,
-            fov: PI / 3.0,
-            radius: 20.0,
+            // El zoom elegido por el jugador se mantiene entre niveles
+            fov: self.data.player.fov,
+            radius: self.data.level_meta.player_radius,
+            velocity: Vector2::zero(),
+            move_speed: self.data.level_meta.move_speed,
+            pitch: 0.0,
         };
 
         if !self.is_position_safe(start_pos.x, start_pos.y) {
@@ -135,10 +512,18 @@ impl GameState {
         }
     }
 
-    fn find_spawn_position(&self) -> Option<(f32, f32)> {
+    // Posición y ángulo inicial: el marcador 'S' del nivel tiene prioridad;
+    // si no existe se busca el primer espacio abierto
+    fn find_spawn_position(&mut self) -> Option<(f32, f32, f32)> {
         if self.data.maze.is_empty() {
             return None;
         }
+
+        if let Some((x, y)) = self.take_spawn_marker() {
+            let world_x = (x as f32 * 100.0) + 50.0;
+            let world_y = (y as f32 * 100.0) + 50.0;
+            return Some((world_x, world_y, self.spawn_facing_angle(x, y)));
+        }
         
         for (y, row) in self.data.maze.iter().enumerate() {
             for (x, &cell) in row.iter().enumerate() {
@@ -147,7 +532,7 @@ impl GameState {
                     let world_y = (y as f32 * 100.0) + 50.0;
                     
                     if self.has_spawn_clearance(x, y) {
-                        return Some((world_x, world_y));
+                        return Some((world_x, world_y, PI / 4.0));
                     }
                 }
             }
@@ -156,6 +541,51 @@ impl GameState {
         None
     }
 
+    // Busca el marcador 'S' y lo reemplaza por suelo para que no quede en el mapa
+    fn take_spawn_marker(&mut self) -> Option<(usize, usize)> {
+        for (y, row) in self.data.maze.iter_mut().enumerate() {
+            if let Some(x) = row.iter().position(|&cell| cell == SPAWN_MARKER) {
+                row[x] = ' ';
+                return Some((x, y));
+            }
+        }
+
+        None
+    }
+
+    // Mira hacia el pasillo abierto más largo junto al spawn (E, S, O, N en caso de empate)
+    fn spawn_facing_angle(&self, x: usize, y: usize) -> f32 {
+        let directions = [(1, 0, 0.0), (0, 1, PI / 2.0), (-1, 0, PI), (0, -1, 3.0 * PI / 2.0)];
+        let mut best_angle = PI / 4.0;
+        let mut best_run = 0;
+
+        for (dx, dy, angle) in directions {
+            let mut run = 0;
+            let mut cx = x as i32 + dx;
+            let mut cy = y as i32 + dy;
+
+            while cx >= 0 && cy >= 0 {
+                let walkable = self.data.maze
+                    .get(cy as usize)
+                    .and_then(|row| row.get(cx as usize))
+                    .is_some_and(|&cell| TileProperties::of(cell).walkable);
+                if !walkable {
+                    break;
+                }
+                run += 1;
+                cx += dx;
+                cy += dy;
+            }
+
+            if run > best_run {
+                best_run = run;
+                best_angle = angle;
+            }
+        }
+
+        best_angle
+    }
+
     fn has_spawn_clearance(&self, x: usize, y: usize) -> bool {
         if self.data.maze.is_empty() {
             return false;
@@ -172,7 +602,7 @@ impl GameState {
                     
                     if check_y < self.data.maze.len() && check_x < self.data.maze[check_y].len() {
                         let cell = self.data.maze[check_y][check_x];
-                        if matches!(cell, '#' | '+' | '-' | '|' | 'r' | 'b' | 'g') {
+                        if TileProperties::of(cell).blocks_sight {
                             return false;
                         }
                     }
@@ -211,208 +641,494 @@ impl GameState {
         }
     }
 
+    // Corre solo si lo pide mientras se desplaza y le queda estamina; devuelve
+    // la entrada con el sprint anulado cuando no puede correr
+    fn update_stamina(&mut self, input: &InputState, delta_time: f32) -> InputState {
+        let data = &mut self.data;
+        let sprinting = input.sprint && input.is_walking() && !data.stamina_exhausted && data.stamina > 0.0;
+
+        if sprinting {
+            data.stamina = (data.stamina - STAMINA_DRAIN_PER_SECOND * delta_time).max(0.0);
+            data.stamina_exhausted = data.stamina == 0.0;
+        } else {
+            data.stamina = (data.stamina + STAMINA_REGEN_PER_SECOND * delta_time).min(1.0);
+            if data.stamina >= STAMINA_RECOVERY_THRESHOLD {
+                data.stamina_exhausted = false;
+            }
+        }
+
+        data.sprinting = sprinting;
+        InputState { sprint: sprinting, ..*input }
+    }
+
+    // Fácil quita una de cada dos trampas del nivel; difícil acelera a las entidades
+    fn apply_difficulty(&mut self) {
+        let difficulty = self.data.difficulty;
+        if difficulty == Difficulty::Easy {
+            let traps = self.data.maze.iter_mut().flatten().filter(|cell| **cell == 't');
+            for cell in traps.skip(1).step_by(2) {
+                *cell = ' ';
+            }
+        }
+
+        for enemy in &mut self.data.enemies {
+            enemy.speed *= difficulty.enemy_speed_multiplier();
+        }
+    }
+
     pub fn reset(&mut self) {
         self.mode = GameMode::Welcome;
-        self.data.lives = 3;
+        self.data.lives = self.data.life_rules.starting_lives(self.data.difficulty, self.data.hardcore);
         self.data.current_level = 1;
         self.data.has_key = false;
         self.data.keys_collected = 0;
         self.data.visited_checkpoints.clear();
+        self.data.collected_items.clear();
+        self.data.consumed_cells.clear();
+        self.data.disarmed_traps.clear();
+        self.data.door_open = 0.0;
+        self.data.enemies.clear();
+        self.data.visited_cells.clear();
+        self.data.last_cell = None;
+        self.data.level_errors.clear();
+        self.data.score = 0;
+        self.data.last_level_score = None;
+        self.data.poison_timer = None;
     }
 
-    pub fn update_with_audio(&mut self, audio_manager: &mut AudioManager, block_size: usize) {
-        self.data.animation_time += 0.1;
-        self.data.notification_manager.update(0.016);
-
-        if self.data.maze.is_empty() {
+    // Con todas las llaves las puertas se deslizan durante DOOR_OPEN_SECONDS y
+    // al terminar pasan a ser casillas abiertas por las que se puede caminar
+    fn update_doors(&mut self, delta_time: f32) {
+        let data = &mut self.data;
+        if !data.has_key || !data.maze.iter().flatten().any(|&cell| cell == DOOR_CLOSED) {
             return;
         }
 
-        self.check_player_interactions_with_notifications(audio_manager, block_size);
-        
-        if self.check_win_condition_safe() {
-            self.mode = GameMode::Victory;
-            audio_manager.play_game_event(GameAudioEvent::LevelComplete);
+        if data.door_open == 0.0 {
+            data.notification_manager.show_doors_unlocked();
         }
 
-        if self.data.lives <= 0 {
-            self.mode = GameMode::GameOver;
+        data.door_open = (data.door_open + delta_time / DOOR_OPEN_SECONDS).min(1.0);
+        if data.door_open >= 1.0 {
+            for cell in data.maze.iter_mut().flatten() {
+                if *cell == DOOR_CLOSED {
+                    *cell = DOOR_OPEN;
+                }
+            }
         }
     }
 
-    fn check_player_interactions_with_notifications(&mut self, audio_manager: &mut AudioManager, block_size: usize) {
-        let player_x = self.data.player.pos.x;
-        let player_y = self.data.player.pos.y;
-        
-        if player_x < 0.0 || player_y < 0.0 || self.data.maze.is_empty() {
-            return;
+    /// Avanza un frame de juego sin renderizar ni leer raylib: movimiento,
+    /// interacciones y condiciones de victoria/derrota. Devuelve los eventos
+    /// de audio generados para que el llamador los reproduzca.
+    pub fn tick(&mut self, input: &InputState, delta_time: f32, block_size: usize) -> Vec<GameAudioEvent> {
+        let mut audio_events = Vec::new();
+
+        if self.mode != GameMode::Playing || self.data.maze.is_empty() {
+            return audio_events;
         }
 
-        let player_grid_x = (player_x / block_size as f32) as usize;
-        let player_grid_y = (player_y / block_size as f32) as usize;
+        // Durante la cuenta atrás el mundo se ve pero no hay movimiento ni peligros
+        let intro_running = self.data.intro_timer > 0.0;
+        if self.data.intro_timer > -INTRO_GO_SECONDS {
+            self.data.intro_timer = (self.data.intro_timer - delta_time).max(-INTRO_GO_SECONDS);
+        }
+        if intro_running {
+            self.data.notification_manager.update(delta_time);
+            return audio_events;
+        }
 
-        if player_grid_y >= self.data.maze.len() {
-            return;
+        self.data.level_time += delta_time;
+        let input = &self.update_stamina(input, delta_time);
+        apply_input(&mut self.data.player, input, &self.data.maze, block_size, delta_time);
+        process_mouse_input_safe(&mut self.data.player, input.mouse_delta_x);
+
+        self.data.unstuck_cooldown = (self.data.unstuck_cooldown - delta_time).max(0.0);
+        self.recover_if_inside_wall();
+
+        // Tiempo de juego real: las texturas animadas avanzan al ritmo de los frames
+        self.data.animation_time += delta_time;
+        self.data.damage_flash_timer = (self.data.damage_flash_timer - delta_time).max(0.0);
+        self.update_doors(delta_time);
+        self.data.notification_manager.update(delta_time);
+
+        // Las trampas desarmadas vuelven a estar activas al agotar su tiempo
+        self.data.disarmed_traps.retain_mut(|(_, remaining)| {
+            *remaining -= delta_time;
+            *remaining > 0.0
+        });
+
+        self.check_player_interactions_with_notifications(&mut audio_events, block_size);
+        self.update_poison(&mut audio_events, delta_time);
+        self.update_enemies(&mut audio_events, delta_time, block_size);
+        
+        if self.check_win_condition_safe() {
+            self.mode = GameMode::Victory;
+            self.score_level();
+            audio_events.push(GameAudioEvent::LevelComplete);
         }
 
-        let row_len = self.data.maze.get(player_grid_y).map(|r| r.len()).unwrap_or(0);
-        if player_grid_x >= row_len {
-            return;
+        if self.data.lives <= 0 {
+            if self.data.hardcore {
+                // En hardcore morir termina la partida entera: de vuelta al menú
+                self.clear_notifications();
+                self.reset();
+            } else {
+                self.mode = GameMode::GameOver;
+            }
         }
 
-        let current_cell = self.data.maze[player_grid_y][player_grid_x];
+        audio_events
+    }
 
-        match current_cell {
-            'k' => {
-                self.data.maze[player_grid_y][player_grid_x] = ' ';
-                self.data.keys_collected += 1;
-                if self.data.keys_collected >= self.data.keys_needed {
-                    self.data.has_key = true;
-                }
-                audio_manager.play_game_event(GameAudioEvent::KeyPickup);
-                self.data.notification_manager.show_key_collected(self.data.keys_collected, self.data.keys_needed);
-            },
-            't' => {
-                self.data.maze[player_grid_y][player_grid_x] = ' ';
-                if self.data.lives > 0 {
-                    self.data.lives -= 1;
-                }
-                
-                audio_manager.play_game_event(GameAudioEvent::TrapTriggered);
-                audio_manager.play_game_event(GameAudioEvent::PlayerHurt);
+    fn check_player_interactions_with_notifications(&mut self, audio_events: &mut Vec<GameAudioEvent>, block_size: usize) {
+        // Objetos: se recogen al tocar el sprite (o con el imán), aunque esté en otra casilla
+        for (x, y, sprite_type) in self.pickups_in_reach(block_size) {
+            match sprite_type {
+                SpriteType::KeyGold => {
+                    self.consume_cell(x, y);
+                    self.data.keys_collected += 1;
+                    self.data.collected_items.push(SpriteType::KeyGold);
+                    if self.data.keys_collected >= self.data.keys_needed {
+                        self.data.has_key = true;
+                    }
+                    audio_events.push(GameAudioEvent::KeyPickup);
+                    self.data.notification_manager.show_key_collected(self.data.keys_collected, self.data.keys_needed);
+                },
+                SpriteType::ExtraLife => {
+                    self.consume_cell(x, y);
+                    if self.data.hardcore {
+                        self.data.notification_manager.show_extra_life_refused();
+                        continue;
+                    }
+                    if !self.gain_life() {
+                        self.data.notification_manager.show_lives_full(self.data.life_rules.max);
+                        continue;
+                    }
+                    self.data.collected_items.push(SpriteType::ExtraLife);
+                    audio_events.push(GameAudioEvent::KeyPickup);
+                    self.data.notification_manager.show_extra_life(self.data.lives);
+                },
+                SpriteType::Checkpoint => {
+                    let checkpoint = (x, y);
+                    if !self.data.visited_checkpoints.contains(&checkpoint) {
+                        self.data.visited_checkpoints.push(checkpoint);
+                        audio_events.push(GameAudioEvent::CheckpointReached);
+                        self.data.notification_manager.show_checkpoint_reached(
+                            self.data.visited_checkpoints.len(), 
+                            self.data.current_level
+                        );
+                    }
+                },
+                _ => {}
+            }
+        }
+
+        // Trampas y salida siguen dependiendo de la casilla del centro del jugador
+        let Some((player_grid_x, player_grid_y)) = self.player_cell(block_size) else {
+            return;
+        };
+        self.data.visited_cells.insert((player_grid_x, player_grid_y));
+        let entered_cell = self.data.last_cell.replace((player_grid_x, player_grid_y)) != Some((player_grid_x, player_grid_y));
+
+        match self.data.maze[player_grid_y][player_grid_x] {
+            't' if !self.is_trap_disarmed(player_grid_x, player_grid_y) => {
+                self.spring_trap(player_grid_x, player_grid_y, audio_events, block_size);
+                self.lose_lives(self.data.difficulty.trap_damage());
+                audio_events.push(GameAudioEvent::PlayerHurt);
                 
                 self.data.notification_manager.show_life_lost(self.data.lives);
                 self.data.notification_manager.show_trap_activated();
                 
-                self.data.player.pos.x -= 10.0;
-                self.data.player.pos.y -= 10.0;
-                
-                if self.data.player.pos.x < 50.0 {
-                    self.data.player.pos.x = 50.0;
-                }
-                if self.data.player.pos.y < 50.0 {
-                    self.data.player.pos.y = 50.0;
-                }
+                self.apply_trap_knockback(player_grid_x, player_grid_y, block_size);
             },
-            'l' => {
-                self.data.maze[player_grid_y][player_grid_x] = ' ';
-                self.data.lives += 1;
-                audio_manager.play_game_event(GameAudioEvent::KeyPickup);
-                self.data.notification_manager.show_extra_life(self.data.lives);
+            'p' if !self.is_trap_disarmed(player_grid_x, player_grid_y) => {
+                self.spring_trap(player_grid_x, player_grid_y, audio_events, block_size);
+                // Pisar otra trampa de veneno no alarga el plazo que ya corre
+                let remaining = *self.data.poison_timer.get_or_insert(POISON_DELAY_SECONDS);
+                self.data.notification_manager.show_poisoned(remaining);
             },
-            'c' => {
-                let checkpoint = (player_grid_x, player_grid_y);
-                if !self.data.visited_checkpoints.contains(&checkpoint) {
-                    self.data.visited_checkpoints.push(checkpoint);
-                    audio_manager.play_game_event(GameAudioEvent::CheckpointReached);
-                    self.data.notification_manager.show_checkpoint_reached(
-                        self.data.visited_checkpoints.len(), 
-                        self.data.current_level
-                    );
+            'x' => {
+                // Sin destino el jugador se queda encima: solo cuenta el salto que ocurre
+                if let Some((x, y)) = self.random_teleport_destination(player_grid_x, player_grid_y, block_size) {
+                    self.data.traps_triggered += 1;
+                    self.data.player.pos = Vector2::new(x, y);
+                    self.data.player.velocity = Vector2::zero();
+                    audio_events.push(GameAudioEvent::Teleported);
+                    self.data.notification_manager.show_teleported();
                 }
             },
-            'e' => {
+            'e' if entered_cell => {
                 if !self.data.has_key {
                     self.data.notification_manager.show_exit_blocked("no_key");
-                } else {
-                    let checkpoints_needed = match self.data.current_level {
-                        1 => 0,
-                        2 => 1, 
-                        3 => 2,
-                        _ => 0,
-                    };
-
-                    if self.data.visited_checkpoints.len() < checkpoints_needed {
-                        self.data.notification_manager.show_exit_blocked("no_checkpoints");
-                    }
+                } else if self.data.visited_checkpoints.len() < checkpoints_needed(self.data.current_level) {
+                    self.data.notification_manager.show_exit_blocked("no_checkpoints");
                 }
             },
             _ => {}
         }
     }
 
-    pub fn clear_notifications(&mut self) {
-        self.data.notification_manager.clear_all();
+    // Casilla del centro del jugador, si está dentro del laberinto
+    fn player_cell(&self, block_size: usize) -> Option<(usize, usize)> {
+        let pos = self.data.player.pos;
+        if pos.x < 0.0 || pos.y < 0.0 {
+            return None;
+        }
+
+        let grid_x = (pos.x / block_size as f32) as usize;
+        let grid_y = (pos.y / block_size as f32) as usize;
+        let row = self.data.maze.get(grid_y)?;
+
+        (grid_x < row.len()).then_some((grid_x, grid_y))
     }
 
-    pub fn update(&mut self, audio_manager: &mut AudioManager, block_size: usize) {
-        self.data.animation_time += 0.1;
+    // Objetos recogibles que el jugador toca: distancia en el mundo entre el
+    // jugador y el sprite de la casilla, menor que la suma de sus radios. Con
+    // el imán se suma además el más cercano a menos de PICKUP_MAGNET_RADIUS
+    // de ese contacto, siempre que no haya una pared en medio
+    fn pickups_in_reach(&self, block_size: usize) -> Vec<(usize, usize, SpriteType)> {
+        let Some((grid_x, grid_y)) = self.player_cell(block_size) else {
+            return Vec::new();
+        };
 
-        if self.data.maze.is_empty() {
-            return;
+        let player = &self.data.player;
+        let mut pickups = Vec::new();
+        let mut nearest_magnet: Option<(f32, (usize, usize, SpriteType))> = None;
+
+        for y in grid_y.saturating_sub(1)..=grid_y + 1 {
+            for x in grid_x.saturating_sub(1)..=grid_x + 1 {
+                let Some(&cell) = self.data.maze.get(y).and_then(|row| row.get(x)) else {
+                    continue;
+                };
+                let Some(sprite_type) = SpriteType::from_char(cell) else {
+                    continue;
+                };
+                let Some(pickup_radius) = sprite_type.pickup_radius() else {
+                    continue;
+                };
+
+                let sprite = Sprite::new(
+                    sprite_type,
+                    (x * block_size) as f32 + block_size as f32 * 0.5,
+                    (y * block_size) as f32 + block_size as f32 * 0.5,
+                );
+                let distance = sprite.distance_to_player(player);
+                let contact = pickup_radius + player.radius;
+                if distance <= contact {
+                    pickups.push((x, y, sprite_type));
+                } else if distance <= contact + PICKUP_MAGNET_RADIUS
+                    && nearest_magnet.is_none_or(|(nearest, _)| distance < nearest)
+                    && self.reachable_from((grid_x, grid_y), (x, y))
+                {
+                    nearest_magnet = Some((distance, (x, y, sprite_type)));
+                }
+            }
         }
 
-        self.check_player_interactions_simple(audio_manager, block_size);
-        
-        if self.check_win_condition_safe() {
-            self.mode = GameMode::Victory;
+        pickups.extend(nearest_magnet.map(|(_, pickup)| pickup));
+        pickups
+    }
+
+    // Casilla vecina alcanzable sin atravesar paredes: las de los lados
+    // siempre lo son; en diagonal las dos casillas de la esquina deben estar libres
+    fn reachable_from(&self, (from_x, from_y): (usize, usize), (x, y): (usize, usize)) -> bool {
+        if from_x == x || from_y == y {
+            return true;
         }
 
-        if self.data.lives <= 0 {
-            self.mode = GameMode::GameOver;
+        [(x, from_y), (from_x, y)].iter().all(|&(cx, cy)| {
+            self.data.maze
+                .get(cy)
+                .and_then(|row| row.get(cx))
+                .is_some_and(|&cell| TileProperties::of(cell).walkable)
+        })
+    }
+
+    // Según el nivel la trampa desaparece o se retrae hasta rearmarse; suena
+    // desde su casilla y cuenta para la puntuación
+    fn spring_trap(&mut self, x: usize, y: usize, audio_events: &mut Vec<GameAudioEvent>, block_size: usize) {
+        let rearm_seconds = self.data.level_meta.trap_rearm_seconds;
+        if rearm_seconds > 0.0 {
+            self.data.disarmed_traps.push(((x, y), rearm_seconds));
+        } else {
+            self.consume_cell(x, y);
         }
+        self.data.traps_triggered += 1;
+
+        let half_block = block_size as f32 * 0.5;
+        audio_events.push(GameAudioEvent::TrapTriggered {
+            x: (x * block_size) as f32 + half_block,
+            y: (y * block_size) as f32 + half_block,
+        });
     }
 
-    fn check_player_interactions_simple(&mut self, _audio_manager: &mut AudioManager, block_size: usize) {
-        let player_x = self.data.player.pos.x;
-        let player_y = self.data.player.pos.y;
-        
-        if player_x < 0.0 || player_y < 0.0 || self.data.maze.is_empty() {
+    // Casilla de suelo libre al azar, distinta de la actual, donde cabe el
+    // jugador y a la que se llega caminando desde el inicio del nivel (nunca
+    // un hueco sellado)
+    fn random_teleport_destination(&mut self, from_x: usize, from_y: usize, block_size: usize) -> Option<(f32, f32)> {
+        let half_block = block_size as f32 * 0.5;
+        let radius = self.data.player.radius;
+        let maze = &self.data.maze;
+        let reachable = reachable_cells(maze, self.data.spawn_cell);
+        let candidates: Vec<(f32, f32)> = maze.iter()
+            .enumerate()
+            .flat_map(|(y, row)| row.iter().enumerate().map(move |(x, &cell)| (x, y, cell)))
+            .filter(|&(x, y, cell)| cell == ' ' && (x, y) != (from_x, from_y) && reachable.contains(&(x, y)))
+            .map(|(x, y, _)| ((x * block_size) as f32 + half_block, (y * block_size) as f32 + half_block))
+            .filter(|&(x, y)| self.can_move_to_safe(x, y, radius))
+            .collect();
+
+        candidates.choose(&mut self.data.rng).copied()
+    }
+
+    // Cuenta atrás del veneno: al agotarse quita una vida y se cura
+    fn update_poison(&mut self, audio_events: &mut Vec<GameAudioEvent>, delta_time: f32) {
+        let Some(remaining) = self.data.poison_timer.as_mut() else {
+            return;
+        };
+
+        *remaining -= delta_time;
+        if *remaining > 0.0 {
             return;
         }
 
-        let player_grid_x = (player_x / block_size as f32) as usize;
-        let player_grid_y = (player_y / block_size as f32) as usize;
+        self.lose_lives(1);
+        audio_events.push(GameAudioEvent::PlayerHurt);
+        self.data.notification_manager.show_life_lost(self.data.lives);
+        self.data.notification_manager.show_poison_drained();
+    }
 
-        if player_grid_y >= self.data.maze.len() {
+    // Suma una vida si no se llegó al máximo de las reglas; false si ya estaba lleno
+    fn gain_life(&mut self) -> bool {
+        if self.data.lives >= self.data.life_rules.max {
+            return false;
+        }
+        self.data.lives += 1;
+        true
+    }
+
+    // Quita vidas con el destello rojo y frena al jugador, que vuelve a
+    // acelerar desde cero. El destello se reinicia en vez de acumularse, así
+    // que varios golpes seguidos no dejan la pantalla roja. La vida ya se
+    // perdió: el veneno pendiente no quita otra
+    fn lose_lives(&mut self, amount: i32) {
+        self.data.lives = (self.data.lives - amount).max(0);
+        self.data.poison_timer = None;
+        self.data.damage_flash_timer = DAMAGE_FLASH_SECONDS;
+        self.data.player.velocity = Vector2::zero();
+    }
+
+    fn is_trap_disarmed(&self, x: usize, y: usize) -> bool {
+        self.data.disarmed_traps.iter().any(|(cell, _)| *cell == (x, y))
+    }
+
+    fn consume_cell(&mut self, x: usize, y: usize) {
+        self.data.maze[y][x] = ' ';
+        self.data.consumed_cells.push((x, y));
+    }
+
+    // Red de seguridad: si el centro del jugador quedó en una casilla no
+    // caminable, moverlo a la posición segura más cercana
+    fn recover_if_inside_wall(&mut self) {
+        let pos = self.data.player.pos;
+        if self.data.unstuck_cooldown > 0.0 || self.is_position_safe(pos.x, pos.y) {
             return;
         }
 
-        let row_len = self.data.maze.get(player_grid_y).map(|r| r.len()).unwrap_or(0);
-        if player_grid_x >= row_len {
+        if let Some((safe_x, safe_y)) = self.find_nearest_safe_position(pos.x, pos.y, self.data.player.radius) {
+            eprintln!(
+                "Jugador dentro de una pared en ({:.1}, {:.1}), nivel {}; movido a ({:.1}, {:.1})",
+                pos.x, pos.y, self.data.current_level, safe_x, safe_y
+            );
+            self.data.player.pos = Vector2::new(safe_x, safe_y);
+            self.data.player.velocity = Vector2::zero();
+            self.data.unstuck_cooldown = UNSTUCK_COOLDOWN;
+        }
+    }
+
+    // Mueve las entidades; la que toca al jugador le quita una vida y lo
+    // empuja como una trampa, y luego espera antes de volver a atacar
+    fn update_enemies(&mut self, audio_events: &mut Vec<GameAudioEvent>, delta_time: f32, block_size: usize) {
+        let player_pos = self.data.player.pos;
+        let player_radius = self.data.player.radius;
+        let mut attacker = None;
+
+        for enemy in &mut self.data.enemies {
+            let was_idle = enemy.state == EnemyState::Idle;
+            enemy.update(player_pos, &self.data.maze, block_size, delta_time);
+            if was_idle && enemy.state == EnemyState::Chasing {
+                audio_events.push(GameAudioEvent::EnemyAlerted { x: enemy.pos.x, y: enemy.pos.y });
+            }
+            if attacker.is_none() && enemy.hit_cooldown <= 0.0 && enemy.touches(player_pos, player_radius) {
+                enemy.hit_cooldown = ENEMY_HIT_COOLDOWN;
+                attacker = Some(enemy.pos);
+            }
+        }
+
+        let Some(enemy_pos) = attacker else {
             return;
+        };
+
+        self.lose_lives(1);
+        audio_events.push(GameAudioEvent::PlayerHurt);
+        self.data.notification_manager.show_life_lost(self.data.lives);
+        self.data.notification_manager.show_enemy_hit();
+        self.apply_knockback_from(enemy_pos.x, enemy_pos.y);
+    }
+
+    // Empuja al jugador alejándolo del centro de la trampa, sin meterlo en muros
+    fn apply_trap_knockback(&mut self, trap_grid_x: usize, trap_grid_y: usize, block_size: usize) {
+        let trap_center_x = trap_grid_x as f32 * block_size as f32 + block_size as f32 * 0.5;
+        let trap_center_y = trap_grid_y as f32 * block_size as f32 + block_size as f32 * 0.5;
+        self.apply_knockback_from(trap_center_x, trap_center_y);
+    }
+
+    // Empuja al jugador alejándolo de un punto del mundo, sin meterlo en muros
+    fn apply_knockback_from(&mut self, source_x: f32, source_y: f32) {
+        let mut dir_x = self.data.player.pos.x - source_x;
+        let mut dir_y = self.data.player.pos.y - source_y;
+        let length = (dir_x * dir_x + dir_y * dir_y).sqrt();
+
+        if length > 0.001 {
+            dir_x /= length;
+            dir_y /= length;
+        } else {
+            // Justo en el centro: retroceder en contra de la mirada
+            dir_x = -self.data.player.a.cos();
+            dir_y = -self.data.player.a.sin();
         }
 
-        let current_cell = self.data.maze[player_grid_y][player_grid_x];
+        let radius = self.data.player.radius;
+        let mut distance = self.data.level_meta.trap_knockback_distance.max(0.0);
+
+        // Acortar el empuje hasta encontrar un destino libre
+        while distance >= 1.0 {
+            let new_x = self.data.player.pos.x + dir_x * distance;
+            let new_y = self.data.player.pos.y + dir_y * distance;
 
-        match current_cell {
-            'k' => {
-                self.data.maze[player_grid_y][player_grid_x] = ' ';
-                self.data.keys_collected += 1;
-                if self.data.keys_collected >= self.data.keys_needed {
-                    self.data.has_key = true;
-                }
-            },
-            't' => {
-                self.data.maze[player_grid_y][player_grid_x] = ' ';
-                if self.data.lives > 0 {
-                    self.data.lives -= 1;
-                }
-                
-                self.data.player.pos.x -= 10.0;
-                self.data.player.pos.y -= 10.0;
-                
-                if self.data.player.pos.x < 50.0 {
-                    self.data.player.pos.x = 50.0;
-                }
-                if self.data.player.pos.y < 50.0 {
-                    self.data.player.pos.y = 50.0;
-                }
-            },
-            'l' => {
-                self.data.maze[player_grid_y][player_grid_x] = ' ';
-                self.data.lives += 1;
-            },
-            'c' => {
-                let checkpoint = (player_grid_x, player_grid_y);
-                if !self.data.visited_checkpoints.contains(&checkpoint) {
-                    self.data.visited_checkpoints.push(checkpoint);
-                }
-            },
-            _ => {}
+            if self.can_move_to_safe(new_x, new_y, radius) {
+                self.data.player.pos.x = new_x;
+                self.data.player.pos.y = new_y;
+                return;
+            }
+
+            distance *= 0.5;
         }
     }
 
+    // Suma a la partida los puntos del nivel recién completado
+    fn score_level(&mut self) {
+        let data = &mut self.data;
+        let level_score = LevelScore::new(data.current_level, data.lives, data.level_time, data.traps_triggered);
+        data.score += level_score.total();
+        data.last_level_score = Some(level_score);
+    }
+
+    pub fn clear_notifications(&mut self) {
+        self.data.notification_manager.clear_all();
+    }
+
     fn is_position_safe(&self, x: f32, y: f32) -> bool {
         if x < 0.0 || y < 0.0 {
             return false;
@@ -434,8 +1150,7 @@ impl GameState {
                 return false;
             }
             
-            let cell = row[grid_x];
-            matches!(cell, ' ' | 'k' | 't' | 'l' | 'c' | 'e')
+            TileProperties::of(row[grid_x]).walkable
         } else {
             false
         }
@@ -484,19 +1199,12 @@ impl GameState {
                 return false;
             }
 
-            let current_cell = row[player_grid_x];
-            
-            if current_cell == 'e' {
-                let checkpoints_needed = match self.data.current_level {
-                    1 => 0,
-                    2 => 1,
-                    3 => 2,
-                    _ => 0,
-                };
-
-                let has_required_checkpoints = self.data.visited_checkpoints.len() >= checkpoints_needed;
-                return self.data.has_key && has_required_checkpoints;
-            }
+            return wins_at_cell(
+                row[player_grid_x],
+                self.data.has_key,
+                self.data.visited_checkpoints.len(),
+                self.data.current_level,
+            );
         }
         
         false
@@ -520,21 +1228,8 @@ impl GameState {
     }
 
     fn can_move_to_safe(&self, x: f32, y: f32, radius: f32) -> bool {
-        let points = [
-            (x, y),
-            (x + radius, y),
-            (x - radius, y),
-            (x, y + radius),
-            (x, y - radius),
-            (x + radius * 0.7, y + radius * 0.7),
-            (x - radius * 0.7, y - radius * 0.7),
-            (x + radius * 0.7, y - radius * 0.7),
-            (x - radius * 0.7, y + radius * 0.7),
-        ];
-
-        points.iter().all(|&(px, py)| {
-            self.is_position_safe(px, py)
-        })
+        // Misma geometría que el movimiento para no dejarlo en un lugar atascado
+        can_move_to_safe(x, y, &self.data.maze, 100, radius)
     }
 
     fn find_nearest_safe_position(&self, x: f32, y: f32, radius: f32) -> Option<(f32, f32)> {
@@ -565,4 +1260,671 @@ impl GameState {
         
         None
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notification::NotificationType;
+    use crate::player::{MAX_PITCH, MIN_FOV};
+
+    const BLOCK: usize = 100;
+
+    // Estado en juego con el jugador en la celda (1, 1) mirando hacia abajo
+    fn playing_state(rows: &[&str]) -> GameState {
+        let mut state = GameState::new();
+        state.mode = GameMode::Playing;
+        state.data.maze = rows.iter().map(|row| row.chars().collect()).collect();
+        state.data.player.pos = Vector2::new(150.0, 150.0);
+        state.data.player.a = PI / 2.0;
+        state
+    }
+
+    fn run_frames(state: &mut GameState, input: &InputState, frames: usize) -> Vec<GameAudioEvent> {
+        let mut events = Vec::new();
+        for _ in 0..frames {
+            events.extend(state.tick(input, 0.016, BLOCK));
+        }
+        events
+    }
+
+    fn forward() -> InputState {
+        InputState { forward: true, ..InputState::default() }
+    }
+
+    #[test]
+    fn walking_over_key_then_exit_wins_the_level() {
+        let mut state = playing_state(&["###", "# #", "#k#", "#e#", "###"]);
+
+        let events = run_frames(&mut state, &forward(), 20);
+
+        assert!(state.data.has_key);
+        assert_eq!(state.data.maze[2][1], ' ');
+        assert!(state.mode == GameMode::Victory);
+        assert!(events.contains(&GameAudioEvent::KeyPickup));
+        assert_eq!(events.last(), Some(&GameAudioEvent::LevelComplete));
+    }
+
+    #[test]
+    fn completing_a_level_adds_its_breakdown_to_the_run_score() {
+        let mut state = playing_state(&["###", "# #", "#k#", "#e#", "###"]);
+        state.data.score = 500;
+        state.data.traps_triggered = 1;
+
+        run_frames(&mut state, &forward(), 20);
+
+        let expected = LevelScore::new(1, state.data.lives, state.data.level_time, 1);
+        assert_eq!(state.data.last_level_score, Some(expected));
+        assert_eq!(state.data.score, 500 + expected.total());
+
+        // Una partida nueva vuelve a empezar de cero
+        state.begin_run(1);
+        assert_eq!((state.data.score, state.data.last_level_score), (0, None));
+    }
+
+    #[test]
+    fn exit_without_key_keeps_playing() {
+        let mut state = playing_state(&["###", "# #", "# #", "#e#", "###"]);
+
+        let events = run_frames(&mut state, &forward(), 20);
+
+        assert!(state.mode == GameMode::Playing);
+        assert!(!events.contains(&GameAudioEvent::LevelComplete));
+        // La pared inferior detiene al jugador dentro de la celda de salida
+        assert_eq!((state.data.player.pos.y / BLOCK as f32) as usize, 3);
+    }
+
+    #[test]
+    fn trap_costs_a_life_and_is_consumed() {
+        let mut state = playing_state(&["###", "# #", "#t#", "# #", "###"]);
+
+        let events = run_frames(&mut state, &forward(), 4);
+
+        assert_eq!(state.data.lives, 2);
+        assert_eq!(state.data.maze[2][1], ' ');
+        assert_eq!(state.data.traps_triggered, 1);
+        assert_eq!(events, vec![GameAudioEvent::TrapTriggered { x: 150.0, y: 250.0 }, GameAudioEvent::PlayerHurt]);
+    }
+
+    #[test]
+    fn poison_drains_a_life_after_its_delay_unless_a_life_is_lost_first() {
+        let mut state = playing_state(&["###", "# #", "#p#", "# #", "###"]);
+
+        let events = run_frames(&mut state, &forward(), 4);
+        assert_eq!(state.data.maze[2][1], ' ');
+        assert_eq!(state.data.lives, 3);
+        assert_eq!(state.data.poison_timer.map(|t| t > POISON_DELAY_SECONDS - 0.1), Some(true));
+        assert_eq!(events, vec![GameAudioEvent::TrapTriggered { x: 150.0, y: 250.0 }]);
+
+        let frames = (POISON_DELAY_SECONDS / 0.016) as usize + 1;
+        let events = run_frames(&mut state, &InputState::default(), frames);
+        assert_eq!(state.data.lives, 2);
+        assert_eq!(state.data.poison_timer, None);
+        assert_eq!(events, vec![GameAudioEvent::PlayerHurt]);
+
+        // Una trampa normal antes de que actúe el veneno lo cura
+        let mut state = playing_state(&["###", "# #", "#p#", "#t#", "# #", "###"]);
+        run_frames(&mut state, &forward(), 20);
+        assert_eq!(state.data.lives, 2);
+        assert_eq!(state.data.poison_timer, None);
+        run_frames(&mut state, &InputState::default(), frames);
+        assert_eq!(state.data.lives, 2);
+    }
+
+    #[test]
+    fn teleport_traps_warp_the_player_to_free_floor() {
+        // El jugador empieza sobre la trampa; el único suelo libre es (3, 2)
+        let mut state = playing_state(&["#####", "#x###", "#xx #", "#####"]);
+
+        let events = run_frames(&mut state, &InputState::default(), 1);
+
+        assert_eq!(state.data.player.pos, Vector2::new(350.0, 250.0));
+        assert_eq!(state.data.maze[1][1], 'x');
+        assert_eq!(state.data.lives, 3);
+        assert!(events.contains(&GameAudioEvent::Teleported));
+        assert_eq!(state.data.traps_triggered, 1);
+    }
+
+    #[test]
+    fn a_teleport_trap_with_nowhere_to_go_is_not_counted_every_frame() {
+        let mut state = playing_state(&["###", "#x#", "###"]);
+
+        let events = run_frames(&mut state, &InputState::default(), 30);
+
+        assert_eq!(state.data.player.pos, Vector2::new(150.0, 150.0));
+        assert_eq!(state.data.traps_triggered, 0);
+        assert!(!events.contains(&GameAudioEvent::Teleported));
+    }
+
+    #[test]
+    fn teleport_traps_skip_sealed_pockets_and_repeat_with_the_seed() {
+        // (3, 2) es suelo libre pero está encerrado; solo se llega a (1, 2), (1, 3) y (2, 3)
+        let rows = ["#####", "#x###", "# # #", "#  ##", "#####"];
+        let destinations = |seed: u64| -> Vec<Vector2> {
+            let mut state = playing_state(&rows);
+            state.data.rng = StdRng::seed_from_u64(seed);
+            (0..8)
+                .map(|_| {
+                    state.data.player.pos = Vector2::new(150.0, 150.0);
+                    run_frames(&mut state, &InputState::default(), 1);
+                    state.data.player.pos
+                })
+                .collect()
+        };
+
+        for seed in 0..10 {
+            let warps = destinations(seed);
+            assert!(warps.iter().all(|pos| pos.y > 200.0 && pos.x < 300.0), "semilla {}: {:?}", seed, warps);
+            assert_eq!(warps, destinations(seed));
+        }
+    }
+
+    #[test]
+    fn losing_a_life_flashes_red_briefly_without_stacking() {
+        let mut state = playing_state(&["###", "# #", "#t#", "# #", "###"]);
+
+        run_frames(&mut state, &forward(), 4);
+        assert_eq!(state.data.lives, 2);
+        let flash = state.data.damage_flash_timer;
+        assert!(flash > 0.0 && flash <= DAMAGE_FLASH_SECONDS);
+
+        // Un segundo golpe vuelve a empezar el destello sin alargarlo
+        state.data.lives = 5;
+        state.lose_lives(1);
+        state.lose_lives(1);
+        assert_eq!(state.data.damage_flash_timer, DAMAGE_FLASH_SECONDS);
+
+        run_frames(&mut state, &InputState::default(), 30);
+        assert_eq!(state.data.damage_flash_timer, 0.0);
+    }
+
+    #[test]
+    fn rearming_trap_fires_again_after_its_cooldown() {
+        let mut state = playing_state(&["###", "#t#", "###"]);
+        state.data.level_meta.trap_rearm_seconds = 1.0;
+        state.data.level_meta.trap_knockback_distance = 0.0;
+        let idle = InputState::default();
+
+        // Dispara, y durante el segundo siguiente queda retraída
+        for _ in 0..4 {
+            state.tick(&idle, 0.25, BLOCK);
+        }
+        assert_eq!(state.data.lives, 2);
+        assert_eq!(state.data.maze[1][1], 't');
+        assert_eq!(state.data.disarmed_traps.len(), 1);
+
+        state.tick(&idle, 0.25, BLOCK);
+        assert_eq!(state.data.lives, 1);
+        assert!(state.data.consumed_cells.is_empty());
+    }
+
+    #[test]
+    fn trap_pushes_the_player_away_from_its_center_by_the_level_distance() {
+        let mut state = playing_state(&["#####", "#   #", "# t #", "#   #", "#####"]);
+        state.data.level_meta.trap_knockback_distance = 60.0;
+        // Al este y un poco al sur del centro de la trampa (250, 250)
+        state.data.player.pos = Vector2::new(280.0, 290.0);
+
+        state.tick(&InputState::default(), 0.016, BLOCK);
+        assert_eq!(state.data.lives, 2);
+        let pos = state.data.player.pos;
+        assert!((pos.x - 316.0).abs() < 0.01 && (pos.y - 338.0).abs() < 0.01, "{:?}", pos);
+    }
+
+    #[test]
+    fn trap_against_a_wall_shortens_the_push_and_never_ends_inside_it() {
+        // Trampa pegada a la pared este (x = 400)
+        let mut state = playing_state(&["#####", "#  t#", "#####"]);
+        state.data.player.pos = Vector2::new(355.0, 150.0);
+
+        state.tick(&InputState::default(), 0.016, BLOCK);
+        assert_eq!(state.data.lives, 2);
+        assert_eq!(state.data.player.pos, Vector2::new(375.0, 150.0));
+
+        // Sin sitio para ningún empuje el jugador se queda donde está
+        let mut state = playing_state(&["#####", "#  t#", "#####"]);
+        state.data.level_meta.trap_knockback_distance = 100.0;
+        state.data.player.pos = Vector2::new(379.0, 150.0);
+
+        state.tick(&InputState::default(), 0.016, BLOCK);
+        let pos = state.data.player.pos;
+        assert!(pos.x + state.data.player.radius <= 400.0, "{:?}", pos);
+        assert!(state.can_move_to_safe(pos.x, pos.y, state.data.player.radius));
+    }
+
+    #[test]
+    fn level_intro_locks_movement_and_hazards_until_go() {
+        let mut state = playing_state(&["###", "# #", "#t#", "# #", "###"]);
+        state.data.intro_timer = LEVEL_INTRO_SECONDS;
+        let start = state.data.player.pos;
+
+        assert_eq!(state.intro_countdown(), Some(3));
+        let events = run_frames(&mut state, &forward(), 150);
+        assert_eq!(state.data.player.pos, start);
+        assert!(events.is_empty());
+        assert_eq!(state.intro_countdown(), Some(1));
+
+        // Pasados los 3 segundos se muestra el GO! y ya se puede avanzar
+        run_frames(&mut state, &forward(), 40);
+        assert_eq!(state.intro_countdown(), Some(0));
+        assert_ne!(state.data.player.pos, start);
+
+        run_frames(&mut state, &forward(), 60);
+        assert_eq!(state.intro_countdown(), None);
+        assert_eq!(state.data.lives, 2);
+    }
+
+    #[test]
+    fn losing_the_last_life_ends_the_game() {
+        let mut state = playing_state(&["###", "# #", "#t#", "# #", "###"]);
+        state.data.lives = 1;
+
+        run_frames(&mut state, &forward(), 4);
+
+        assert!(state.mode == GameMode::GameOver);
+    }
+
+    #[test]
+    fn hardcore_runs_have_one_life_and_dying_returns_to_the_menu() {
+        let mut state = playing_state(&["###", "# #", "#l#", "# #", "#t#", "# #", "###"]);
+        state.data.hardcore = true;
+        state.begin_run(3);
+        assert!(state.mode == GameMode::Loading(1));
+        assert_eq!(state.data.lives, HARDCORE_LIVES);
+        state.mode = GameMode::Playing;
+
+        // La vida extra se consume pero no suma
+        run_frames(&mut state, &forward(), 4);
+        assert_eq!(state.data.maze[2][1], ' ');
+        assert_eq!(state.data.lives, HARDCORE_LIVES);
+        assert!(state.mode == GameMode::Playing);
+
+        // La trampa termina la partida entera, sin pantalla de game over
+        run_frames(&mut state, &forward(), 20);
+        assert!(state.mode == GameMode::Welcome);
+        assert!(state.data.hardcore);
+        assert_eq!(state.data.current_level, 1);
+    }
+
+    #[test]
+    fn escape_only_quits_outside_a_running_level() {
+        assert!(GameMode::Welcome.escape_quits());
+        assert!(GameMode::GameOver.escape_quits());
+        assert!(GameMode::Victory.escape_quits());
+        assert!(!GameMode::Playing.escape_quits());
+        assert!(!GameMode::Paused.escape_quits());
+        assert!(!GameMode::LevelError.escape_quits());
+    }
+
+    #[test]
+    fn difficulty_sets_lives_trap_count_and_trap_damage() {
+        let mut state = playing_state(&["#####", "#t t#", "# t #", "#t  #", "#####"]);
+        state.data.difficulty = Difficulty::Easy;
+        state.begin_run(1);
+        assert_eq!(state.data.lives, 5);
+        // Volver al menú deja las vidas de la misma dificultad
+        state.data.lives = 1;
+        state.reset();
+        assert_eq!(state.data.lives, 5);
+
+        // Una de cada dos trampas desaparece, en orden de lectura
+        state.apply_difficulty();
+        let traps: Vec<(usize, usize)> = state.data.maze.iter().enumerate()
+            .flat_map(|(y, row)| row.iter().enumerate().filter(|(_, &cell)| cell == 't').map(move |(x, _)| (x, y)))
+            .collect();
+        assert_eq!(traps, vec![(1, 1), (2, 2)]);
+
+        let mut state = playing_state(&["###", "# #", "#t#", "# #", "###"]);
+        state.data.difficulty = Difficulty::Hard;
+        state.begin_run(1);
+        assert_eq!(state.data.lives, 2);
+        state.mode = GameMode::Playing;
+        state.data.lives = 3;
+        run_frames(&mut state, &forward(), 4);
+        assert_eq!(state.data.lives, 1);
+    }
+
+    #[test]
+    fn extra_lives_and_level_refills_stop_at_the_life_cap() {
+        let mut state = playing_state(&["####", "# l#", "####"]);
+        state.data.lives = MAX_LIVES;
+        state.data.player.pos = Vector2::new(196.0, 150.0);
+        run_frames(&mut state, &InputState::default(), 1);
+
+        // Con el máximo la vida extra se consume sin sumar
+        assert_eq!(state.data.lives, MAX_LIVES);
+        assert_eq!(state.data.maze[1][2], ' ');
+        assert!(state.data.collected_items.is_empty());
+
+        // Pasar de nivel devuelve una vida, pero no en hardcore ni por encima del tope
+        state.data.lives = 2;
+        state.advance_level();
+        assert_eq!(state.data.lives, 3);
+        assert!(state.mode == GameMode::Loading(2));
+        state.data.lives = MAX_LIVES;
+        state.advance_level();
+        assert_eq!(state.data.lives, MAX_LIVES);
+        state.data.hardcore = true;
+        state.data.lives = 1;
+        state.advance_level();
+        assert_eq!(state.data.lives, 1);
+
+        // La base de vidas viene de los settings y la dificultad la ajusta
+        let rules = LifeRules { starting: 5, ..LifeRules::default() };
+        assert_eq!(rules.starting_lives(Difficulty::Normal, false), 5);
+        assert_eq!(rules.starting_lives(Difficulty::Easy, false), MAX_LIVES);
+        assert_eq!(rules.starting_lives(Difficulty::Hard, false), 4);
+        assert_eq!(rules.starting_lives(Difficulty::Hard, true), HARDCORE_LIVES);
+    }
+
+    #[test]
+    fn spawn_marker_sets_position_and_faces_open_corridor() {
+        let mut state = playing_state(&[
+            "#######",
+            "#     #",
+            "##S####",
+            "## ####",
+            "## ####",
+            "#######",
+        ]);
+
+        state.set_player_start_position(1);
+
+        assert_eq!(state.data.player.pos, Vector2::new(250.0, 250.0));
+        assert_eq!(state.data.player.a, PI / 2.0);
+        assert_eq!(state.data.maze[2][2], ' ');
+    }
+
+    #[test]
+    fn starting_a_level_levels_the_view_but_keeps_the_zoom() {
+        let mut state = playing_state(&["#####", "#   #", "#####"]);
+        state.data.player.pitch = MAX_PITCH;
+        state.data.player.fov = MIN_FOV;
+
+        state.set_player_start_position(1);
+
+        assert_eq!(state.data.player.pitch, 0.0);
+        assert_eq!(state.data.player.fov, MIN_FOV);
+    }
+
+    #[test]
+    fn missing_spawn_marker_falls_back_to_scanning() {
+        let mut state = playing_state(&["#####", "#   #", "#   #", "#   #", "#####"]);
+
+        state.set_player_start_position(1);
+
+        assert_eq!(state.data.player.pos, Vector2::new(250.0, 250.0));
+    }
+
+    #[test]
+    fn player_inside_a_wall_is_moved_out_with_cooldown() {
+        let mut state = playing_state(&["#####", "#   #", "#   #", "#####"]);
+        state.data.player.pos = Vector2::new(50.0, 150.0);
+
+        state.tick(&InputState::default(), 0.016, BLOCK);
+        let rescued = state.data.player.pos;
+        assert!(state.is_position_safe(rescued.x, rescued.y));
+
+        // Durante el cooldown no se vuelve a mover aunque quede en una pared
+        state.data.player.pos = Vector2::new(50.0, 150.0);
+        state.tick(&InputState::default(), 0.016, BLOCK);
+        assert_eq!(state.data.player.pos, Vector2::new(50.0, 150.0));
+    }
+
+    #[test]
+    fn exit_requirements_list_missing_keys_and_checkpoints() {
+        let mut state = playing_state(&["#####", "# k #", "#####"]);
+        state.data.current_level = 3;
+        state.data.keys_needed = 3;
+        state.data.keys_collected = 1;
+        state.data.visited_checkpoints.push((1, 1));
+
+        let requirements = state.data.exit_requirements();
+        assert_eq!(requirements, ExitRequirements { keys_missing: 2, checkpoints_missing: 1 });
+        assert_eq!(requirements.lock_label().as_deref(), Some("FALTAN 2 LLAVES - 1 CHECKPOINT"));
+
+        state.data.has_key = true;
+        state.data.visited_checkpoints.push((2, 1));
+        assert!(state.data.exit_requirements().is_met());
+        assert_eq!(state.data.exit_requirements().lock_label(), None);
+    }
+
+    #[test]
+    fn winning_needs_the_exit_the_key_and_the_level_checkpoints() {
+        assert!(wins_at_cell('e', true, 0, 1));
+        assert!(!wins_at_cell('e', false, 0, 1));
+        assert!(!wins_at_cell('e', true, 1, 3));
+        assert!(wins_at_cell('e', true, 2, 3));
+
+        // Fuera de la salida no se gana aunque se cumpla todo
+        for cell in [' ', 'k', 'c', '#'] {
+            assert!(!wins_at_cell(cell, true, 2, 3));
+        }
+    }
+
+    #[test]
+    fn notifications_last_their_duration_in_seconds_at_any_frame_rate() {
+        for fps in [30.0, 60.0, 144.0] {
+            let mut state = playing_state(&["#####", "#   #", "#####"]);
+            state.data.notification_manager.add_notification("HOLA", NotificationType::Info, 2.0);
+            let run_seconds = |state: &mut GameState, seconds: f32| {
+                for _ in 0..(seconds * fps) as usize {
+                    state.tick(&InputState::default(), 1.0 / fps, BLOCK);
+                }
+            };
+
+            run_seconds(&mut state, 1.9);
+            assert_eq!(state.data.notification_manager.notifications.len(), 1, "{} FPS", fps);
+            run_seconds(&mut state, 0.2);
+            assert!(state.data.notification_manager.notifications.is_empty(), "{} FPS", fps);
+        }
+    }
+
+    #[test]
+    fn blocked_exit_warns_once_per_approach() {
+        let mut state = playing_state(&["#####", "# e #", "#####"]);
+        let idle = InputState::default();
+        run_frames(&mut state, &idle, 1);
+        assert!(state.data.notification_manager.notifications.is_empty());
+
+        state.data.player.pos = Vector2::new(250.0, 150.0);
+        run_frames(&mut state, &idle, 1);
+        assert_eq!(state.data.notification_manager.notifications.len(), 1);
+
+        // Quedarse sobre la salida no repite el aviso
+        state.clear_notifications();
+        run_frames(&mut state, &idle, 120);
+        assert!(state.data.notification_manager.notifications.is_empty());
+
+        // Salir y volver a entrar sí
+        state.data.player.pos = Vector2::new(150.0, 150.0);
+        run_frames(&mut state, &idle, 1);
+        state.data.player.pos = Vector2::new(250.0, 150.0);
+        run_frames(&mut state, &idle, 1);
+        assert_eq!(state.data.notification_manager.notifications.len(), 1);
+    }
+
+    #[test]
+    fn touching_a_key_in_the_next_cell_collects_it() {
+        // Llave en la casilla (3, 1); el jugador se queda justo antes del borde
+        let mut state = playing_state(&["#####", "#  k#", "#####"]);
+        state.data.player.pos = Vector2::new(296.0, 150.0);
+
+        state.tick(&InputState::default(), 0.016, 100);
+
+        assert_eq!(state.data.keys_collected, 1);
+        assert_eq!(state.data.maze[1][3], ' ');
+        assert_eq!(state.data.consumed_cells, vec![(3, 1)]);
+    }
+
+    #[test]
+    fn magnet_collects_the_nearest_neighbouring_pickup_but_not_through_walls() {
+        // Dos llaves en las casillas vecinas: el imán se lleva solo la más cercana
+        let mut state = playing_state(&["#####", "#k k#", "#####"]);
+        state.data.keys_needed = 2;
+        state.data.player.pos = Vector2::new(252.0, 150.0);
+        state.tick(&InputState::default(), 0.016, BLOCK);
+        assert_eq!(state.data.keys_collected, 1);
+        assert_eq!(state.data.maze[1][3], ' ');
+        assert_eq!(state.data.maze[1][1], 'k');
+
+        // En diagonal tras la esquina de una pared no se recoge
+        let mut state = playing_state(&["####", "# ##", "##k#", "####"]);
+        state.data.player.pos = Vector2::new(180.0, 180.0);
+        run_frames(&mut state, &InputState::default(), 5);
+        assert_eq!(state.data.keys_collected, 0);
+    }
+
+    #[test]
+    fn paused_levels_stay_frozen_until_resumed() {
+        let mut state = playing_state(&["###", "# #", "#k#", "###"]);
+        state.pause();
+        assert!(state.mode == GameMode::Paused);
+
+        let timer = state.data.animation_time;
+        assert!(run_frames(&mut state, &forward(), 10).is_empty());
+        assert_eq!(state.data.player.pos, Vector2::new(150.0, 150.0));
+        assert_eq!(state.data.animation_time, timer);
+        assert_eq!(state.data.level_time, 0.0);
+
+        // Los ajustes abiertos en una pausa no siguen abiertos en la siguiente
+        state.settings_selection = Some(2);
+        state.resume();
+        run_frames(&mut state, &forward(), 20);
+        assert!(state.data.has_key);
+        state.pause();
+        assert_eq!(state.settings_selection, None);
+        state.resume();
+        assert!((state.data.level_time - 20.0 * 0.016).abs() < 1e-4);
+    }
+
+    #[test]
+    fn sprinting_is_faster_and_stops_when_stamina_runs_out() {
+        let corridor = ["##########################", "#                        #", "##########################"];
+        let east = |sprint| InputState { forward: true, sprint, ..InputState::default() };
+
+        let facing_east = || {
+            let mut state = playing_state(&corridor);
+            state.data.player.a = 0.0;
+            state
+        };
+        let (mut walking, mut sprinting) = (facing_east(), facing_east());
+        run_frames(&mut walking, &east(false), 30);
+        run_frames(&mut sprinting, &east(true), 30);
+        assert!(sprinting.data.player.pos.x > walking.data.player.pos.x + 50.0);
+        assert!(sprinting.data.sprinting && sprinting.data.stamina < 1.0);
+
+        // Agotada: camina aunque mantenga SHIFT hasta pasar el umbral
+        sprinting.data.stamina = 0.01;
+        run_frames(&mut sprinting, &east(true), 3);
+        assert!(sprinting.data.stamina_exhausted && !sprinting.data.sprinting);
+
+        let frames_to_recover = (STAMINA_RECOVERY_THRESHOLD / STAMINA_REGEN_PER_SECOND / 0.016) as usize + 2;
+        run_frames(&mut sprinting, &InputState::default(), frames_to_recover);
+        assert!(!sprinting.data.stamina_exhausted);
+    }
+
+    #[test]
+    fn doors_block_until_the_key_is_taken_then_slide_open() {
+        // La llave queda a dos casillas, fuera del alcance del imán
+        let mut state = playing_state(&["###", "# #", "# #", "#k#", "#D#", "# #", "###"]);
+        state.data.has_key = false;
+        run_frames(&mut state, &InputState::default(), 10);
+        assert_eq!(state.data.door_open, 0.0);
+
+        // Toma la llave y empuja la puerta mientras se desliza
+        run_frames(&mut state, &forward(), 30);
+        assert!(state.data.has_key);
+        assert!(state.data.door_open > 0.0 && state.data.door_open < 1.0);
+        assert_eq!(state.data.maze[4][1], DOOR_CLOSED);
+        assert!(state.data.player.pos.y < 400.0);
+
+        run_frames(&mut state, &forward(), 60);
+        assert_eq!(state.data.maze[4][1], DOOR_OPEN);
+        assert!(state.data.player.pos.y > 400.0);
+    }
+
+    #[test]
+    fn enemies_that_reach_the_player_cost_one_life_and_push_back() {
+        let mut state = playing_state(&["#######", "#  m  #", "#######"]);
+        state.data.enemies = take_enemies_from_maze(&mut state.data.maze, BLOCK);
+        state.data.player.a = 0.0;
+        let lives = state.data.lives;
+
+        let events = run_frames(&mut state, &InputState::default(), 30);
+
+        assert_eq!(state.data.lives, lives - 1);
+        assert!(events.contains(&GameAudioEvent::PlayerHurt));
+        let gap = state.data.enemies[0].pos.distance_to(state.data.player.pos);
+        assert!(gap > 45.0 + 10.0, "el empuje no separó al jugador: {}", gap);
+    }
+
+    #[test]
+    fn a_missing_level_file_is_reported_instead_of_replaced() {
+        let mut state = GameState::new();
+
+        state.load_level_from(2, "assets/levels/no_existe.txt");
+
+        assert!(state.data.maze.is_empty());
+        assert_eq!(state.data.current_level, 2);
+        assert_eq!(state.data.level_errors.len(), 1);
+        assert!(state.data.level_errors[0].starts_with("NO SE PUDO ABRIR assets/levels/no_existe.txt"));
+
+        // Al volver a cargar un nivel que sí existe el error desaparece
+        state.start_game(1);
+        assert!(state.data.level_errors.is_empty());
+        assert!(state.mode == GameMode::Playing);
+    }
+
+    #[test]
+    fn walked_cells_leave_a_trail_that_a_new_level_clears() {
+        let mut state = playing_state(&["###", "# #", "# #", "# #", "###"]);
+
+        run_frames(&mut state, &forward(), 20);
+
+        let expected: HashSet<(usize, usize)> = [(1, 1), (1, 2), (1, 3)].into_iter().collect();
+        assert_eq!(state.data.visited_cells, expected);
+
+        state.load_level(1);
+        assert!(state.data.visited_cells.is_empty());
+    }
+
+    #[test]
+    fn shipped_levels_pass_validation() {
+        let mut state = GameState::new();
+        for level in 1..=3 {
+            state.start_game(level);
+            assert!(state.data.level_errors.is_empty(), "nivel {}: {:?}", level, state.data.level_errors);
+            assert!(state.mode == GameMode::Playing);
+        }
+    }
+
+    #[test]
+    fn random_level_rebuilds_the_same_maze_from_its_seed() {
+        let mut state = GameState::new();
+        state.data.random_seed = 7;
+        state.start_game(RANDOM_LEVEL);
+        assert!(state.mode == GameMode::Playing);
+        assert_eq!(state.data.keys_needed, GENERATED_MAZE_KEYS as i32);
+        let maze = state.data.maze.clone();
+
+        // Reiniciar el nivel da el mismo laberinto; otra semilla, uno distinto
+        state.load_level(RANDOM_LEVEL);
+        assert_eq!(state.data.maze, maze);
+        state.data.random_seed = 8;
+        state.load_level(RANDOM_LEVEL);
+        assert_ne!(state.data.maze, maze);
+    }
+
+    #[test]
+    fn tick_outside_playing_mode_is_a_no_op() {
+        let mut state = playing_state(&["###", "# #", "#k#", "###"]);
+        state.mode = GameMode::Welcome;
+
+        let events = run_frames(&mut state, &forward(), 10);
+
+        assert!(events.is_empty());
+        assert_eq!(state.data.player.pos, Vector2::new(150.0, 150.0));
+        assert_eq!(state.data.maze[2][1], 'k');
+    }
+}
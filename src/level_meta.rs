@@ -0,0 +1,94 @@
+// level_meta.rs - Ajustes opcionales de cada nivel (levelN_meta.txt)
+
+use crate::player::{DEFAULT_PLAYER_RADIUS, MAX_MOVE_SPEED};
+
+// Distancia por defecto que una trampa empuja al jugador
+const TRAP_KNOCKBACK_DISTANCE: f32 = 40.0;
+
+/// Ajustes de un nivel que cambian cómo se mueve el jugador
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LevelMeta {
+    pub player_radius: f32,
+    pub move_speed: f32,
+    // Segundos hasta que una trampa disparada se rearma; 0 = de un solo uso
+    pub trap_rearm_seconds: f32,
+    // Cuánto empujan al jugador las trampas y las entidades; 0 = no empujan
+    pub trap_knockback_distance: f32,
+}
+
+impl Default for LevelMeta {
+    fn default() -> Self {
+        LevelMeta {
+            player_radius: DEFAULT_PLAYER_RADIUS,
+            move_speed: MAX_MOVE_SPEED,
+            trap_rearm_seconds: 0.0,
+            trap_knockback_distance: TRAP_KNOCKBACK_DISTANCE,
+        }
+    }
+}
+
+// El radio debe dejar pasar al jugador por un pasillo de una casilla
+const PLAYER_RADIUS_RANGE: (f32, f32) = (4.0, 45.0);
+const MOVE_SPEED_RANGE: (f32, f32) = (1.0, 60.0);
+const TRAP_REARM_RANGE: (f32, f32) = (0.0, 120.0);
+// Hasta una casilla: el empuje se acorta igual si hay una pared antes
+const TRAP_KNOCKBACK_RANGE: (f32, f32) = (0.0, 100.0);
+
+impl LevelMeta {
+    /// Lee líneas `clave valor`; las claves desconocidas o los valores fuera
+    /// de rango se ignoran y se mantiene el valor por defecto
+    pub fn parse(text: &str) -> Self {
+        let mut meta = LevelMeta::default();
+
+        for line in text.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with("//")) {
+            let mut parts = line.split_whitespace();
+            let (Some(key), Some(value)) = (parts.next(), parts.next()) else {
+                continue;
+            };
+            let Ok(value) = value.parse::<f32>() else {
+                eprintln!("Valor inválido para '{}' en la metadata del nivel: {}", key, value);
+                continue;
+            };
+
+            let (field, (min, max)) = match key {
+                "player_radius" => (&mut meta.player_radius, PLAYER_RADIUS_RANGE),
+                "move_speed" => (&mut meta.move_speed, MOVE_SPEED_RANGE),
+                "trap_rearm_seconds" => (&mut meta.trap_rearm_seconds, TRAP_REARM_RANGE),
+                "trap_knockback_distance" => (&mut meta.trap_knockback_distance, TRAP_KNOCKBACK_RANGE),
+                _ => {
+                    eprintln!("Clave desconocida en la metadata del nivel: {}", key);
+                    continue;
+                }
+            };
+
+            if (min..=max).contains(&value) {
+                *field = value;
+            } else {
+                eprintln!("'{}' fuera de rango ({} a {}): {}", key, min, max, value);
+            }
+        }
+
+        meta
+    }
+}
+
+/// Carga la metadata de un nivel; sin archivo se usan los valores por defecto
+pub fn load_level_meta(filename: &str) -> LevelMeta {
+    std::fs::read_to_string(filename)
+        .map(|text| LevelMeta::parse(&text))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn meta_keeps_defaults_for_unknown_keys_and_out_of_range_values() {
+        let meta = LevelMeta::parse("// nivel estrecho\nplayer_radius 12\nmove_speed 500\nfoo 3\ntrap_rearm_seconds x\ntrap_knockback_distance 250\n");
+        assert_eq!(meta, LevelMeta { player_radius: 12.0, ..LevelMeta::default() });
+        let meta = LevelMeta::parse("trap_knockback_distance 0\n");
+        assert_eq!(meta, LevelMeta { trap_knockback_distance: 0.0, ..LevelMeta::default() });
+        assert_eq!(load_level_meta("assets/levels/no_existe_meta.txt"), LevelMeta::default());
+    }
+}
@@ -1,4 +1,4 @@
-// line.rs - Sistema de líneas 
+// line.rs - Sistema de líneas
 
 use raylib::prelude::*;
 use crate::framebuffer::Framebuffer;
@@ -8,11 +8,41 @@ pub fn line(
     start: Vector2,
     end: Vector2,
 ) {
-    let mut x0 = start.x as i32;
-    let mut y0 = start.y as i32;
-    let x1 = end.x as i32;
-    let y1 = end.y as i32;
+    draw_line(framebuffer, start.x as i32, start.y as i32, end.x as i32, end.y as i32, 1);
+}
 
+/// Línea de Bresenham con el color actual y un trazo de `thickness` pixeles
+/// (0 no dibuja nada); lo que cae fuera del framebuffer se descarta
+pub fn draw_line(framebuffer: &mut Framebuffer, x0: i32, y0: i32, x1: i32, y1: i32, thickness: u32) {
+    let bounds = (0, 0, framebuffer.width as i32, framebuffer.height as i32);
+    draw_line_clipped(framebuffer, x0, y0, x1, y1, thickness, bounds);
+}
+
+/// Igual que `draw_line` pero recortada al rectángulo `(left, top, right, bottom)`
+/// (derecha y abajo exclusivos), como el marco del minimapa
+pub fn draw_line_clipped(
+    framebuffer: &mut Framebuffer,
+    x0: i32,
+    y0: i32,
+    x1: i32,
+    y1: i32,
+    thickness: u32,
+    (left, top, right, bottom): (i32, i32, i32, i32),
+) {
+    if thickness == 0 {
+        return;
+    }
+
+    // El framebuffer también limita, aunque el rectángulo sea más grande
+    let left = left.max(0);
+    let top = top.max(0);
+    let right = right.min(framebuffer.width as i32);
+    let bottom = bottom.min(framebuffer.height as i32);
+
+    // Pincel cuadrado; con grosor par queda corrido hacia abajo a la derecha
+    let brush = -((thickness as i32 - 1) / 2)..=(thickness as i32 / 2);
+
+    let (mut x, mut y) = (x0, y0);
     let dx = (x1 - x0).abs();
     let dy = -(y1 - y0).abs();
     let sx = if x0 < x1 { 1 } else { -1 };
@@ -20,25 +50,65 @@ pub fn line(
     let mut err = dx + dy;
 
     loop {
-        if x0 >= 0
-            && y0 >= 0
-            && (x0 as u32) < framebuffer.width
-            && (y0 as u32) < framebuffer.height
-        {
-            framebuffer.set_pixel(x0 as u32, y0 as u32);
+        for oy in brush.clone() {
+            for ox in brush.clone() {
+                let (px, py) = (x + ox, y + oy);
+                if px >= left && px < right && py >= top && py < bottom {
+                    framebuffer.set_pixel(px as u32, py as u32);
+                }
+            }
         }
 
-        if x0 == x1 && y0 == y1 {
+        if x == x1 && y == y1 {
             break;
         }
         let e2 = 2 * err;
         if e2 >= dy {
             err += dy;
-            x0 += sx;
+            x += sx;
         }
         if e2 <= dx {
             err += dx;
-            y0 += sy;
+            y += sy;
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lit_pixels(framebuffer: &mut Framebuffer) -> Vec<(u32, u32)> {
+        let mut lit = Vec::new();
+        for y in 0..framebuffer.height {
+            for x in 0..framebuffer.width {
+                if framebuffer.get_pixel(x, y) == Some(Color::WHITE) {
+                    lit.push((x, y));
+                }
+            }
+        }
+        lit
+    }
+
+    #[test]
+    fn thick_lines_widen_around_the_path_and_clip_to_bounds() {
+        let mut framebuffer = Framebuffer::new(10, 10);
+        framebuffer.set_current_color(Color::WHITE);
+        draw_line(&mut framebuffer, 2, 5, 6, 5, 3);
+        let lit = lit_pixels(&mut framebuffer);
+        // El pincel cuadrado de 3x3 también asoma un pixel en cada punta
+        assert_eq!(lit.len(), 7 * 3);
+        assert_eq!((lit[0], lit[lit.len() - 1]), ((1, 4), (7, 6)));
+
+        // Diagonal que sale de pantalla por los dos extremos: solo queda lo visible
+        let mut framebuffer = Framebuffer::new(10, 10);
+        framebuffer.set_current_color(Color::WHITE);
+        draw_line(&mut framebuffer, -5, -5, 20, 20, 1);
+        assert_eq!(lit_pixels(&mut framebuffer), (0..10).map(|i| (i, i)).collect::<Vec<_>>());
+
+        let mut framebuffer = Framebuffer::new(10, 10);
+        framebuffer.set_current_color(Color::WHITE);
+        draw_line_clipped(&mut framebuffer, 0, 2, 9, 2, 2, (3, 0, 6, 3));
+        assert_eq!(lit_pixels(&mut framebuffer), vec![(3, 2), (4, 2), (5, 2)]);
+    }
+}
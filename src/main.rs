@@ -3,6 +3,7 @@
 mod line;
 mod framebuffer;
 mod maze;
+mod level_meta;
 mod caster;
 mod player;
 mod game_state;
@@ -12,57 +13,136 @@ mod sprite_manager;
 mod ui;
 mod notification;
 mod collision;
+mod asset_loader;
+mod translucency;
+mod replay;
+mod palette;
+mod settings;
+mod debug_view;
+mod view_model;
+mod best_times;
+mod score;
+mod enemies;
+mod pathfinding;
+mod editor;
+mod transition;
 
 use framebuffer::Framebuffer;
-use player::{process_events_with_maze_safe, process_mouse_input_safe};
-use game_state::{GameState, GameMode};
-use audio::{AudioManager, GameAudioEvent, MusicType};
+use player::{adjust_fov, gamepad_reset_pressed, InputState};
+use game_state::{checkpoints_needed, GameState, GameMode, RANDOM_LEVEL};
+use audio::{AudioManager, FootstepSurface, GameAudioEvent, MusicType};
 use texture_manager::TextureManager;
 use sprite_manager::SpriteManager;
-use ui::{render_fps, render_minimap, render_welcome_screen, render_game_over, render_victory};
-use caster::{render_world_with_sprites};
+use ui::{format_level_time, render_fps, render_fps_graph, render_missing_assets, FPS_GRAPH_FRAMES, render_minimap, render_welcome_screen, render_game_over, render_victory, render_loading_screen, render_level_intro, render_pause_menu, render_minimap_settings, render_poison_tint, render_damage_flash, MIN_WINDOW_WIDTH, MIN_WINDOW_HEIGHT, MinimapObjectives, MinimapSetting, MinimapView, PauseOption, VictorySummary};
+use caster::{render_world_with_sprites, FrameContext, LevelView};
+use debug_view::render_debug_view;
+use view_model::ViewModel;
+use asset_loader::AssetLoader;
+use replay::{Replay, ReplayMode, ReplayPlayer};
+use palette::{Palette, lives_status_label};
+use settings::Settings;
+use best_times::BestTimes;
+use score::HighScore;
+use notification::NotificationType;
+use editor::LevelEditor;
+use transition::ScreenFade;
 
 use raylib::prelude::*;
-use std::time::{Duration, Instant};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 fn main() {
-    let window_width = 1300;
-    let window_height = 900;
+    let mut window_width = 1300;
+    let mut window_height = 900;
     let block_size = 100;
 
     let (mut window, raylib_thread) = raylib::init()
         .size(window_width, window_height)
         .title("The Backrooms Escape - PROYECTO 1")
         .log_level(TraceLogLevel::LOG_WARNING)
+        .resizable()
         .build();
 
     window.set_target_fps(60);
+    window.set_window_min_size(MIN_WINDOW_WIDTH as i32, MIN_WINDOW_HEIGHT as i32);
 
     let mut framebuffer = Framebuffer::new(window_width as u32, window_height as u32);
     framebuffer.set_background_color(Color::new(20, 20, 30, 255));
 
     let mut game_state = GameState::new();
+    let mut settings = Settings::load();
+    game_state.data.life_rules.starting = settings.starting_lives;
+    game_state.data.player.fov = settings.fov_radians();
+    let mut best_times = BestTimes::load();
+    let mut high_score = HighScore::load();
+    // El mundo se dibuja aparte, a la escala elegida, y luego se estira a la ventana
+    let (world_width, world_height) = settings.render_size(window_width as u32, window_height as u32);
+    let mut world_framebuffer = Framebuffer::new(world_width, world_height);
+    let mut palette = Palette::of(settings.palette);
     
     // Inicializar sistemas de renderizado
-    let texture_manager = TextureManager::new(&mut window, &raylib_thread);
+    let mut texture_manager = TextureManager::new(&mut window, &raylib_thread);
     let mut sprite_manager = SpriteManager::new(&mut window, &raylib_thread);
+    sprite_manager.set_max_rendered_sprites(settings.max_rendered_sprites);
+    texture_manager.set_filtering(settings.texture_filtering);
+    let mut view_model = ViewModel::new();
     
     // Inicializar sistema de audio
-    let mut audio_manager = AudioManager::new();
+    let mut audio_manager = AudioManager::new(&settings.audio);
+    
+    // Decodificar assets en segundo plano mostrando la pantalla de carga
+    let mut asset_loader = AssetLoader::spawn();
+    while !asset_loader.is_finished() && !window.window_should_close() {
+        asset_loader.poll(&mut texture_manager, &mut sprite_manager, &mut audio_manager);
+        
+        framebuffer.clear();
+        render_loading_screen(&mut framebuffer, "CARGANDO ASSETS", asset_loader.progress());
+        framebuffer.swap_buffers(&mut window, &raylib_thread);
+    }
+    
+    // Grabación o reproducción de la entrada (--record / --replay)
+    let replay_mode = ReplayMode::from_args(std::env::args().skip(1));
+    // Editor de niveles (--editor): F6 cambia de pincel, F7 pinta y F8 exporta
+    let editor_enabled = LevelEditor::enabled_by_args(std::env::args().skip(1));
+    let mut level_editor: Option<LevelEditor> = None;
+    let mut replay_recording: Option<Replay> = None;
+    let mut replay_playback: Option<ReplayPlayer> = None;
+    
+    if let ReplayMode::Playback(path) = &replay_mode {
+        match Replay::load(path) {
+            Ok(replay) => {
+                let player = ReplayPlayer::new(replay);
+                game_state.mode = GameMode::Loading(player.level());
+                game_state.data.random_seed = player.seed();
+                replay_playback = Some(player);
+            },
+            Err(error) => eprintln!("{}", error),
+        }
+    }
     
     let mut fps_counter = 0;
     let mut fps_timer = Instant::now();
     let mut current_fps = 0.0;
+    // Tiempos de los últimos frames para el gráfico de F3
+    let mut frame_times: VecDeque<f32> = VecDeque::with_capacity(FPS_GRAPH_FRAMES);
+    let mut show_fps_graph = false;
+    let mut show_missing_assets = false;
+    // Fundido entre niveles; mientras dura no se lee la entrada
+    let mut level_fade: Option<ScreenFade> = None;
+    // La pantalla de carga ya se mostró; el nivel se construye en el frame siguiente
+    let mut loading_frame_shown = false;
 
     let mut last_mouse_x = window.get_mouse_x();
+    let mut last_mouse_y = window.get_mouse_y();
     let mut delta_time = 0.016; // ~60 FPS inicial
     let mut was_moving = false; // Para detectar cambios de movimiento
     
     while !window.window_should_close() {
         let frame_start = Instant::now();
         
-        // Actualizar sistema de audio
-        audio_manager.update(delta_time);
+        // Actualizar sistema de audio; los sonidos ambientales solo suenan en juego
+        let listener = (game_state.mode == GameMode::Playing).then_some(game_state.data.player.pos);
+        audio_manager.update(delta_time, listener);
         
         // Calcular FPS
         fps_counter += 1;
@@ -72,8 +152,28 @@ fn main() {
             fps_timer = Instant::now();
         }
 
+        // Al redimensionar la ventana los framebuffers se crean a la nueva
+        // medida; el z-buffer de sprites se ajusta solo al ancho del mundo
+        if window.is_window_resized() {
+            window_width = window.get_screen_width().max(MIN_WINDOW_WIDTH as i32);
+            window_height = window.get_screen_height().max(MIN_WINDOW_HEIGHT as i32);
+            framebuffer = Framebuffer::new(window_width as u32, window_height as u32);
+            framebuffer.set_background_color(Color::new(20, 20, 30, 255));
+            let (world_width, world_height) = settings.render_size(window_width as u32, window_height as u32);
+            world_framebuffer = Framebuffer::new(world_width, world_height);
+        }
+
         framebuffer.clear();
 
+        // Fundido entre niveles: con la pantalla en negro se pasa al siguiente
+        // nivel; ESPACIO lo salta. Se avanza antes de dibujar para que la misma
+        // pulsación que lo inicia no lo salte
+        if let Some(fade) = level_fade.as_mut() {
+            if fade.update(delta_time, window.is_key_pressed(KeyboardKey::KEY_SPACE)) {
+                game_state.advance_level();
+            }
+        }
+
         match game_state.mode {
             GameMode::Welcome => {
                 // Reproducir mÃºsica de menÃº si no estÃ¡ sonando
@@ -82,15 +182,50 @@ fn main() {
                     audio_manager.play_menu_music();
                 }
                 
-                let selected_level = render_welcome_screen(&mut framebuffer, &window);
+                let selected_level = render_welcome_screen(
+                    &mut framebuffer,
+                    &window,
+                    &mut game_state.menu_selection,
+                    &mut game_state.data.hardcore,
+                    &mut game_state.data.difficulty,
+                    settings.hardcore_cleared,
+                );
                 if let Some(level) = selected_level {
+                    // Cada partida en el nivel aleatorio genera un laberinto nuevo
+                    if level == RANDOM_LEVEL {
+                        game_state.data.random_seed = rand::random();
+                    }
+                    // Mostrar un frame de carga antes de construir el nivel
+                    game_state.begin_run(level);
+                }
+            },
+            GameMode::Loading(level) => {
+                render_loading_screen(&mut framebuffer, "CARGANDO NIVEL", 1.0);
+
+                // El primer frame solo presenta la pantalla de carga; el nivel
+                // se construye en el siguiente, con esa pantalla ya a la vista
+                if !loading_frame_shown {
+                    loading_frame_shown = true;
+                } else {
+                    loading_frame_shown = false;
+
                     game_state.start_game(level);
-                    
+                    texture_manager.set_theme(level);
+                    if editor_enabled {
+                        level_editor = Some(LevelEditor::from_level(&game_state.data, block_size));
+                    }
+
                     // Cargar sprites del maze
                     sprite_manager.load_sprites_from_maze(&game_state.data.maze, block_size);
-                    
+                    audio_manager.set_ambient_sources(sprite_manager.ambient_sources());
+
                     // Cambiar a mÃºsica de fondo del juego
-                    audio_manager.play_background_music();
+                    audio_manager.resume_background_music_now();
+
+                    // Cada nivel empieza una grabación nueva (se guarda la del último nivel)
+                    if let ReplayMode::Record(_) = replay_mode {
+                        replay_recording = Some(Replay::new(level, game_state.data.random_seed));
+                    }
                 }
             },
             GameMode::Playing => {
@@ -98,130 +233,513 @@ fn main() {
                 let mouse_x = window.get_mouse_x();
                 let mouse_delta = mouse_x - last_mouse_x;
                 last_mouse_x = mouse_x;
+                let mouse_y = window.get_mouse_y();
+                let mouse_delta_y = mouse_y - last_mouse_y;
+                last_mouse_y = mouse_y;
 
                 let maze_clone = game_state.data.maze.clone();
                 
                 if !maze_clone.is_empty() && !maze_clone[0].is_empty() {
-                    // Detectar si el jugador se estÃ¡ moviendo
-                    let is_moving = window.is_key_down(KeyboardKey::KEY_W) ||
-                                   window.is_key_down(KeyboardKey::KEY_S) ||
-                                   window.is_key_down(KeyboardKey::KEY_A) ||
-                                   window.is_key_down(KeyboardKey::KEY_D) ||
-                                   window.is_key_down(KeyboardKey::KEY_Q) ||
-                                   window.is_key_down(KeyboardKey::KEY_E) ||
-                                   window.is_key_down(KeyboardKey::KEY_UP) ||
-                                   window.is_key_down(KeyboardKey::KEY_DOWN) ||
-                                   window.is_key_down(KeyboardKey::KEY_LEFT) ||
-                                   window.is_key_down(KeyboardKey::KEY_RIGHT);
+                    // En reproducción se ignora la entrada en vivo
+                    let (input, frame_delta) = match replay_playback.as_mut() {
+                        Some(playback) => playback
+                            .next_frame()
+                            .map(|frame| (frame.input, frame.delta_time))
+                            .unwrap_or((InputState::default(), delta_time)),
+                        None if level_fade.is_some() => (InputState::default(), delta_time),
+                        None => {
+                            let input = InputState::from_raylib(&window, &settings.keys, mouse_delta as f32, mouse_delta_y as f32)
+                                .with_gamepad(&window, settings.gamepad_deadzone, delta_time);
+                            (input, delta_time)
+                        },
+                    };
+                    
+                    if let Some(recording) = replay_recording.as_mut() {
+                        recording.record(frame_delta, &input);
+                    }
 
                     // Actualizar sonido de pasos
+                    // Sin pasos durante la cuenta atrás, el jugador no se mueve
+                    let is_moving = input.is_moving() && game_state.data.intro_timer <= 0.0;
                     if is_moving != was_moving {
                         audio_manager.handle_player_movement(is_moving);
                         was_moving = is_moving;
                     }
 
-                    // Procesar eventos de entrada
-                    process_events_with_maze_safe(
-                        &mut game_state.data.player, 
-                        &window, 
-                        &maze_clone, 
-                        block_size
-                    );
-                    process_mouse_input_safe(&mut game_state.data.player, mouse_delta as f32);
-
                     // Actualizar sprites
                     sprite_manager.update_sprites(delta_time);
 
-                    // Actualizar estado del juego
-                    game_state.update_with_audio(&mut audio_manager, block_size);
+                    // Avanzar la lógica del juego y reproducir sus eventos de audio
+                    for event in game_state.tick(&input, frame_delta, block_size) {
+                        audio_manager.play_game_event(event, &game_state.data.player);
+                    }
 
-                    // Renderizar mundo con sprites
-                    render_world_with_sprites(
-                        &mut framebuffer,
-                        &maze_clone,
-                        &texture_manager,
-                        &mut sprite_manager,
+                    // Quitar los sprites de los objetos y trampas consumidos
+                    if !game_state.data.consumed_cells.is_empty() {
+                        sprite_manager.remove_sprites_at_cells(game_state.data.consumed_cells.drain(..), block_size);
+                        audio_manager.set_ambient_sources(sprite_manager.ambient_sources());
+                    }
+                    
+                    audio_manager.set_sprinting(game_state.data.sprinting);
+                    audio_manager.set_footstep_surface(FootstepSurface::at(
+                        &game_state.data.maze,
+                        game_state.data.player.pos,
                         block_size,
-                        &game_state.data.player,
-                    );
+                    ));
                     
-                    game_state.data.notification_manager.render(&mut framebuffer);
+                    // Guardar la grabación al terminar el nivel
+                    if game_state.mode != GameMode::Playing {
+                        save_replay(&mut replay_recording, &replay_mode);
+                    }
 
-                    // Renderizar UI
-                    render_hud_enhanced(&mut framebuffer, &game_state.data, current_fps);
+                    // Récord del nivel; las repeticiones y los laberintos aleatorios no cuentan
+                    let counts_for_record = replay_playback.is_none() && game_state.data.current_level != RANDOM_LEVEL;
+                    if game_state.mode == GameMode::Victory && counts_for_record {
+                        let level_time = game_state.data.level_time;
+                        if best_times.record(game_state.data.current_level, level_time) {
+                            best_times.save();
+                            game_state.data.notification_manager.show_new_record(level_time);
+                        }
+                        if high_score.record(game_state.data.score) {
+                            high_score.save();
+                        }
+                    }
 
-                    render_minimap(
-                        &mut framebuffer,
-                        &maze_clone,
-                        &game_state.data.player,
-                        block_size,
-                    );
+                    if window.is_key_down(KeyboardKey::KEY_F4) {
+                        // Vista de depuración mientras se mantiene F4
+                        render_debug_view(
+                            &mut framebuffer,
+                            &maze_clone,
+                            game_state.data.regions.as_ref(),
+                            &game_state.data.player,
+                            block_size,
+                            game_state.data.door_open,
+                            &palette,
+                        );
+                    } else {
+                        // Candado sobre el portal mientras falten requisitos
+                        sprite_manager.set_exit_lock(game_state.data.exit_requirements().lock_label());
+                        // Las trampas que se están rearmando se ven hundidas
+                        let retracted_traps: Vec<(usize, usize)> = game_state.data.disarmed_traps
+                            .iter()
+                            .map(|(cell, _)| *cell)
+                            .collect();
+                        sprite_manager.set_retracted_traps(&retracted_traps, block_size);
+                        // Las entidades se dibujan donde las dejó la lógica del juego
+                        let enemy_positions: Vec<Vector2> = game_state.data.enemies
+                            .iter()
+                            .map(|enemy| enemy.pos)
+                            .collect();
+                        sprite_manager.set_enemy_positions(&enemy_positions);
 
-                    render_fps(&mut framebuffer, current_fps);
+                        // Renderizar mundo con sprites
+                        render_level(&mut world_framebuffer, &game_state.data, &texture_manager, &mut sprite_manager, block_size);
+                        framebuffer.blit_scaled(&world_framebuffer);
+
+                        // Linterna en primera persona, por encima del mundo y bajo el HUD
+                        view_model.update(&game_state.data.player);
+                        view_model.render(&mut framebuffer, &game_state.data.player);
+
+                        if let Some(remaining) = game_state.data.poison_timer {
+                            render_poison_tint(&mut framebuffer, remaining, game_state.data.animation_time);
+                        }
+                        if game_state.data.damage_flash_timer > 0.0 {
+                            render_damage_flash(&mut framebuffer, game_state.data.damage_flash_timer);
+                        }
+                    
+                        game_state.data.notification_manager.render(&mut framebuffer, &palette, settings.hud_scale);
+
+                        // Renderizar UI
+                        let hud_bottom = render_hud_enhanced(&mut framebuffer, &game_state.data, &sprite_manager, current_fps, &palette, settings.hud_scale);
+
+                        // Oculto: ni siquiera se recorre el laberinto
+                        if let Some(view) = MinimapView::place(&framebuffer, &settings.minimap, hud_bottom, &maze_clone, block_size) {
+                            render_minimap(
+                                &mut framebuffer,
+                                &maze_clone,
+                                &game_state.data.player,
+                                &game_state.data.visited_cells,
+                                &view,
+                                &MinimapObjectives {
+                                    animation_time: game_state.data.animation_time,
+                                    exit_open: game_state.data.exit_requirements().is_met(),
+                                    keys_collected: game_state.data.keys_collected,
+                                    keys_needed: game_state.data.keys_needed,
+                                },
+                                &palette.for_level_theme(game_state.data.current_level),
+                            );
+                        }
+
+                        render_fps(&mut framebuffer, current_fps, &palette);
+                        if let Some(editor) = level_editor.as_ref() {
+                            let label = format!("EDITOR - PINCEL: {} (F6 / F7 / F8)", editor.brush_label());
+                            framebuffer.set_current_color(Color::YELLOW);
+                            let label_y = framebuffer.height - 20;
+                            render_text(&mut framebuffer, &label, 15, label_y);
+                        }
+                        if show_fps_graph {
+                            render_fps_graph(&mut framebuffer, &frame_times, &palette);
+                        }
+                        if show_missing_assets {
+                            let missing: Vec<String> = sprite_manager
+                                .missing_sprites()
+                                .iter()
+                                .map(|sprite_type| sprite_type.get_file_path())
+                                .chain(asset_loader.failed_assets().iter().copied())
+                                .map(str::to_string)
+                                .collect();
+                            render_missing_assets(&mut framebuffer, &missing, &palette);
+                        }
+
+                        // Cuenta atrás de inicio de nivel encima de todo
+                        if let Some(countdown) = game_state.intro_countdown() {
+                            render_level_intro(&mut framebuffer, countdown);
+                        }
+                    }
                     
                 } else {
-                    render_error_screen(&mut framebuffer, "ERROR: Maze not loaded correctly");
+                    let hints = ["PRESIONA R PARA REINTENTAR".to_string(), "ESC ABRE LA PAUSA PARA VOLVER AL MENU".to_string()];
+                    render_error_screen(&mut framebuffer, "ERROR: LABERINTO NO CARGADO", &hints);
                 }
 
                 // Reset de emergencia
-                if window.is_key_pressed(KeyboardKey::KEY_R) {
-                    game_state.load_level(game_state.data.current_level);
-                    sprite_manager.load_sprites_from_maze(&game_state.data.maze, block_size);
+                let reset_pressed = window.is_key_pressed(KeyboardKey::KEY_R) || gamepad_reset_pressed(&window);
+                if replay_playback.is_none() && reset_pressed {
+                    restart_level(&mut game_state, &mut sprite_manager, &mut audio_manager, &mut replay_recording, block_size);
+                }
+
+                // Pausa: el nivel se congela hasta volver del menú
+                if window.is_key_pressed(KeyboardKey::KEY_ESCAPE) || window.is_key_pressed(KeyboardKey::KEY_P) {
+                    game_state.pause();
+                    audio_manager.pause_all();
+                    was_moving = false;
+                }
+
+                // Rueda del mouse: acercar / alejar la vista
+                let wheel_move = window.get_mouse_wheel_move();
+                if wheel_move != 0.0 {
+                    adjust_fov(&mut game_state.data.player, wheel_move);
+                }
+
+                // Campo de visión base con `[` / `]`; se guarda y reemplaza el zoom
+                let fov_steps = window.is_key_pressed(KeyboardKey::KEY_RIGHT_BRACKET) as i32
+                    - window.is_key_pressed(KeyboardKey::KEY_LEFT_BRACKET) as i32;
+                if fov_steps != 0 {
+                    settings.adjust_fov(fov_steps);
+                    settings.save();
+                    game_state.data.player.fov = settings.fov_radians();
+                    let message = format!("FOV: {:.0}", settings.fov_degrees);
+                    game_state.data.notification_manager.add_notification(&message, NotificationType::Info, 2.0);
+                }
+
+                // Alternar minimapa fijo / rotando con el jugador
+                if window.is_key_pressed(KeyboardKey::KEY_N) {
+                    settings.minimap.orientation = settings.minimap.orientation.toggled();
+                    settings.save();
+                }
+
+                // Alternar minimapa completo / ampliado alrededor del jugador
+                if window.is_key_pressed(KeyboardKey::KEY_Z) {
+                    settings.minimap.zoom = settings.minimap.zoom.toggled();
+                    settings.save();
+                    let message = format!("MINIMAPA: {}", settings.minimap.zoom.label());
+                    game_state.data.notification_manager.add_notification(&message, NotificationType::Info, 2.0);
+                }
+
+                // Mover el minimapa de esquina (o esconderlo)
+                if window.is_key_pressed(KeyboardKey::KEY_M) {
+                    settings.minimap.corner = settings.minimap.corner.next();
+                    settings.save();
+                    let message = format!("MINIMAPA: {}", settings.minimap.corner.label());
+                    game_state.data.notification_manager.add_notification(&message, NotificationType::Info, 2.0);
+                }
+
+                if let Some(editor) = level_editor.as_mut() {
+                    if window.is_key_pressed(KeyboardKey::KEY_F6) {
+                        editor.next_brush();
+                    }
+                    if window.is_key_pressed(KeyboardKey::KEY_F7)
+                        && editor.paint_ahead(&mut game_state.data, block_size).is_some()
+                    {
+                        sprite_manager.load_sprites_from_maze(&game_state.data.maze, block_size);
+                        audio_manager.set_ambient_sources(sprite_manager.ambient_sources());
+                    }
+                    if window.is_key_pressed(KeyboardKey::KEY_F8) {
+                        match editor.export(game_state.data.current_level) {
+                            Ok(path) => {
+                                let message = format!("NIVEL EXPORTADO: {}", path);
+                                game_state.data.notification_manager.add_notification(&message, NotificationType::Success, 2.0);
+                            },
+                            Err(error) => eprintln!("No se pudo exportar el nivel: {}", error),
+                        }
+                    }
+                }
+
+                // Gráfico de tiempos de frame bajo el contador de FPS
+                if window.is_key_pressed(KeyboardKey::KEY_F3) {
+                    show_fps_graph = !show_fps_graph;
+                }
+
+                // Lista de assets que no cargaron, para encontrar rutas mal escritas
+                if window.is_key_pressed(KeyboardKey::KEY_F10) {
+                    show_missing_assets = !show_missing_assets;
+                }
+
+                // Tamaño del HUD y las notificaciones para pantallas grandes
+                if window.is_key_pressed(KeyboardKey::KEY_F9) {
+                    settings.next_hud_scale();
+                    settings.save();
+                    let message = format!("ESCALA DEL HUD: X{}", settings.hud_scale);
+                    game_state.data.notification_manager.add_notification(&message, NotificationType::Info, 2.0);
+                }
+
+                // Resolución interna del mundo: 50, 75 o 100 por ciento de la ventana
+                if window.is_key_pressed(KeyboardKey::KEY_F5) {
+                    settings.next_render_scale();
+                    settings.save();
+                    let (world_width, world_height) = settings.render_size(window_width as u32, window_height as u32);
+                    world_framebuffer = Framebuffer::new(world_width, world_height);
+                    let message = format!("ESCALA DE RENDER: {:.2}", settings.render_scale);
+                    game_state.data.notification_manager.add_notification(&message, NotificationType::Info, 2.0);
+                }
+
+                // Cambiar la paleta de colores del HUD y guardarla
+                if window.is_key_pressed(KeyboardKey::KEY_C) {
+                    settings.palette = settings.palette.next();
+                    palette = Palette::of(settings.palette);
+                    settings.save();
+                    let message = format!("PALETA: {}", settings.palette.label());
+                    game_state.data.notification_manager.add_notification(&message, NotificationType::Info, 2.0);
                 }
 
                 // Control de volumen con teclas
-                if window.is_key_pressed(KeyboardKey::KEY_MINUS) {
-                    let new_volume = (audio_manager.get_music_volume() - 0.1).max(0.0);
-                    audio_manager.set_music_volume(new_volume);
+                let volume_steps = window.is_key_pressed(KeyboardKey::KEY_EQUAL) as i32
+                    - window.is_key_pressed(KeyboardKey::KEY_MINUS) as i32;
+                if volume_steps != 0 {
+                    let music_volume = settings.audio.music_volume + volume_steps as f32 * 0.1;
+                    settings.audio.music_volume = music_volume.clamp(0.0, 1.0);
+                    settings.save();
+                    audio_manager.apply_settings(&settings.audio);
                 }
-                if window.is_key_pressed(KeyboardKey::KEY_EQUAL) {
-                    let new_volume = (audio_manager.get_music_volume() + 0.1).min(1.0);
-                    audio_manager.set_music_volume(new_volume);
+                // Silenciar todo el audio (se guarda junto a los volúmenes)
+                if window.is_key_pressed(KeyboardKey::KEY_ZERO) {
+                    settings.audio.muted = !settings.audio.muted;
+                    settings.save();
+                    audio_manager.apply_settings(&settings.audio);
+                    let message = if settings.audio.muted { "AUDIO SILENCIADO" } else { "AUDIO ACTIVADO" };
+                    game_state.data.notification_manager.add_notification(message, NotificationType::Info, 2.0);
+                }
+            },
+            GameMode::Paused => {
+                // El mouse se sigue leyendo para que al volver no haya un salto de cámara
+                last_mouse_x = window.get_mouse_x();
+                last_mouse_y = window.get_mouse_y();
+
+                // Mundo congelado: sin tick ni animación de sprites
+                render_level(&mut world_framebuffer, &game_state.data, &texture_manager, &mut sprite_manager, block_size);
+                framebuffer.blit_scaled(&world_framebuffer);
+                view_model.render(&mut framebuffer, &game_state.data.player);
+
+                // Con los ajustes del minimapa abiertos el menú de pausa espera;
+                // ESC vuelve a él en vez de seguir jugando
+                let pause_choice = match game_state.settings_selection.as_mut() {
+                    Some(selection) => {
+                        match render_minimap_settings(&mut framebuffer, &window, selection, &settings.minimap) {
+                            Some(MinimapSetting::Back) => game_state.settings_selection = None,
+                            Some(setting) => {
+                                setting.apply(&mut settings.minimap);
+                                settings.save();
+                            },
+                            None => {},
+                        }
+                        None
+                    },
+                    None => render_pause_menu(&mut framebuffer, &window, &mut game_state.pause_selection),
+                };
+
+                match pause_choice {
+                    Some(PauseOption::Resume) => {
+                        game_state.resume();
+                        audio_manager.resume_all();
+                    },
+                    Some(PauseOption::RestartLevel) => {
+                        restart_level(&mut game_state, &mut sprite_manager, &mut audio_manager, &mut replay_recording, block_size);
+                        game_state.resume();
+                        audio_manager.resume_all();
+                    },
+                    Some(PauseOption::Settings) => game_state.settings_selection = Some(0),
+                    Some(PauseOption::MainMenu) => {
+                        save_replay(&mut replay_recording, &replay_mode);
+                        game_state.reset();
+                        audio_manager.stop_background_music();
+                    },
+                    None => {},
+                }
+            },
+            GameMode::LevelError => {
+                // Errores del archivo del nivel en lugar de un laberinto de emergencia
+                let mut lines = game_state.data.level_errors.clone();
+                lines.push(String::new());
+                lines.push("ENTER O ESC PARA VOLVER AL MENU".to_string());
+                let title = format!("ERROR EN EL NIVEL {}", game_state.data.current_level);
+                render_error_screen(&mut framebuffer, &title, &lines);
+
+                if window.is_key_pressed(KeyboardKey::KEY_ENTER) || window.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+                    game_state.reset();
+                    audio_manager.stop_background_music();
                 }
             },
             GameMode::GameOver => {
+                // El nivel queda de fondo con el estado final del jugador
+                render_level(&mut world_framebuffer, &game_state.data, &texture_manager, &mut sprite_manager, block_size);
+                framebuffer.blit_scaled(&world_framebuffer);
+                
                 if render_game_over(&mut framebuffer, &window) {
                     game_state.reset();
                     audio_manager.stop_background_music();
                 }
             },
             GameMode::Victory => {
-                let should_continue = render_victory(&mut framebuffer, &window, game_state.data.current_level);
+                render_level(&mut world_framebuffer, &game_state.data, &texture_manager, &mut sprite_manager, block_size);
+                framebuffer.blit_scaled(&world_framebuffer);
+                
+                let summary = VictorySummary {
+                    level: game_state.data.current_level,
+                    level_time: game_state.data.level_time,
+                    best_time: best_times.best(game_state.data.current_level),
+                    level_score: game_state.data.last_level_score,
+                    run_score: game_state.data.score,
+                    high_score: high_score.best(),
+                };
+                let should_continue = render_victory(&mut framebuffer, &window, &summary);
+                game_state.data.notification_manager.update(delta_time);
+                game_state.data.notification_manager.render(&mut framebuffer, &palette, settings.hud_scale);
                 
-                if should_continue {
+                if should_continue && level_fade.is_none() {
                     if game_state.data.current_level < 3 {
-                        let next_level = game_state.data.current_level + 1;
-
                         game_state.clear_notifications();
                         
                         // Reproducir sonido de victoria
-                        audio_manager.play_game_event(GameAudioEvent::LevelComplete);
-                        
-                        // Cargar el siguiente nivel directamente
-                        game_state.load_level(next_level);
-                        sprite_manager.load_sprites_from_maze(&game_state.data.maze, block_size);
-                        game_state.mode = GameMode::Playing;
+                        audio_manager.play_game_event(GameAudioEvent::LevelComplete, &game_state.data.player);
                         
-                        // Reanudar mÃºsica de fondo
-                        audio_manager.resume_background_music_now();
+                        // El siguiente nivel se carga con la pantalla en negro
+                        level_fade = Some(ScreenFade::new());
                     } else {
-                        audio_manager.play_game_event(GameAudioEvent::LevelComplete);
+                        audio_manager.play_game_event(GameAudioEvent::LevelComplete, &game_state.data.player);
+                        // Terminar los tres niveles con una sola vida queda registrado
+                        if game_state.hardcore_run_completed() {
+                            settings.hardcore_cleared = true;
+                            settings.save();
+                        }
                         game_state.reset();
+                        audio_manager.stop_background_music();
                     }
                 }
             }
         }
 
+        // ESC cierra la ventana en los menús; en el nivel pausa (ver GameMode::escape_quits)
+        window.set_exit_key(game_state.mode.escape_quits().then_some(KeyboardKey::KEY_ESCAPE));
+
+        if let Some(fade) = level_fade {
+            framebuffer.fade_to_black(fade.darkness());
+            if fade.is_finished() {
+                level_fade = None;
+            }
+        }
+
+        // F12 guarda una captura del frame en cualquier pantalla
+        if window.is_key_pressed(KeyboardKey::KEY_F12) {
+            match save_screenshot(&framebuffer) {
+                Ok(path) => game_state.data.notification_manager.show_screenshot_saved(&path),
+                Err(error) => eprintln!("{}", error),
+            }
+        }
+
         // Mostrar framebuffer
         framebuffer.swap_buffers(&mut window, &raylib_thread);
         
         // Calcular delta time para prÃ³ximo frame
         let frame_time = frame_start.elapsed();
-        delta_time = frame_time.as_secs_f32().min(0.033); // Cap a ~30 FPS mÃ­nimo
-        
-        // Limitar FPS
-        std::thread::sleep(Duration::from_millis(16));
+        if frame_times.len() == FPS_GRAPH_FRAMES {
+            frame_times.pop_front();
+        }
+        frame_times.push_back(frame_time.as_secs_f32());
+        // set_target_fps ya espera al final del dibujado; el tope solo evita un
+        // salto enorme tras un tirón (ventana arrastrada, carga de nivel)
+        delta_time = frame_time.as_secs_f32().min(0.05);
     }
+    
+    save_replay(&mut replay_recording, &replay_mode);
+}
+
+// Vuelve a cargar el nivel actual desde cero (tecla R y menú de pausa)
+fn restart_level(
+    game_state: &mut GameState,
+    sprite_manager: &mut SpriteManager,
+    audio_manager: &mut AudioManager,
+    replay_recording: &mut Option<Replay>,
+    block_size: usize,
+) {
+    game_state.load_level(game_state.data.current_level);
+    sprite_manager.load_sprites_from_maze(&game_state.data.maze, block_size);
+    audio_manager.set_ambient_sources(sprite_manager.ambient_sources());
+
+    // El nivel reinicia desde cero, igual que su grabación
+    if let Some(recording) = replay_recording.as_mut() {
+        recording.frames.clear();
+    }
+}
+
+// Dibuja el nivel actual con sus sprites y las luces que dan en ese frame
+fn render_level(
+    world_framebuffer: &mut Framebuffer,
+    game_data: &game_state::GameData,
+    texture_manager: &TextureManager,
+    sprite_manager: &mut SpriteManager,
+    block_size: usize,
+) {
+    let level = LevelView {
+        maze: &game_data.maze,
+        regions: game_data.regions.as_ref(),
+        block_size,
+        door_open: game_data.door_open,
+    };
+    let lights = sprite_manager.point_lights(game_data.player.pos);
+    let frame = FrameContext {
+        fog: game_data.fog,
+        lights: &lights,
+        animation_time: game_data.animation_time,
+    };
+    render_world_with_sprites(world_framebuffer, &level, texture_manager, sprite_manager, &game_data.player, &frame);
+}
+
+fn save_replay(recording: &mut Option<Replay>, replay_mode: &ReplayMode) {
+    if let (Some(replay), ReplayMode::Record(path)) = (recording.take(), replay_mode) {
+        if let Err(error) = replay.save(path) {
+            eprintln!("{}", error);
+        }
+    }
+}
+
+const SCREENSHOT_DIR: &str = "assets/screenshots";
+
+// Guarda el frame como PNG con la fecha en milisegundos en el nombre
+fn save_screenshot(framebuffer: &Framebuffer) -> Result<String, String> {
+    std::fs::create_dir_all(SCREENSHOT_DIR)
+        .map_err(|e| format!("No se pudo crear {}: {}", SCREENSHOT_DIR, e))?;
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    let path = format!("{}/screenshot_{}.png", SCREENSHOT_DIR, timestamp);
+
+    framebuffer
+        .to_image()
+        .save(&path)
+        .map_err(|e| format!("No se pudo guardar la captura {}: {}", path, e))?;
+    Ok(path)
 }
 
 fn render_border_frame(framebuffer: &mut Framebuffer, x: u32, y: u32, width: u32, height: u32, color: Color) {
@@ -259,91 +777,146 @@ pub fn render_text(framebuffer: &mut Framebuffer, text: &str, x: u32, y: u32) {
 fn render_hud_enhanced(
     framebuffer: &mut Framebuffer, 
     game_data: &game_state::GameData,
+    sprite_manager: &SpriteManager,
     fps: f32,
-) {
-    let hud_height = 140;
+    palette: &Palette,
+    scale: u32,
+) -> u32 {
+    const ICON_SIZE: u32 = 14;
+    const ICON_SPACING: u32 = 4;
+    const INVENTORY_TOP: u32 = 120;
+
+    // El diseño está pensado a escala 1; todas las medidas se multiplican
+    let s = |value: u32| value * scale;
+    let text = |framebuffer: &mut Framebuffer, value: &str, x: u32, y: u32| {
+        ui::render_text_with_scale(framebuffer, value, s(x), s(y), scale);
+    };
+
     let hud_width = 240;
     
+    // El HUD crece hacia abajo si el inventario ocupa varias filas
+    let icons_per_row = ((hud_width - 25) / (ICON_SIZE + ICON_SPACING)).max(1);
+    let inventory_rows = (game_data.collected_items.len() as u32).div_ceil(icons_per_row);
+    let hud_height = 140.max(INVENTORY_TOP + inventory_rows * (ICON_SIZE + ICON_SPACING) + 10);
+    
     // Fondo del HUD con transparencia
     framebuffer.set_current_color(Color::new(0, 0, 0, 150));
-    for y in 5..hud_height {
-        for x in 5..hud_width {
+    for y in s(5)..s(hud_height) {
+        for x in s(5)..s(hud_width) {
             if x < framebuffer.width && y < framebuffer.height {
-                framebuffer.set_pixel(x, y);
+                framebuffer.blend_pixel(x, y);
             }
         }
     }
     
     // Marco del HUD
-    render_border_frame(framebuffer, 5, 5, hud_width - 5, hud_height - 5, Color::new(100, 100, 150, 255));
+    render_border_frame(framebuffer, s(5), s(5), s(hud_width - 5), s(hud_height - 5), palette.frame);
     
     // InformaciÃ³n del juego
-    framebuffer.set_current_color(Color::WHITE);
-    render_text(framebuffer, "ESTADO", 15, 15);
+    framebuffer.set_current_color(palette.text);
+    text(framebuffer, "ESTADO", 15, 15);
+    if game_data.hardcore {
+        framebuffer.set_current_color(palette.danger);
+        text(framebuffer, "HARDCORE", 100, 15);
+    }
+    framebuffer.set_current_color(palette.muted);
+    text(framebuffer, game_data.difficulty.label(), 170, 15);
     
-    // Vidas con color dinÃ¡mico
-    let lives_color = if game_data.lives > 2 { 
-        Color::GREEN 
-    } else if game_data.lives == 2 { 
-        Color::YELLOW 
-    } else { 
-        Color::RED 
+    // Vidas con color dinÃ¡mico y una etiqueta cuando quedan pocas
+    framebuffer.set_current_color(palette.lives_color(game_data.lives));
+    let lives_text = match lives_status_label(game_data.lives) {
+        Some(label) => format!("VIDAS: {} - {}", game_data.lives, label),
+        None => format!("VIDAS: {}", game_data.lives),
     };
-    framebuffer.set_current_color(lives_color);
-    let lives_text = format!("VIDAS: {}", game_data.lives);
-    render_text(framebuffer, &lives_text, 15, 30);
+    text(framebuffer, &lives_text, 15, 30);
+
+    // Estamina junto a las vidas; en rojo mientras se recupera tras agotarse
+    const STAMINA_BAR_X: u32 = 150;
+    const STAMINA_BAR_WIDTH: u32 = 75;
+    render_border_frame(framebuffer, s(STAMINA_BAR_X), s(29), s(STAMINA_BAR_WIDTH), s(8), palette.frame);
+    let stamina_color = if game_data.stamina_exhausted {
+        palette.danger
+    } else if game_data.sprinting {
+        palette.caution
+    } else {
+        palette.good
+    };
+    framebuffer.set_current_color(stamina_color);
+    let filled = (s(STAMINA_BAR_WIDTH - 2) as f32 * game_data.stamina.clamp(0.0, 1.0)) as u32;
+    for y in s(31)..s(36) {
+        for x in s(STAMINA_BAR_X + 1)..(s(STAMINA_BAR_X + 1) + filled) {
+            framebuffer.set_pixel(x, y);
+        }
+    }
     
     // Llaves con progreso
     let keys_color = if game_data.keys_collected >= game_data.keys_needed { 
-        Color::GOLD 
+        palette.key 
     } else { 
-        Color::YELLOW 
+        palette.caution 
     };
     framebuffer.set_current_color(keys_color);
     let keys_text = format!("LLAVES: {}/{}", game_data.keys_collected, game_data.keys_needed);
-    render_text(framebuffer, &keys_text, 15, 45);
+    text(framebuffer, &keys_text, 15, 45);
+
+    // Puntos acumulados en la partida
+    framebuffer.set_current_color(palette.special);
+    text(framebuffer, &format!("PUNTOS: {}", game_data.score), 110, 45);
     
     // Nivel actual
-    framebuffer.set_current_color(Color::CYAN);
-    let level_text = format!("NIVEL: {}", game_data.current_level);
-    render_text(framebuffer, &level_text, 15, 60);
+    framebuffer.set_current_color(palette.info);
+    let level_text = if game_data.current_level == RANDOM_LEVEL {
+        "NIVEL: RANDOM".to_string()
+    } else {
+        format!("NIVEL: {}", game_data.current_level)
+    };
+    text(framebuffer, &level_text, 15, 60);
+
+    // Cronómetro del nivel para speedruns
+    framebuffer.set_current_color(palette.text);
+    text(framebuffer, &format_level_time(game_data.level_time), 110, 60);
     
     // Estado de la salida
     if game_data.has_key {
-        framebuffer.set_current_color(Color::GREEN);
-        render_text(framebuffer, "SALIDA BLOQUEADA!", 15, 75);
+        framebuffer.set_current_color(palette.good);
+        text(framebuffer, "SALIDA BLOQUEADA!", 15, 75);
     } else {
-        framebuffer.set_current_color(Color::ORANGE);
-        render_text(framebuffer, "LLAVES ENCONTRADAS!", 15, 75);
+        framebuffer.set_current_color(palette.caution);
+        text(framebuffer, "LLAVES ENCONTRADAS!", 15, 75);
     }
     
     // Checkpoints para niveles avanzados
-    if game_data.current_level > 1 {
-        let checkpoints_needed = game_data.current_level - 1;
+    let checkpoints_needed = checkpoints_needed(game_data.current_level);
+    if checkpoints_needed > 0 {
         let checkpoints_color = if game_data.visited_checkpoints.len() >= checkpoints_needed {
-            Color::PURPLE
+            palette.special
         } else {
-            Color::GRAY
+            palette.muted
         };
         framebuffer.set_current_color(checkpoints_color);
         let checkpoints_text = format!("CHECKPOINTS: {}/{}", 
             game_data.visited_checkpoints.len(), checkpoints_needed);
-        render_text(framebuffer, &checkpoints_text, 15, 90);
+        text(framebuffer, &checkpoints_text, 15, 90);
     }
     
     // FPS
-    let fps_color = if fps >= 50.0 { 
-        Color::GREEN 
-    } else if fps >= 30.0 { 
-        Color::YELLOW 
-    } else { 
-        Color::RED 
-    };
-    framebuffer.set_current_color(fps_color);
-    render_text(framebuffer, &format!("FPS: {:.0}", fps), 15, 105);
+    framebuffer.set_current_color(palette.fps_color(fps));
+    text(framebuffer, &format!("FPS: {:.0}", fps), 15, 105);
+    
+    // Inventario: iconos de los objetos recogidos, de izquierda a derecha
+    for (i, &item) in game_data.collected_items.iter().enumerate() {
+        let column = i as u32 % icons_per_row;
+        let row = i as u32 / icons_per_row;
+        let icon_x = 15 + column * (ICON_SIZE + ICON_SPACING);
+        let icon_y = INVENTORY_TOP + row * (ICON_SIZE + ICON_SPACING);
+        
+        sprite_manager.render_icon(framebuffer, item, s(icon_x), s(icon_y), s(ICON_SIZE));
+    }
+
+    s(hud_height)
 }
 
-fn render_error_screen(framebuffer: &mut Framebuffer, error_msg: &str) {
+fn render_error_screen(framebuffer: &mut Framebuffer, title: &str, lines: &[String]) {
     // Fondo rojo de error
     for y in 0..framebuffer.height {
         for x in 0..framebuffer.width {
@@ -352,24 +925,10 @@ fn render_error_screen(framebuffer: &mut Framebuffer, error_msg: &str) {
         }
     }
     
-    // Texto de error
+    // Título y una línea por problema
     framebuffer.set_current_color(Color::WHITE);
-    render_error_text(framebuffer, error_msg, 400, 400);
-    render_error_text(framebuffer, "PRESIONA R PARA REINTENTAR", 450, 450);
-    render_error_text(framebuffer, "Presiona ESC para salir", 450, 480);
-}
-
-fn render_error_text(framebuffer: &mut Framebuffer, text: &str, x: u32, y: u32) {
-    for (i, c) in text.chars().enumerate() {
-        if c != ' ' {
-            let char_x = x + (i as u32 * 8);
-            for dx in 0..6 {
-                for dy in 0..8 {
-                    if char_x + dx < framebuffer.width && y + dy < framebuffer.height {
-                        framebuffer.set_pixel(char_x + dx, y + dy);
-                    }
-                }
-            }
-        }
+    render_text(framebuffer, title, 400, 400);
+    for (i, line) in lines.iter().enumerate() {
+        render_text(framebuffer, line, 420, 430 + i as u32 * 15);
     }
 }
\ No newline at end of file
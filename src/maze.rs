@@ -1,9 +1,138 @@
 // maze.rs - Sistema de maze 
 use std::fs::File;
 use std::io::{BufRead, BufReader};
+use crate::pathfinding::a_star;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
 
 pub type Maze = Vec<Vec<char>>;
 
+/// Propiedades de colisión y visibilidad de un tipo de casilla
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TileProperties {
+    pub walkable: bool,
+    pub blocks_sight: bool,
+    pub is_hazard: bool,
+    pub is_pickup: bool,
+    pub translucent: bool,
+    // Altura de la pared respecto a una normal ('#' = 1); 0 en casillas sin pared
+    pub height: f32,
+}
+
+impl TileProperties {
+    const WALL: TileProperties = TileProperties { walkable: false, blocks_sight: true, is_hazard: false, is_pickup: false, translucent: false, height: 1.0 };
+    // Pared del doble de alto. Se ve por encima de los muros bajos, pero el
+    // rayo se detiene en la primera pared opaca: detrás de una pared normal
+    // más cercana no asoma
+    const TALL_WALL: TileProperties = TileProperties { height: 2.0, ..TileProperties::WALL };
+    // Muro bajo: bloquea el paso pero se ve por encima, así que los rayos siguen
+    const LOW_WALL: TileProperties = TileProperties { walkable: false, blocks_sight: false, is_hazard: false, is_pickup: false, translucent: false, height: 0.5 };
+    const FLOOR: TileProperties = TileProperties { walkable: true, blocks_sight: false, is_hazard: false, is_pickup: false, translucent: false, height: 0.0 };
+    const PICKUP: TileProperties = TileProperties { walkable: true, blocks_sight: false, is_hazard: false, is_pickup: true, translucent: false, height: 0.0 };
+    const HAZARD: TileProperties = TileProperties { walkable: true, blocks_sight: false, is_hazard: true, is_pickup: false, translucent: false, height: 0.0 };
+    // Vidrio: bloquea el paso pero los rayos siguen y se compone encima
+    const GLASS: TileProperties = TileProperties { walkable: false, blocks_sight: false, is_hazard: false, is_pickup: false, translucent: true, height: 1.0 };
+    // Caracteres desconocidos: no se pueden pisar pero los rayos los atraviesan
+    const UNKNOWN: TileProperties = TileProperties { walkable: false, blocks_sight: false, is_hazard: false, is_pickup: false, translucent: false, height: 0.0 };
+
+    /// Busca las propiedades de una casilla en la tabla de tiles
+    pub fn of(cell: char) -> TileProperties {
+        TILE_TABLE
+            .iter()
+            .find(|(ch, _)| *ch == cell)
+            .map(|(_, props)| *props)
+            .unwrap_or(TileProperties::UNKNOWN)
+    }
+
+    /// Pared opaca que no tapa la vista: el caster la dibuja y sigue de largo
+    pub fn is_low_wall(&self) -> bool {
+        !self.blocks_sight && !self.translucent && self.height > 0.0
+    }
+}
+
+/// Capa paralela al maze que etiqueta zonas del nivel para variar sus texturas
+pub type RegionMap = Vec<Vec<char>>;
+
+// Casilla sin región: usa la textura normal de su carácter
+pub const NO_REGION: char = '.';
+
+// Puerta cerrada hasta conseguir la llave; al terminar de abrirse pasa a ser
+// una puerta abierta, que se pisa como el suelo
+pub const DOOR_CLOSED: char = 'D';
+pub const DOOR_OPEN: char = 'd';
+
+// Tabla única de tiles: para un nuevo tipo de casilla basta con añadir una línea
+const TILE_TABLE: [(char, TileProperties); 21] = [
+    ('#', TileProperties::WALL),
+    ('W', TileProperties::TALL_WALL),
+    ('w', TileProperties::LOW_WALL),
+    ('+', TileProperties::WALL),
+    ('-', TileProperties::WALL),
+    ('|', TileProperties::WALL),
+    ('r', TileProperties::WALL),
+    ('b', TileProperties::WALL),
+    ('g', TileProperties::WALL),
+    // Marco del portal de salida (textura animada)
+    ('P', TileProperties::WALL),
+    (DOOR_CLOSED, TileProperties::WALL),
+    ('v', TileProperties::GLASS),
+    (' ', TileProperties::FLOOR),
+    ('c', TileProperties::FLOOR),
+    ('e', TileProperties::FLOOR),
+    (DOOR_OPEN, TileProperties::FLOOR),
+    ('k', TileProperties::PICKUP),
+    ('l', TileProperties::PICKUP),
+    ('t', TileProperties::HAZARD),
+    ('p', TileProperties::HAZARD),
+    ('x', TileProperties::HAZARD),
+];
+
+/// Qué representa una casilla en el minimapa; cada paleta le da su color
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MinimapTile {
+    Wall,
+    Glass,
+    Key,
+    ExtraLife,
+    Checkpoint,
+    Exit,
+    Door,
+    OpenDoor,
+    Hazard,
+    Unknown,
+}
+
+impl MinimapTile {
+    /// Único lugar que decide cómo aparece cada casilla en el minimapa;
+    /// None para el suelo vacío. Las paredes y el vidrio salen de la tabla
+    /// de tiles, así que un tipo de pared nuevo no necesita cambios aquí.
+    pub fn of(cell: char) -> Option<MinimapTile> {
+        match cell {
+            'k' => return Some(MinimapTile::Key),
+            'l' => return Some(MinimapTile::ExtraLife),
+            'c' => return Some(MinimapTile::Checkpoint),
+            'e' => return Some(MinimapTile::Exit),
+            DOOR_CLOSED => return Some(MinimapTile::Door),
+            DOOR_OPEN => return Some(MinimapTile::OpenDoor),
+            _ => {}
+        }
+
+        let props = TileProperties::of(cell);
+        if props.blocks_sight || props.is_low_wall() {
+            Some(MinimapTile::Wall)
+        } else if props.translucent {
+            Some(MinimapTile::Glass)
+        } else if props.is_hazard {
+            Some(MinimapTile::Hazard)
+        } else if props.walkable {
+            None
+        } else {
+            Some(MinimapTile::Unknown)
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct MazeData {
     pub grid: Maze,
@@ -14,7 +143,8 @@ pub struct MazeData {
 impl MazeData {
     pub fn new(grid: Maze) -> Self {
         let height = grid.len();
-        let width = if height > 0 { grid[0].len() } else { 0 };
+        // Ancho de la fila más larga; las cortas terminan en pared (get_cell)
+        let width = grid.iter().map(|row| row.len()).max().unwrap_or(0);
         
         MazeData {
             grid,
@@ -24,11 +154,12 @@ impl MazeData {
     }
 
     pub fn get_cell(&self, x: usize, y: usize) -> char {
-        if y < self.height && x < self.width {
-            self.grid[y][x]
-        } else {
-            '#' // Retornar muro por defecto fuera de bounds
-        }
+        // Cada fila tiene su propio largo: pasado su final es muro
+        self.grid
+            .get(y)
+            .and_then(|row| row.get(x))
+            .copied()
+            .unwrap_or('#')
     }
 
     pub fn get_cell_safe(&self, x: f32, y: f32, block_size: usize) -> char {
@@ -43,13 +174,11 @@ impl MazeData {
     }
 
     pub fn is_wall(&self, x: usize, y: usize) -> bool {
-        let cell = self.get_cell(x, y);
-        matches!(cell, '#' | '+' | '-' | '|' | 'r' | 'b' | 'g')
+        TileProperties::of(self.get_cell(x, y)).blocks_sight
     }
 
     pub fn is_walkable(&self, x: usize, y: usize) -> bool {
-        let cell = self.get_cell(x, y);
-        matches!(cell, ' ' | 'k' | 't' | 'l' | 'c' | 'e')
+        TileProperties::of(self.get_cell(x, y)).walkable
     }
 
     pub fn can_move_to(&self, world_x: f32, world_y: f32, block_size: usize) -> bool {
@@ -86,56 +215,198 @@ impl MazeData {
     }
 }
 
-pub fn load_maze(filename: &str) -> Maze {
-    match File::open(filename) {
-        Ok(file) => {
-            let reader = BufReader::new(file);
-            let mut maze: Maze = reader
-                .lines()
-                .map(|line| line.unwrap_or_default().chars().collect())
-                .collect();
-
-            // Asegurar que todas las filas tengan la misma longitud
-            if let Some(max_width) = maze.iter().map(|row| row.len()).max() {
-                for row in &mut maze {
-                    while row.len() < max_width {
-                        row.push(' ');
-                    }
-                }
-            }
+/// Lee un nivel del disco. Un archivo que falta, no se puede leer o está
+/// vacío es un error con el mensaje para la pantalla de errores del nivel
+pub fn load_maze(filename: &str) -> Result<Maze, String> {
+    let file = File::open(filename).map_err(|error| format!("NO SE PUDO ABRIR {}: {}", filename, error))?;
+    let mut maze: Maze = BufReader::new(file)
+        .lines()
+        .map(|line| line.map(|line| line.chars().collect()))
+        .collect::<Result<_, _>>()
+        .map_err(|error| format!("NO SE PUDO LEER {}: {}", filename, error))?;
 
-            maze
-        },
-        Err(_) => {
-            create_default_maze()
+    // Asegurar que todas las filas tengan la misma longitud
+    let max_width = maze.iter().map(|row| row.len()).max().unwrap_or(0);
+    if max_width == 0 {
+        return Err(format!("EL NIVEL {} ESTA VACIO", filename));
+    }
+    for row in &mut maze {
+        while row.len() < max_width {
+            row.push(' ');
         }
     }
+
+    Ok(maze)
+}
+
+/// Escribe el laberinto con el mismo formato que lee `load_maze`: una fila por línea
+pub fn save_maze(maze: &Maze, path: &str) -> std::io::Result<()> {
+    let text: String = maze.iter()
+        .map(|row| row.iter().collect::<String>() + "\n")
+        .collect();
+    std::fs::write(path, text)
+}
+
+/// Carga el mapa de regiones de un nivel. Si el archivo no existe el nivel
+/// usa una textura por carácter, como siempre.
+pub fn load_region_map(filename: &str) -> Option<RegionMap> {
+    let file = File::open(filename).ok()?;
+    let regions = BufReader::new(file)
+        .lines()
+        .map(|line| line.unwrap_or_default().chars().collect())
+        .collect();
+
+    Some(regions)
+}
+
+/// Región de una casilla, o None si no tiene (o el mapa no la cubre)
+pub fn region_at(regions: &RegionMap, x: usize, y: usize) -> Option<char> {
+    regions
+        .get(y)
+        .and_then(|row| row.get(x))
+        .copied()
+        .filter(|&region| region != NO_REGION && region != ' ')
 }
 
 pub fn load_maze_data(filename: &str) -> MazeData {
-    let grid = load_maze(filename);
+    let grid = load_maze(filename).unwrap_or_default();
     MazeData::new(grid)
 }
 
-fn create_default_maze() -> Maze {
-    vec![
-        "################".chars().collect(),
-        "#              #".chars().collect(),
-        "# ############ #".chars().collect(),
-        "#              #".chars().collect(),
-        "# ####### # # ###".chars().collect(),
-        "# #     # # #   #".chars().collect(),
-        "# # ### # # ### #".chars().collect(),
-        "# #   # # #   # #".chars().collect(),
-        "# ### # # ### # #".chars().collect(),
-        "# #k  # #   # # #".chars().collect(),
-        "# ##### ### # # #".chars().collect(),
-        "#           # # #".chars().collect(),
-        "############# # #".chars().collect(),
-        "#             # #".chars().collect(),
-        "#           l  ##".chars().collect(),
-        "##############e##".chars().collect(),
-    ]
+/// Revisa que un nivel se pueda completar: que tenga salida y llaves
+/// suficientes, que el inicio no esté en una pared y que cada llave,
+/// checkpoint y salida se alcance desde `spawn` con las puertas abiertas.
+/// Además, las llaves pedidas deben alcanzarse con las puertas cerradas.
+pub fn validate_maze(maze: &Maze, spawn: (usize, usize), keys_needed: usize) -> Result<(), Vec<String>> {
+    let cells_of = |target: char| -> Vec<(usize, usize)> {
+        maze.iter()
+            .enumerate()
+            .flat_map(|(y, row)| row.iter().enumerate().filter(move |(_, &cell)| cell == target).map(move |(x, _)| (x, y)))
+            .collect()
+    };
+    let exits = cells_of('e');
+    let keys = cells_of('k');
+    let checkpoints = cells_of('c');
+    let mut errors = Vec::new();
+
+    if exits.is_empty() {
+        errors.push("NO HAY SALIDA (E) EN EL NIVEL".to_string());
+    }
+    if keys.len() < keys_needed {
+        errors.push(format!("FALTAN LLAVES: HAY {} Y EL NIVEL PIDE {}", keys.len(), keys_needed));
+    }
+
+    let spawn_walkable = maze.get(spawn.1)
+        .and_then(|row| row.get(spawn.0))
+        .is_some_and(|&cell| TileProperties::of(cell).walkable);
+    if !spawn_walkable {
+        errors.push("EL INICIO ESTA DENTRO DE UNA PARED".to_string());
+        return Err(errors);
+    }
+
+    let opened: Maze = maze.iter()
+        .map(|row| row.iter().map(|&cell| if cell == DOOR_CLOSED { DOOR_OPEN } else { cell }).collect())
+        .collect();
+    let unreachable = |cells: &[(usize, usize)]| cells.iter().filter(|&&cell| a_star(&opened, spawn, cell).is_none()).count();
+
+    let sealed_keys = unreachable(&keys);
+    let reachable_keys = keys.iter().filter(|&&key| a_star(maze, spawn, key).is_some()).count();
+    if sealed_keys > 0 {
+        errors.push(format!("LLAVES INALCANZABLES DESDE EL INICIO: {} DE {}", sealed_keys, keys.len()));
+    } else if reachable_keys < keys_needed.min(keys.len()) {
+        errors.push(format!("LLAVES DETRAS DE PUERTAS: {} DE {}", keys.len() - reachable_keys, keys.len()));
+    }
+
+    let sealed_checkpoints = unreachable(&checkpoints);
+    if sealed_checkpoints > 0 {
+        errors.push(format!("CHECKPOINTS INALCANZABLES DESDE EL INICIO: {} DE {}", sealed_checkpoints, checkpoints.len()));
+    }
+
+    if !exits.is_empty() && exits.iter().all(|&exit| a_star(&opened, spawn, exit).is_none()) {
+        errors.push("SALIDA INALCANZABLE DESDE EL INICIO".to_string());
+    }
+
+    if errors.is_empty() { Ok(()) } else { Err(errors) }
+}
+
+// Llaves y trampas que se reparten en un laberinto generado
+pub const GENERATED_MAZE_KEYS: usize = 3;
+const GENERATED_MAZE_TRAPS: usize = 4;
+
+/// Laberinto aleatorio por backtracking recursivo de `width` x `height`
+/// celdas de pasillo (el mapa mide 2n+1 casillas por lado). El inicio 'S'
+/// queda en la esquina superior izquierda, la salida en la celda más lejana
+/// por camino, y las trampas solo en callejones sin salida para no cortar el
+/// paso. La misma semilla da siempre el mismo laberinto.
+pub fn generate_maze(width: usize, height: usize, seed: u64) -> Maze {
+    let width = width.max(2);
+    let height = height.max(2);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut maze: Maze = vec![vec!['#'; width * 2 + 1]; height * 2 + 1];
+    let to_map = |(x, y): (usize, usize)| (x * 2 + 1, y * 2 + 1);
+
+    // Backtracking con pila explícita: se talla hacia un vecino sin visitar al
+    // azar y se retrocede al quedarse sin vecinos
+    let mut visited = vec![vec![false; width]; height];
+    let mut stack: Vec<(usize, usize)> = vec![(0, 0)];
+    visited[0][0] = true;
+    maze[1][1] = ' ';
+
+    while let Some(&(x, y)) = stack.last() {
+        let mut neighbors: Vec<(usize, usize)> = [(x + 1, y), (x.wrapping_sub(1), y), (x, y + 1), (x, y.wrapping_sub(1))]
+            .into_iter()
+            .filter(|&(nx, ny)| nx < width && ny < height && !visited[ny][nx])
+            .collect();
+        neighbors.shuffle(&mut rng);
+
+        let Some(&next) = neighbors.first() else {
+            stack.pop();
+            continue;
+        };
+
+        let (mx, my) = to_map((x, y));
+        let (nx, ny) = to_map(next);
+        maze[(my + ny) / 2][(mx + nx) / 2] = ' ';
+        maze[ny][nx] = ' ';
+        visited[next.1][next.0] = true;
+        stack.push(next);
+    }
+
+    let spawn = (1, 1);
+    let mut cells: Vec<(usize, usize)> = (0..height)
+        .flat_map(|y| (0..width).map(move |x| to_map((x, y))))
+        .filter(|&cell| cell != spawn)
+        .collect();
+
+    // Salida en la celda con el camino más largo desde el inicio
+    let path_length = |cell: (usize, usize)| a_star(&maze, spawn, cell).map_or(0, |path| path.len());
+    let exit_index = (0..cells.len()).max_by_key(|&i| path_length(cells[i])).unwrap_or(0);
+    let exit = cells.swap_remove(exit_index);
+
+    let is_dead_end = |maze: &Maze, (x, y): (usize, usize)| {
+        [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)]
+            .iter()
+            .filter(|&&(nx, ny)| maze[ny][nx] == ' ')
+            .count() == 1
+    };
+    let (mut dead_ends, mut corridors): (Vec<_>, Vec<_>) = cells.into_iter().partition(|&cell| is_dead_end(&maze, cell));
+    dead_ends.shuffle(&mut rng);
+    corridors.shuffle(&mut rng);
+
+    // Trampas primero en callejones; las llaves van donde quede lugar
+    let traps: Vec<_> = dead_ends.drain(..GENERATED_MAZE_TRAPS.min(dead_ends.len())).collect();
+    let keys: Vec<_> = dead_ends.into_iter().chain(corridors).take(GENERATED_MAZE_KEYS).collect();
+
+    maze[spawn.1][spawn.0] = 'S';
+    maze[exit.1][exit.0] = 'e';
+    for (x, y) in traps {
+        maze[y][x] = 't';
+    }
+    for (x, y) in keys {
+        maze[y][x] = 'k';
+    }
+
+    maze
 }
 
 // Funciones de conveniencia para compatibilidad
@@ -147,4 +418,108 @@ pub fn get_cell_safe(x: f32, y: f32, maze: &Maze, block_size: usize) -> char {
 pub fn can_move_to_safe(x: f32, y: f32, maze: &Maze, block_size: usize) -> bool {
     let maze_data = MazeData::new(maze.clone());
     maze_data.can_move_to(x, y, block_size)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn maze(rows: &[&str]) -> Maze {
+        rows.iter().map(|row| row.chars().collect()).collect()
+    }
+
+    #[test]
+    fn missing_or_empty_level_files_are_errors() {
+        let missing = load_maze("assets/levels/no_existe.txt").unwrap_err();
+        assert!(missing.starts_with("NO SE PUDO ABRIR assets/levels/no_existe.txt"), "{}", missing);
+
+        let path = std::env::temp_dir().join("empty_level_test.txt");
+        std::fs::write(&path, "\n\n").unwrap();
+        let empty = load_maze(path.to_str().unwrap());
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(empty, Err(format!("EL NIVEL {} ESTA VACIO", path.to_str().unwrap())));
+
+        assert_eq!(load_maze("assets/levels/level1.txt").map(|maze| maze.is_empty()), Ok(false));
+    }
+
+    #[test]
+    fn generated_mazes_are_reproducible_and_solvable() {
+        let maze = generate_maze(8, 6, 42);
+        assert_eq!((maze.len(), maze[0].len()), (13, 17));
+        assert_eq!(maze, generate_maze(8, 6, 42));
+        assert_ne!(maze, generate_maze(8, 6, 43));
+
+        for seed in 0..20 {
+            let mut maze = generate_maze(8, 6, seed);
+            let count = |maze: &Maze, target: char| maze.iter().flatten().filter(|&&cell| cell == target).count();
+            assert_eq!((count(&maze, 'S'), count(&maze, 'e'), count(&maze, 'k')), (1, 1, GENERATED_MAZE_KEYS));
+            assert!(count(&maze, 't') <= GENERATED_MAZE_TRAPS);
+
+            // El marco exterior siempre es pared
+            assert!(maze[0].iter().chain(maze[12].iter()).all(|&cell| cell == '#'));
+            assert!(maze.iter().all(|row| row[0] == '#' && row[16] == '#'));
+
+            maze[1][1] = ' ';
+            assert_eq!(validate_maze(&maze, (1, 1), GENERATED_MAZE_KEYS), Ok(()), "semilla {}", seed);
+        }
+    }
+
+    #[test]
+    fn validation_reports_missing_and_unreachable_goals() {
+        assert_eq!(validate_maze(&maze(&["#####", "# k #", "#  e#", "#####"]), (1, 1), 1), Ok(()));
+        // Puerta entre el inicio y la salida: se abre con la llave, así que vale
+        assert_eq!(validate_maze(&maze(&["######", "#k D e#", "######"]), (2, 1), 1), Ok(()));
+
+        let errors = validate_maze(&maze(&["#####", "#   #", "#####"]), (1, 1), 2).unwrap_err();
+        assert_eq!(errors, vec![
+            "NO HAY SALIDA (E) EN EL NIVEL".to_string(),
+            "FALTAN LLAVES: HAY 0 Y EL NIVEL PIDE 2".to_string(),
+        ]);
+
+        let walled_off = maze(&["#######", "# #kD e#", "#######"]);
+        let errors = validate_maze(&walled_off, (1, 1), 1).unwrap_err();
+        assert_eq!(errors, vec![
+            "LLAVES INALCANZABLES DESDE EL INICIO: 1 DE 1".to_string(),
+            "SALIDA INALCANZABLE DESDE EL INICIO".to_string(),
+        ]);
+
+        let errors = validate_maze(&walled_off, (0, 0), 1).unwrap_err();
+        assert_eq!(errors, vec!["EL INICIO ESTA DENTRO DE UNA PARED".to_string()]);
+    }
+
+    #[test]
+    fn sealed_keys_and_checkpoints_are_reported_even_when_enough_remain() {
+        // Sobra una llave, pero está encerrada entre paredes igual que el checkpoint
+        let sealed = maze(&["#########", "#kk  e#k#", "#c#####c#", "#########"]);
+        let errors = validate_maze(&sealed, (3, 1), 2).unwrap_err();
+        assert_eq!(errors, vec![
+            "LLAVES INALCANZABLES DESDE EL INICIO: 1 DE 3".to_string(),
+            "CHECKPOINTS INALCANZABLES DESDE EL INICIO: 1 DE 2".to_string(),
+        ]);
+
+        // Una llave tras la única puerta no sirve para abrirla
+        let errors = validate_maze(&maze(&["######", "# Dke#", "######"]), (1, 1), 1).unwrap_err();
+        assert_eq!(errors, vec!["LLAVES DETRAS DE PUERTAS: 1 DE 1".to_string()]);
+    }
+
+    #[test]
+    fn every_tile_has_a_minimap_representation() {
+        for (cell, props) in TILE_TABLE.iter() {
+            let tile = MinimapTile::of(*cell);
+            if *cell == DOOR_CLOSED {
+                assert_eq!(tile, Some(MinimapTile::Door));
+            } else if props.blocks_sight || props.is_low_wall() {
+                assert_eq!(tile, Some(MinimapTile::Wall), "'{}'", cell);
+            } else if *cell == ' ' {
+                assert_eq!(tile, None);
+            } else {
+                assert!(tile.is_some(), "'{}' no se ve en el minimapa", cell);
+            }
+        }
+
+        assert_eq!(MinimapTile::of('v'), Some(MinimapTile::Glass));
+        assert_eq!(MinimapTile::of('t'), Some(MinimapTile::Hazard));
+        assert_eq!(MinimapTile::of('?'), Some(MinimapTile::Unknown));
+        assert_eq!(MinimapTile::of(DOOR_OPEN), Some(MinimapTile::OpenDoor));
+    }
+}
@@ -2,12 +2,18 @@
 
 use raylib::prelude::*;
 use crate::framebuffer::Framebuffer;
+use crate::palette::Palette;
+use std::cmp::Reverse;
 use std::collections::VecDeque;
 
+// Un mensaje idéntico a uno mostrado hace menos de esto se ignora
+const DUPLICATE_WINDOW_SECONDS: f32 = 1.0;
+// Comienzo común de los avisos de salida bloqueada
+const EXIT_BLOCKED_PREFIX: &str = "SALIDA BLOQUEADA";
+
 #[derive(Debug, Clone)]
 pub struct Notification {
     pub message: String,
-    pub color: Color,
     pub duration: f32,
     pub remaining_time: f32,
     pub notification_type: NotificationType,
@@ -32,13 +38,22 @@ impl NotificationType {
             NotificationType::Special => Color::new(200, 100, 255, 255),
         }
     }
+
+    /// Importancia al hacer lugar en la cola: se descarta primero la menor
+    pub fn priority(&self) -> u8 {
+        match self {
+            NotificationType::Error => 3,
+            NotificationType::Warning => 2,
+            NotificationType::Success | NotificationType::Special => 1,
+            NotificationType::Info => 0,
+        }
+    }
 }
 
 impl Notification {
     pub fn new(message: String, notification_type: NotificationType, duration: f32) -> Self {
         Notification {
             message,
-            color: notification_type.get_color(),
             duration,
             remaining_time: duration,
             notification_type,
@@ -50,6 +65,11 @@ impl Notification {
         self.remaining_time > 0.0
     }
 
+    // Segundos desde que se mostró
+    fn age(&self) -> f32 {
+        self.duration - self.remaining_time
+    }
+
     pub fn get_alpha(&self) -> f32 {
         let fade_time = 1.0; // Últimos 1 segundo con fade
         if self.remaining_time <= fade_time {
@@ -62,7 +82,8 @@ impl Notification {
 
 #[derive(Clone)]
 pub struct NotificationManager {
-    notifications: VecDeque<Notification>,
+    // La más nueva primero
+    pub(crate) notifications: VecDeque<Notification>,
     max_notifications: usize,
 }
 
@@ -74,15 +95,31 @@ impl NotificationManager {
         }
     }
 
+    /// Agrega una notificación arriba de la cola. Se ignora si repite un
+    /// mensaje del último segundo; con la cola llena se descarta la de menor
+    /// prioridad (la más vieja entre iguales), o la nueva si es la menor
     pub fn add_notification(&mut self, message: &str, notification_type: NotificationType, duration: f32) {
-        let notification = Notification::new(message.to_string(), notification_type, duration);
-        
-        // Remover notificaciones más antiguas si hay demasiadas
+        let repeated = self.notifications
+            .iter()
+            .any(|notification| notification.message == message && notification.age() < DUPLICATE_WINDOW_SECONDS);
+        if repeated {
+            return;
+        }
+
         while self.notifications.len() >= self.max_notifications {
-            self.notifications.pop_back();
+            // Las más viejas están al final de la cola
+            let Some(lowest) = (0..self.notifications.len())
+                .min_by_key(|&i| (self.notifications[i].notification_type.priority(), Reverse(i)))
+            else {
+                break;
+            };
+            if self.notifications[lowest].notification_type.priority() > notification_type.priority() {
+                return;
+            }
+            self.notifications.remove(lowest);
         }
         
-        self.notifications.push_front(notification);
+        self.notifications.push_front(Notification::new(message.to_string(), notification_type, duration));
     }
 
     // Funciones específicas para diferentes tipos de eventos
@@ -104,12 +141,21 @@ impl NotificationManager {
         }
     }
 
+    pub fn show_doors_unlocked(&mut self) {
+        self.add_notification("PUERTAS DESBLOQUEADAS!", NotificationType::Info, 2.5);
+    }
+
     pub fn show_checkpoint_reached(&mut self, checkpoints: usize, level: usize) {
         let message = format!("CHECKPOINT ALCANZADO! ({}/{})", checkpoints, level.saturating_sub(1));
         self.add_notification(&message, NotificationType::Special, 2.5);
     }
 
     pub fn show_exit_blocked(&mut self, reason: &str) {
+        // Se pide cada frame sobre la salida: uno solo en pantalla a la vez
+        if self.notifications.iter().any(|notification| notification.message.starts_with(EXIT_BLOCKED_PREFIX)) {
+            return;
+        }
+
         let message = match reason {
             "no_key" => "SALIDA BLOQUEADA - ENCUENTRA LAS LLAVES PRIMERO!",
             "no_checkpoints" => "SALIDA BLOQUEADA - CHECKPOINTS INCOMPLETOS!",
@@ -124,10 +170,51 @@ impl NotificationManager {
         self.add_notification(&message, NotificationType::Success, 2.5);
     }
 
+    pub fn show_extra_life_refused(&mut self) {
+        self.add_notification("HARDCORE: NO HAY VIDAS EXTRA", NotificationType::Warning, 2.5);
+    }
+
+    pub fn show_lives_full(&mut self, max_lives: i32) {
+        let message = format!("VIDAS AL MAXIMO: {}", max_lives);
+        self.add_notification(&message, NotificationType::Info, 2.5);
+    }
+
+    pub fn show_new_record(&mut self, seconds: f32) {
+        let message = format!("NEW RECORD! {}", crate::ui::format_level_time(seconds));
+        self.add_notification(&message, NotificationType::Special, 3.0);
+    }
+
+    pub fn show_enemy_hit(&mut self) {
+        self.add_notification("UNA ENTIDAD TE ATRAPO!", NotificationType::Error, 2.0);
+    }
+
     pub fn show_trap_activated(&mut self) {
         self.add_notification("TRAMPA ACTIVADA!", NotificationType::Error, 2.0);
     }
 
+    pub fn show_poisoned(&mut self, seconds: f32) {
+        let message = format!("ENVENENADO! PIERDES UNA VIDA EN {:.0} S", seconds.ceil());
+        self.add_notification(&message, NotificationType::Warning, 2.5);
+    }
+
+    pub fn show_poison_drained(&mut self) {
+        self.add_notification("EL VENENO TE QUITO UNA VIDA!", NotificationType::Error, 2.0);
+    }
+
+    pub fn show_teleported(&mut self) {
+        self.add_notification("TELETRANSPORTADO!", NotificationType::Info, 2.0);
+    }
+
+    pub fn show_screenshot_saved(&mut self, path: &str) {
+        // Solo el nombre del archivo: la carpeta es siempre la misma
+        let file_name = std::path::Path::new(path)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or(path);
+        let message = format!("CAPTURA GUARDADA: {}", file_name);
+        self.add_notification(&message, NotificationType::Info, 2.5);
+    }
+
     pub fn update(&mut self, delta_time: f32) {
         // Actualizar todas las notificaciones y remover las expiradas
         self.notifications.retain_mut(|notification| {
@@ -135,9 +222,10 @@ impl NotificationManager {
         });
     }
 
-    pub fn render(&self, framebuffer: &mut Framebuffer) {
-        let notification_height = 25;
-        let notification_width = 400;
+    /// Dibuja las notificaciones centradas arriba; `scale` agranda caja y texto
+    pub fn render(&self, framebuffer: &mut Framebuffer, palette: &Palette, scale: u32) {
+        let notification_height = 18 + 7 * scale;
+        let notification_width = (400 * scale).min(framebuffer.width.saturating_sub(20));
         let start_x = (framebuffer.width - notification_width) / 2;
         let start_y = 50;
 
@@ -149,20 +237,22 @@ impl NotificationManager {
                 break; // No renderizar fuera de pantalla
             }
 
-            self.render_notification(framebuffer, notification, start_x, notification_y, notification_width, notification_height);
+            let area = (start_x, notification_y, notification_width, notification_height);
+            self.render_notification(framebuffer, notification, palette, area, scale);
         }
     }
 
+    // `(x, y, width, height)`: caja de la notificación en pantalla
     fn render_notification(
         &self,
         framebuffer: &mut Framebuffer,
         notification: &Notification,
-        x: u32,
-        y: u32,
-        width: u32,
-        height: u32,
+        palette: &Palette,
+        (x, y, width, height): (u32, u32, u32, u32),
+        scale: u32,
     ) {
         let alpha = notification.get_alpha();
+        let color = palette.notification_color(notification.notification_type);
         
         // Fondo de la notificación con transparencia
         let bg_color = Color::new(0, 0, 0, (150.0 * alpha) as u8);
@@ -170,33 +260,33 @@ impl NotificationManager {
         for py in y..(y + height) {
             for px in x..(x + width) {
                 if px < framebuffer.width && py < framebuffer.height {
-                    framebuffer.set_pixel(px, py);
+                    framebuffer.blend_pixel(px, py);
                 }
             }
         }
 
-        // Borde de la notificación
+        // Borde de la notificación (se desvanece con el resto)
         let border_color = Color::new(
-            notification.color.r,
-            notification.color.g,
-            notification.color.b,
+            color.r,
+            color.g,
+            color.b,
             (255.0 * alpha) as u8,
         );
         self.render_border(framebuffer, x, y, width, height, border_color);
 
         // Texto de la notificación
         let text_color = Color::new(
-            notification.color.r,
-            notification.color.g,
-            notification.color.b,
+            color.r,
+            color.g,
+            color.b,
             (255.0 * alpha) as u8,
         );
         framebuffer.set_current_color(text_color);
         
         // Centrar el texto en la notificación
         let text_x = x + 10;
-        let text_y = y + (height - 7) / 2; // 7 es la altura de la fuente
-        crate::ui::render_text(framebuffer, &notification.message, text_x, text_y);
+        let text_y = y + (height - 7 * scale) / 2; // 7 es la altura de la fuente
+        crate::ui::render_text_clipped(framebuffer, &notification.message, text_x, text_y, x + width - 10, scale);
     }
 
     fn render_border(&self, framebuffer: &mut Framebuffer, x: u32, y: u32, width: u32, height: u32, color: Color) {
@@ -206,10 +296,10 @@ impl NotificationManager {
         for px in x..=(x + width) {
             if px < framebuffer.width {
                 if y < framebuffer.height {
-                    framebuffer.set_pixel(px, y);
+                    framebuffer.blend_pixel(px, y);
                 }
                 if y + height < framebuffer.height {
-                    framebuffer.set_pixel(px, y + height);
+                    framebuffer.blend_pixel(px, y + height);
                 }
             }
         }
@@ -218,10 +308,10 @@ impl NotificationManager {
         for py in y..=(y + height) {
             if py < framebuffer.height {
                 if x < framebuffer.width {
-                    framebuffer.set_pixel(x, py);
+                    framebuffer.blend_pixel(x, py);
                 }
                 if x + width < framebuffer.width {
-                    framebuffer.set_pixel(x + width, py);
+                    framebuffer.blend_pixel(x + width, py);
                 }
             }
         }
@@ -230,4 +320,97 @@ impl NotificationManager {
     pub fn clear_all(&mut self) {
         self.notifications.clear();
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::palette::{Palette, PaletteKind};
+
+    #[test]
+    fn long_messages_stay_inside_the_notification_box() {
+        // A escala 1 la caja mide 400px centrada (x = 100 a 500, borde incluido);
+        // a escala 2 no cabe y se ajusta a la pantalla menos 10px por lado
+        for (scale, box_width) in [(1, 400), (2, 580)] {
+            let mut framebuffer = Framebuffer::new(600, 120);
+            let mut manager = NotificationManager::new();
+            manager.add_notification(&"MENSAJE DEMASIADO LARGO ".repeat(10), NotificationType::Warning, 3.0);
+
+            manager.render(&mut framebuffer, &Palette::of(PaletteKind::Default), scale);
+
+            let box_left = (framebuffer.width - box_width) / 2;
+            let box_right = box_left + box_width;
+            for y in 0..framebuffer.height {
+                for x in (0..box_left).chain(box_right + 1..framebuffer.width) {
+                    let color = framebuffer.color_buffer.get_color(x as i32, y as i32);
+                    assert_eq!(color, Color::BLACK, "escala {}: pixel fuera de la caja en ({}, {})", scale, x, y);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn notification_backgrounds_let_the_world_show_through() {
+        let mut framebuffer = Framebuffer::new(600, 120);
+        framebuffer.set_background_color(Color::WHITE);
+        framebuffer.clear();
+        let mut manager = NotificationManager::new();
+        manager.add_notification("HOLA", NotificationType::Info, 3.0);
+
+        manager.render(&mut framebuffer, &Palette::of(PaletteKind::Default), 1);
+
+        // Fondo negro con alfa 150 sobre blanco: gris opaco, no negro
+        let background = framebuffer.get_pixel(490, 60).unwrap();
+        assert_eq!(background, Color::new(104, 104, 104, 255));
+    }
+
+    #[test]
+    fn duplicates_within_a_second_are_ignored() {
+        let mut manager = NotificationManager::new();
+        manager.add_notification("TELETRANSPORTADO!", NotificationType::Info, 2.0);
+        manager.update(0.5);
+        manager.add_notification("TELETRANSPORTADO!", NotificationType::Info, 2.0);
+        assert_eq!(manager.notifications.len(), 1);
+
+        // Pasado el segundo el mismo mensaje vuelve a entrar
+        manager.update(0.6);
+        manager.add_notification("TELETRANSPORTADO!", NotificationType::Info, 2.0);
+        assert_eq!(manager.notifications.len(), 2);
+
+        // Parado sobre la salida solo hay un aviso, aunque cambie el motivo
+        manager.clear_all();
+        for _ in 0..120 {
+            manager.show_exit_blocked("no_key");
+            manager.show_exit_blocked("no_checkpoints");
+            manager.update(1.0 / 60.0);
+        }
+        assert_eq!(manager.notifications.len(), 1);
+    }
+
+    #[test]
+    fn errors_outrank_info_when_the_queue_is_full() {
+        let mut manager = NotificationManager::new();
+        manager.add_notification("ERROR 1", NotificationType::Error, 3.0);
+        for i in 0..4 {
+            manager.add_notification(&format!("INFO {}", i), NotificationType::Info, 3.0);
+        }
+
+        manager.add_notification("ERROR 2", NotificationType::Error, 3.0);
+        let messages: Vec<&str> = manager.notifications.iter().map(|n| n.message.as_str()).collect();
+        assert_eq!(messages, vec!["ERROR 2", "INFO 3", "INFO 2", "INFO 1", "ERROR 1"]);
+
+        // Con la cola llena de errores, un Info nuevo no desplaza a ninguno
+        for i in 3..7 {
+            manager.add_notification(&format!("ERROR {}", i), NotificationType::Error, 3.0);
+        }
+        manager.add_notification("INFO 4", NotificationType::Info, 3.0);
+        assert!(manager.notifications.iter().all(|n| n.notification_type == NotificationType::Error));
+    }
+
+    #[test]
+    fn screenshot_notice_names_the_saved_file() {
+        let mut manager = NotificationManager::new();
+        manager.show_screenshot_saved("assets/screenshots/screenshot_1700000000000.png");
+        assert_eq!(manager.notifications[0].message, "CAPTURA GUARDADA: screenshot_1700000000000.png");
+    }
+}
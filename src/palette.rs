@@ -0,0 +1,279 @@
+// palette.rs - Paletas de colores semánticos para HUD, minimapa y notificaciones
+
+use raylib::prelude::*;
+use crate::game_state::RANDOM_LEVEL;
+use crate::maze::MinimapTile;
+use crate::notification::NotificationType;
+
+/// Paleta elegida por el jugador
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PaletteKind {
+    Default,
+    Deuteranopia,
+    Protanopia,
+    HighContrast,
+}
+
+impl PaletteKind {
+    pub const ALL: [PaletteKind; 4] = [
+        PaletteKind::Default,
+        PaletteKind::Deuteranopia,
+        PaletteKind::Protanopia,
+        PaletteKind::HighContrast,
+    ];
+
+    /// Nombre usado en el archivo de settings
+    pub fn name(&self) -> &'static str {
+        match self {
+            PaletteKind::Default => "default",
+            PaletteKind::Deuteranopia => "deuteranopia",
+            PaletteKind::Protanopia => "protanopia",
+            PaletteKind::HighContrast => "high_contrast",
+        }
+    }
+
+    /// Nombre para mostrar en pantalla (la fuente solo tiene mayúsculas)
+    pub fn label(&self) -> &'static str {
+        match self {
+            PaletteKind::Default => "NORMAL",
+            PaletteKind::Deuteranopia => "DEUTERANOPIA",
+            PaletteKind::Protanopia => "PROTANOPIA",
+            PaletteKind::HighContrast => "ALTO CONTRASTE",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        PaletteKind::ALL.iter().copied().find(|kind| kind.name() == name)
+    }
+
+    pub fn next(&self) -> Self {
+        let index = PaletteKind::ALL.iter().position(|kind| kind == self).unwrap_or(0);
+        PaletteKind::ALL[(index + 1) % PaletteKind::ALL.len()]
+    }
+}
+
+/// Colores por significado. Las paletas para daltonismo evitan distinguir
+/// estados solo por rojo contra verde (usan azul / naranja / amarillo).
+#[derive(Debug, Clone, Copy)]
+pub struct Palette {
+    pub kind: PaletteKind,
+    pub good: Color,
+    pub caution: Color,
+    pub danger: Color,
+    pub info: Color,
+    pub special: Color,
+    pub muted: Color,
+    pub text: Color,
+    pub frame: Color,
+    pub wall: Color,
+    pub key: Color,
+    pub exit: Color,
+    pub hazard: Color,
+    pub checkpoint: Color,
+    pub extra_life: Color,
+    pub glass: Color,
+    pub player: Color,
+    pub player_direction: Color,
+}
+
+impl Palette {
+    pub fn of(kind: PaletteKind) -> Palette {
+        match kind {
+            PaletteKind::Default => Palette {
+                kind: PaletteKind::Default,
+                good: Color::GREEN,
+                caution: Color::YELLOW,
+                danger: Color::RED,
+                info: Color::CYAN,
+                special: Color::PURPLE,
+                muted: Color::GRAY,
+                text: Color::WHITE,
+                frame: Color::new(100, 100, 150, 255),
+                wall: Color::new(180, 180, 120, 255),
+                key: Color::GOLD,
+                exit: Color::GREEN,
+                hazard: Color::RED,
+                checkpoint: Color::CYAN,
+                extra_life: Color::PURPLE,
+                glass: Color::SKYBLUE,
+                player: Color::RED,
+                player_direction: Color::BLUE,
+            },
+            // Verde y rojo se confunden: bueno = azul, peligro = naranja/bermellón
+            PaletteKind::Deuteranopia => Palette {
+                kind: PaletteKind::Deuteranopia,
+                good: Color::new(86, 180, 233, 255),
+                caution: Color::new(240, 228, 66, 255),
+                danger: Color::new(230, 110, 0, 255),
+                info: Color::new(200, 200, 255, 255),
+                special: Color::new(204, 121, 167, 255),
+                muted: Color::GRAY,
+                text: Color::WHITE,
+                frame: Color::new(100, 100, 150, 255),
+                wall: Color::new(170, 170, 170, 255),
+                key: Color::new(240, 228, 66, 255),
+                exit: Color::new(86, 180, 233, 255),
+                hazard: Color::new(230, 110, 0, 255),
+                checkpoint: Color::WHITE,
+                extra_life: Color::new(204, 121, 167, 255),
+                glass: Color::new(150, 210, 230, 255),
+                player: Color::new(230, 110, 0, 255),
+                player_direction: Color::WHITE,
+            },
+            // El rojo se ve oscuro: peligro en naranja claro para que resalte
+            PaletteKind::Protanopia => Palette {
+                kind: PaletteKind::Protanopia,
+                good: Color::new(0, 140, 230, 255),
+                caution: Color::new(240, 228, 66, 255),
+                danger: Color::new(255, 170, 0, 255),
+                info: Color::new(200, 200, 255, 255),
+                special: Color::new(204, 121, 167, 255),
+                muted: Color::GRAY,
+                text: Color::WHITE,
+                frame: Color::new(100, 100, 150, 255),
+                wall: Color::new(170, 170, 170, 255),
+                key: Color::new(240, 228, 66, 255),
+                exit: Color::new(0, 140, 230, 255),
+                hazard: Color::new(255, 170, 0, 255),
+                checkpoint: Color::WHITE,
+                extra_life: Color::new(204, 121, 167, 255),
+                glass: Color::new(150, 210, 230, 255),
+                player: Color::new(255, 170, 0, 255),
+                player_direction: Color::WHITE,
+            },
+            // Pocos colores muy saturados sobre negro
+            PaletteKind::HighContrast => Palette {
+                kind: PaletteKind::HighContrast,
+                good: Color::WHITE,
+                caution: Color::YELLOW,
+                danger: Color::MAGENTA,
+                info: Color::new(0, 255, 255, 255),
+                special: Color::new(0, 255, 255, 255),
+                muted: Color::new(160, 160, 160, 255),
+                text: Color::WHITE,
+                frame: Color::WHITE,
+                wall: Color::WHITE,
+                key: Color::YELLOW,
+                exit: Color::new(0, 255, 255, 255),
+                hazard: Color::MAGENTA,
+                checkpoint: Color::new(0, 255, 0, 255),
+                extra_life: Color::YELLOW,
+                glass: Color::new(120, 120, 255, 255),
+                player: Color::YELLOW,
+                player_direction: Color::new(0, 255, 255, 255),
+            },
+        }
+    }
+
+    /// Copia con las paredes del minimapa del color del tema del nivel (ver
+    /// LEVEL_THEME_FILES). Solo en la paleta normal: las de accesibilidad
+    /// mantienen su contraste
+    pub fn for_level_theme(&self, level: usize) -> Palette {
+        let wall = match (self.kind, level) {
+            (PaletteKind::Default, 2) => Color::new(180, 90, 80, 255),
+            (PaletteKind::Default, 3) => Color::new(90, 110, 190, 255),
+            (PaletteKind::Default, RANDOM_LEVEL) => Color::new(90, 170, 100, 255),
+            _ => self.wall,
+        };
+        Palette { wall, ..*self }
+    }
+
+    /// Color de las vidas: bien con 3 o más, aviso con 2, peligro con 1 o menos
+    pub fn lives_color(&self, lives: i32) -> Color {
+        if lives > 2 {
+            self.good
+        } else if lives == 2 {
+            self.caution
+        } else {
+            self.danger
+        }
+    }
+
+    pub fn fps_color(&self, fps: f32) -> Color {
+        if fps >= 50.0 {
+            self.good
+        } else if fps >= 30.0 {
+            self.caution
+        } else {
+            self.danger
+        }
+    }
+
+    pub fn notification_color(&self, notification_type: NotificationType) -> Color {
+        // La paleta normal conserva los tonos suaves propios de las notificaciones
+        if self.kind == PaletteKind::Default {
+            return notification_type.get_color();
+        }
+
+        match notification_type {
+            NotificationType::Info => self.info,
+            NotificationType::Warning => self.caution,
+            NotificationType::Error => self.danger,
+            NotificationType::Success => self.good,
+            NotificationType::Special => self.special,
+        }
+    }
+
+    pub fn minimap_color(&self, tile: MinimapTile) -> Color {
+        match tile {
+            MinimapTile::Wall => self.wall,
+            MinimapTile::Glass => self.glass,
+            MinimapTile::Key => self.key,
+            MinimapTile::ExtraLife => self.extra_life,
+            MinimapTile::Checkpoint => self.checkpoint,
+            MinimapTile::Exit => self.exit,
+            // La puerta cerrada lleva el color de la llave que la abre
+            MinimapTile::Door => self.key,
+            MinimapTile::OpenDoor => self.muted,
+            MinimapTile::Hazard => self.hazard,
+            MinimapTile::Unknown => self.text,
+        }
+    }
+
+    /// Suelo ya recorrido en el minimapa: el tono apagado de la paleta, oscurecido
+    pub fn visited_floor(&self) -> Color {
+        Color::new(self.muted.r / 3, self.muted.g / 3, self.muted.b / 3, 255)
+    }
+
+    /// Color de una casilla en el minimapa; None para el suelo
+    pub fn minimap_cell_color(&self, cell: char) -> Option<Color> {
+        MinimapTile::of(cell).map(|tile| self.minimap_color(tile))
+    }
+}
+
+/// Texto que acompaña al color de las vidas para no depender solo de él
+pub fn lives_status_label(lives: i32) -> Option<&'static str> {
+    if lives <= 1 {
+        Some("CRITICO")
+    } else if lives == 2 {
+        Some("BAJO")
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn palette_names_round_trip_and_cycle_through_all() {
+        let mut kind = PaletteKind::Default;
+        for _ in 0..PaletteKind::ALL.len() {
+            assert_eq!(PaletteKind::from_name(kind.name()), Some(kind));
+            kind = kind.next();
+        }
+        assert_eq!(kind, PaletteKind::Default);
+    }
+
+    #[test]
+    fn colorblind_palettes_do_not_use_red_and_green_for_status() {
+        for kind in [PaletteKind::Deuteranopia, PaletteKind::Protanopia] {
+            let palette = Palette::of(kind);
+            for color in [palette.good, palette.danger, palette.exit, palette.hazard] {
+                assert_ne!(color, Color::GREEN);
+                assert_ne!(color, Color::RED);
+            }
+        }
+    }
+}
@@ -0,0 +1,156 @@
+// pathfinding.rs - Caminos más cortos sobre la rejilla del laberinto
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet, VecDeque};
+use crate::maze::{Maze, TileProperties};
+
+/// Camino más corto de `start` a `goal` (ambos incluidos) pasando solo por
+/// casillas caminables, con movimientos en cruz. Paredes y puertas cerradas
+/// cortan el paso. None si no hay camino o algún extremo no se puede pisar.
+pub fn a_star(maze: &Maze, start: (usize, usize), goal: (usize, usize)) -> Option<Vec<(usize, usize)>> {
+    if !is_walkable(maze, start) || !is_walkable(maze, goal) {
+        return None;
+    }
+
+    let width = maze.iter().map(|row| row.len()).max().unwrap_or(0);
+    let index = |(x, y): (usize, usize)| y * width + x;
+    // Distancia Manhattan: nunca sobreestima con movimientos en cruz
+    let heuristic = |(x, y): (usize, usize)| x.abs_diff(goal.0) + y.abs_diff(goal.1);
+
+    let mut cost = vec![usize::MAX; width * maze.len()];
+    let mut came_from: Vec<Option<(usize, usize)>> = vec![None; width * maze.len()];
+    let mut open = BinaryHeap::new();
+    cost[index(start)] = 0;
+    open.push(Reverse((heuristic(start), 0, start)));
+
+    while let Some(Reverse((_, current_cost, current))) = open.pop() {
+        if current == goal {
+            let mut path = vec![goal];
+            let mut cell = goal;
+            while let Some(previous) = came_from[index(cell)] {
+                path.push(previous);
+                cell = previous;
+            }
+            path.reverse();
+            return Some(path);
+        }
+
+        // Entrada vieja de una casilla que ya se alcanzó por un camino mejor
+        if current_cost > cost[index(current)] {
+            continue;
+        }
+
+        let (x, y) = current;
+        let neighbors = [(x + 1, y), (x.wrapping_sub(1), y), (x, y + 1), (x, y.wrapping_sub(1))];
+        for neighbor in neighbors {
+            if !is_walkable(maze, neighbor) {
+                continue;
+            }
+
+            let neighbor_cost = current_cost + 1;
+            if neighbor_cost < cost[index(neighbor)] {
+                cost[index(neighbor)] = neighbor_cost;
+                came_from[index(neighbor)] = Some(current);
+                open.push(Reverse((neighbor_cost + heuristic(neighbor), neighbor_cost, neighbor)));
+            }
+        }
+    }
+
+    None
+}
+
+/// Todas las casillas a las que se llega caminando desde `start` (incluida),
+/// con las mismas reglas que `a_star`; vacío si `start` no se puede pisar
+pub fn reachable_cells(maze: &Maze, start: (usize, usize)) -> HashSet<(usize, usize)> {
+    let mut reached = HashSet::new();
+    if !is_walkable(maze, start) {
+        return reached;
+    }
+
+    let mut frontier = VecDeque::from([start]);
+    reached.insert(start);
+    while let Some((x, y)) = frontier.pop_front() {
+        let neighbors = [(x + 1, y), (x.wrapping_sub(1), y), (x, y + 1), (x, y.wrapping_sub(1))];
+        for neighbor in neighbors {
+            if is_walkable(maze, neighbor) && reached.insert(neighbor) {
+                frontier.push_back(neighbor);
+            }
+        }
+    }
+
+    reached
+}
+
+// Las filas pueden tener largos distintos: fuera de ellas no se camina
+fn is_walkable(maze: &Maze, (x, y): (usize, usize)) -> bool {
+    maze.get(y)
+        .and_then(|row| row.get(x))
+        .is_some_and(|&cell| TileProperties::of(cell).walkable)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::DOOR_CLOSED;
+
+    #[test]
+    fn paths_go_around_walls_and_never_through_them() {
+        let maze: Maze = ["#####", "#   #", "# # #", "#   #", "#####"]
+            .iter()
+            .map(|row| row.chars().collect())
+            .collect();
+
+        let path = a_star(&maze, (1, 1), (3, 3)).unwrap();
+        assert_eq!(path.len(), 5);
+        assert_eq!((path[0], path[4]), ((1, 1), (3, 3)));
+        assert!(path.iter().all(|&cell| is_walkable(&maze, cell)));
+        assert!(path.windows(2).all(|step| step[0].0.abs_diff(step[1].0) + step[0].1.abs_diff(step[1].1) == 1));
+
+        assert_eq!(a_star(&maze, (1, 1), (1, 1)), Some(vec![(1, 1)]));
+        assert_eq!(a_star(&maze, (1, 1), (2, 2)), None);
+    }
+
+    #[test]
+    fn a_star_crosses_a_winding_maze_but_not_a_closed_door() {
+        let mut maze: Maze = [
+            "###################",
+            "#                 #",
+            "# ############### #",
+            "#                 #",
+            "# ####### # # ####",
+            "# #     # # #   #",
+            "# # ### # # ### #",
+            "# #   # # #   # #",
+            "# ### # # ### # #",
+            "# #k  # #   # # #",
+            "# ##### ### # # #",
+            "#           # # #",
+            "############# # #",
+            "#             # #",
+            "#           l  ##",
+            "##############e##",
+        ].iter().map(|row| row.chars().collect()).collect();
+
+        // Del inicio (1, 1) a la salida (14, 15) hay 27 pasos
+        let path = a_star(&maze, (1, 1), (14, 15)).unwrap();
+        assert_eq!(path.len(), 28);
+        assert_eq!(a_star(&maze, (1, 1), (3, 9)).map(|path| path.len()), Some(35));
+
+        // La única casilla frente a la salida, cerrada con una puerta
+        maze[14][14] = DOOR_CLOSED;
+        assert_eq!(a_star(&maze, (1, 1), (14, 15)), None);
+    }
+
+    #[test]
+    fn reachable_cells_stop_at_walls_and_closed_doors() {
+        let maze: Maze = ["#######", "#  #  #", "#  D  #", "#######"]
+            .iter()
+            .map(|row| row.chars().collect())
+            .collect();
+
+        let reached = reachable_cells(&maze, (1, 1));
+        let expected: HashSet<(usize, usize)> = [(1, 1), (2, 1), (1, 2), (2, 2)].into_iter().collect();
+        assert_eq!(reached, expected);
+        assert!(reachable_cells(&maze, (0, 0)).is_empty());
+    }
+}
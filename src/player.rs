@@ -2,7 +2,7 @@
 
 use raylib::prelude::*;
 use std::f32::consts::PI;
-use crate::maze::Maze;
+use crate::maze::{Maze, TileProperties};
 
 #[derive(Clone)]
 pub struct Player {
@@ -10,15 +10,50 @@ pub struct Player {
     pub a: f32,
     pub fov: f32,
     pub radius: f32,
+    pub velocity: Vector2,
+    pub move_speed: f32,
+    // Mirada vertical como fracción de la altura de pantalla (positivo = arriba)
+    pub pitch: f32,
 }
 
+pub const DEFAULT_PLAYER_RADIUS: f32 = 20.0;
+
+// Límite de la mirada vertical: el horizonte no pasa del 15% / 85% de la pantalla
+pub const MAX_PITCH: f32 = 0.35;
+
+// Campo de visión ajustable con la rueda del mouse (radianes): de 40° a 110°,
+// el máximo que se puede elegir en los settings
+pub const MIN_FOV: f32 = PI / 4.5;
+pub const MAX_FOV: f32 = PI * 11.0 / 18.0;
+const FOV_STEP: f32 = PI / 36.0;
+
+// Los ajustes "por frame" están pensados a esta tasa; con otra se escalan por
+// delta_time para que el jugador recorra lo mismo por segundo
+pub const REFERENCE_FPS: f32 = 60.0;
+
+// Ajustes de movimiento (unidades por frame de referencia)
+pub const MAX_MOVE_SPEED: f32 = 18.0;
+pub const MOVE_ACCELERATION: f32 = 6.0;
+pub const MOVE_FRICTION: f32 = 4.5;
+// Velocidad al correr (SHIFT) respecto a la normal
+pub const SPRINT_MULTIPLIER: f32 = 1.6;
+
+// Gamepad: el stick derecho a fondo equivale a mover el mouse tantos pixeles por frame
+pub const GAMEPAD_LOOK_SENSITIVITY: f32 = 20.0;
+// Inclinación mínima de un stick (0 a 1) para que cuente
+pub const DEFAULT_GAMEPAD_DEADZONE: f32 = 0.25;
+const GAMEPAD_ID: i32 = 0;
+
 impl Player {
     pub fn new() -> Self {
         Player {
             pos: Vector2::new(150.0, 150.0),
             a: PI / 3.0,
             fov: PI / 3.0,
-            radius: 20.0,
+            radius: DEFAULT_PLAYER_RADIUS,
+            velocity: Vector2::zero(),
+            move_speed: MAX_MOVE_SPEED,
+            pitch: 0.0,
         }
     }
 
@@ -27,27 +62,202 @@ impl Player {
             pos: Vector2::new(x, y),
             a: angle,
             fov: PI / 3.0,
-            radius: 20.0,
+            radius: DEFAULT_PLAYER_RADIUS,
+            velocity: Vector2::zero(),
+            move_speed: MAX_MOVE_SPEED,
+            pitch: 0.0,
         }
     }
+
+    /// Fila de pantalla donde queda el horizonte según la mirada vertical
+    pub fn horizon(&self, screen_height: u32) -> f32 {
+        screen_height as f32 * (0.5 + self.pitch)
+    }
+}
+
+/// Teclas de movimiento elegidas por el jugador. Las flechas, RE PAG / AV PAG
+/// y el SHIFT derecho siguen funcionando siempre como alternativa fija
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeyBindings {
+    pub forward: KeyboardKey,
+    pub backward: KeyboardKey,
+    pub strafe_left: KeyboardKey,
+    pub strafe_right: KeyboardKey,
+    pub turn_left: KeyboardKey,
+    pub turn_right: KeyboardKey,
+    pub sprint: KeyboardKey,
 }
 
-// Función principal de procesamiento de eventos con validación de colisiones
-pub fn process_events_with_maze_safe(
-    player: &mut Player, 
-    rl: &RaylibHandle, 
-    maze: &Maze, 
-    block_size: usize
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings {
+            forward: KeyboardKey::KEY_W,
+            backward: KeyboardKey::KEY_S,
+            strafe_left: KeyboardKey::KEY_A,
+            strafe_right: KeyboardKey::KEY_D,
+            turn_left: KeyboardKey::KEY_Q,
+            turn_right: KeyboardKey::KEY_E,
+            sprint: KeyboardKey::KEY_LEFT_SHIFT,
+        }
+    }
+}
+
+// Teclas que se pueden asignar, con el nombre que se usa en settings.toml
+const BINDABLE_KEYS: [(&str, KeyboardKey); 33] = [
+    ("A", KeyboardKey::KEY_A), ("B", KeyboardKey::KEY_B), ("C", KeyboardKey::KEY_C),
+    ("D", KeyboardKey::KEY_D), ("E", KeyboardKey::KEY_E), ("F", KeyboardKey::KEY_F),
+    ("G", KeyboardKey::KEY_G), ("H", KeyboardKey::KEY_H), ("I", KeyboardKey::KEY_I),
+    ("J", KeyboardKey::KEY_J), ("K", KeyboardKey::KEY_K), ("L", KeyboardKey::KEY_L),
+    ("M", KeyboardKey::KEY_M), ("N", KeyboardKey::KEY_N), ("O", KeyboardKey::KEY_O),
+    ("P", KeyboardKey::KEY_P), ("Q", KeyboardKey::KEY_Q), ("R", KeyboardKey::KEY_R),
+    ("S", KeyboardKey::KEY_S), ("T", KeyboardKey::KEY_T), ("U", KeyboardKey::KEY_U),
+    ("V", KeyboardKey::KEY_V), ("W", KeyboardKey::KEY_W), ("X", KeyboardKey::KEY_X),
+    ("Y", KeyboardKey::KEY_Y), ("Z", KeyboardKey::KEY_Z),
+    ("SPACE", KeyboardKey::KEY_SPACE), ("TAB", KeyboardKey::KEY_TAB),
+    ("LEFT_SHIFT", KeyboardKey::KEY_LEFT_SHIFT), ("RIGHT_SHIFT", KeyboardKey::KEY_RIGHT_SHIFT),
+    ("LEFT_CONTROL", KeyboardKey::KEY_LEFT_CONTROL), ("RIGHT_CONTROL", KeyboardKey::KEY_RIGHT_CONTROL),
+    ("LEFT_ALT", KeyboardKey::KEY_LEFT_ALT),
+];
+
+impl KeyBindings {
+    /// Acciones con su tecla, en el orden en que se guardan
+    pub fn entries(&self) -> [(&'static str, KeyboardKey); 7] {
+        [
+            ("forward", self.forward),
+            ("backward", self.backward),
+            ("strafe_left", self.strafe_left),
+            ("strafe_right", self.strafe_right),
+            ("turn_left", self.turn_left),
+            ("turn_right", self.turn_right),
+            ("sprint", self.sprint),
+        ]
+    }
+
+    /// Asigna una tecla por nombre; devuelve false si la acción o la tecla
+    /// no existen, sin cambiar nada
+    pub fn bind(&mut self, action: &str, key_name: &str) -> bool {
+        let Some(key) = key_from_name(key_name) else {
+            return false;
+        };
+        let slot = match action {
+            "forward" => &mut self.forward,
+            "backward" => &mut self.backward,
+            "strafe_left" => &mut self.strafe_left,
+            "strafe_right" => &mut self.strafe_right,
+            "turn_left" => &mut self.turn_left,
+            "turn_right" => &mut self.turn_right,
+            "sprint" => &mut self.sprint,
+            _ => return false,
+        };
+        *slot = key;
+        true
+    }
+}
+
+pub fn key_from_name(name: &str) -> Option<KeyboardKey> {
+    let name = name.trim().to_ascii_uppercase();
+    BINDABLE_KEYS.iter().find(|(key_name, _)| *key_name == name).map(|(_, key)| *key)
+}
+
+pub fn key_name(key: KeyboardKey) -> &'static str {
+    BINDABLE_KEYS.iter().find(|(_, bound)| *bound == key).map(|(name, _)| *name).unwrap_or("?")
+}
+
+/// Estado de entrada de un frame, independiente de raylib para poder
+/// simular secuencias de teclas en tests
+#[derive(Debug, Clone, Copy, Default)]
+pub struct InputState {
+    pub forward: bool,
+    pub backward: bool,
+    pub strafe_left: bool,
+    pub strafe_right: bool,
+    pub turn_left: bool,
+    pub turn_right: bool,
+    pub look_up: bool,
+    pub look_down: bool,
+    pub sprint: bool,
+    pub mouse_delta_x: f32,
+    pub mouse_delta_y: f32,
+}
+
+impl InputState {
+    pub fn from_raylib(rl: &RaylibHandle, keys: &KeyBindings, mouse_delta_x: f32, mouse_delta_y: f32) -> Self {
+        InputState {
+            forward: rl.is_key_down(KeyboardKey::KEY_UP) || rl.is_key_down(keys.forward),
+            backward: rl.is_key_down(KeyboardKey::KEY_DOWN) || rl.is_key_down(keys.backward),
+            strafe_left: rl.is_key_down(keys.strafe_left),
+            strafe_right: rl.is_key_down(keys.strafe_right),
+            turn_left: rl.is_key_down(KeyboardKey::KEY_LEFT) || rl.is_key_down(keys.turn_left),
+            turn_right: rl.is_key_down(KeyboardKey::KEY_RIGHT) || rl.is_key_down(keys.turn_right),
+            look_up: rl.is_key_down(KeyboardKey::KEY_PAGE_UP),
+            look_down: rl.is_key_down(KeyboardKey::KEY_PAGE_DOWN),
+            sprint: rl.is_key_down(keys.sprint) || rl.is_key_down(KeyboardKey::KEY_RIGHT_SHIFT),
+            mouse_delta_x,
+            mouse_delta_y,
+        }
+    }
+
+    /// Suma la entrada del primer gamepad conectado; teclado, mouse y mando
+    /// funcionan a la vez
+    pub fn with_gamepad(self, rl: &RaylibHandle, deadzone: f32, delta_time: f32) -> Self {
+        if !rl.is_gamepad_available(GAMEPAD_ID) {
+            return self;
+        }
+
+        let axis = |axis| rl.get_gamepad_axis_movement(GAMEPAD_ID, axis);
+        let left = Vector2::new(axis(GamepadAxis::GAMEPAD_AXIS_LEFT_X), axis(GamepadAxis::GAMEPAD_AXIS_LEFT_Y));
+        let right = Vector2::new(axis(GamepadAxis::GAMEPAD_AXIS_RIGHT_X), axis(GamepadAxis::GAMEPAD_AXIS_RIGHT_Y));
+        let mut input = self.with_sticks(left, right, deadzone, delta_time * REFERENCE_FPS);
+        // Apretar el stick izquierdo corre, como en la mayoría de juegos
+        input.sprint |= rl.is_gamepad_button_down(GAMEPAD_ID, GamepadButton::GAMEPAD_BUTTON_LEFT_THUMB);
+        input
+    }
+
+    // Stick izquierdo como las teclas de movimiento (así las grabaciones no
+    // cambian de formato); el derecho gira y mira como el mouse, con su fuerza
+    // y durante los `frames` de referencia que dura el frame real
+    fn with_sticks(mut self, left: Vector2, right: Vector2, deadzone: f32, frames: f32) -> Self {
+        self.forward |= left.y < -deadzone;
+        self.backward |= left.y > deadzone;
+        self.strafe_left |= left.x < -deadzone;
+        self.strafe_right |= left.x > deadzone;
+
+        let look = apply_deadzone(right, deadzone) * frames;
+        self.mouse_delta_x += look.x * GAMEPAD_LOOK_SENSITIVITY;
+        self.mouse_delta_y += look.y * GAMEPAD_LOOK_SENSITIVITY;
+        self
+    }
+
+    /// Se pide desplazamiento (sin contar los giros)
+    pub fn is_walking(&self) -> bool {
+        self.forward || self.backward || self.strafe_left || self.strafe_right
+    }
+
+    pub fn is_moving(&self) -> bool {
+        self.forward || self.backward || self.strafe_left || self.strafe_right ||
+            self.turn_left || self.turn_right
+    }
+}
+
+// Función principal de procesamiento de entrada con validación de colisiones.
+// Todo lo que avanza por tiempo se escala por delta_time; el mouse no, porque
+// su desplazamiento ya es la distancia real recorrida
+pub fn apply_input(
+    player: &mut Player,
+    input: &InputState,
+    maze: &Maze,
+    block_size: usize,
+    delta_time: f32,
 ) {
-    const MOVE_SPEED: f32 = 18.0;
     const ROTATION_SPEED: f32 = PI / 18.0;
+    let frames = delta_time * REFERENCE_FPS;
 
     // Rotación con teclas
-    if rl.is_key_down(KeyboardKey::KEY_RIGHT) || rl.is_key_down(KeyboardKey::KEY_E) {
-        player.a += ROTATION_SPEED;
+    if input.turn_right {
+        player.a += ROTATION_SPEED * frames;
     }
-    if rl.is_key_down(KeyboardKey::KEY_LEFT) || rl.is_key_down(KeyboardKey::KEY_Q) {
-        player.a -= ROTATION_SPEED;
+    if input.turn_left {
+        player.a -= ROTATION_SPEED * frames;
     }
 
     // Normalizar ángulo
@@ -58,68 +268,121 @@ pub fn process_events_with_maze_safe(
         player.a -= 2.0 * PI;
     }
 
-    // Movimiento hacia adelante
-    if rl.is_key_down(KeyboardKey::KEY_UP) || rl.is_key_down(KeyboardKey::KEY_W) {
-        let new_x = player.pos.x + MOVE_SPEED * player.a.cos();
-        let new_y = player.pos.y + MOVE_SPEED * player.a.sin();
-        
-        if can_move_to_safe(new_x, new_y, maze, block_size, player.radius) {
-            player.pos.x = new_x;
-            player.pos.y = new_y;
-        } else {
-            // Intentar movimiento deslizante (sliding)
-            if can_move_to_safe(new_x, player.pos.y, maze, block_size, player.radius) {
-                player.pos.x = new_x;
-            } else if can_move_to_safe(player.pos.x, new_y, maze, block_size, player.radius) {
-                player.pos.y = new_y;
-            }
-        }
+    // Mirada vertical con teclas o mouse (subir el mouse mira hacia arriba)
+    const PITCH_SPEED: f32 = 0.02;
+    const MOUSE_PITCH_SENSITIVITY: f32 = 0.0015;
+    if input.look_up {
+        player.pitch += PITCH_SPEED * frames;
     }
+    if input.look_down {
+        player.pitch -= PITCH_SPEED * frames;
+    }
+    player.pitch -= input.mouse_delta_y * MOUSE_PITCH_SENSITIVITY;
+    player.pitch = player.pitch.clamp(-MAX_PITCH, MAX_PITCH);
 
-    // Movimiento hacia atrás
-    if rl.is_key_down(KeyboardKey::KEY_DOWN) || rl.is_key_down(KeyboardKey::KEY_S) {
-        let new_x = player.pos.x - MOVE_SPEED * player.a.cos();
-        let new_y = player.pos.y - MOVE_SPEED * player.a.sin();
-        
-        if can_move_to_safe(new_x, new_y, maze, block_size, player.radius) {
-            player.pos.x = new_x;
-            player.pos.y = new_y;
-        } else {
-            // Intentar movimiento deslizante
-            if can_move_to_safe(new_x, player.pos.y, maze, block_size, player.radius) {
-                player.pos.x = new_x;
-            } else if can_move_to_safe(player.pos.x, new_y, maze, block_size, player.radius) {
-                player.pos.y = new_y;
-            }
-        }
+    // Velocidad deseada según las teclas (el strafe es más lento)
+    let forward_dir = Vector2::new(player.a.cos(), player.a.sin());
+    let right_dir = Vector2::new(-player.a.sin(), player.a.cos());
+    let mut wish = Vector2::zero();
+
+    if input.forward {
+        wish += forward_dir;
+    }
+    if input.backward {
+        wish -= forward_dir;
+    }
+    if input.strafe_right {
+        wish += right_dir * 0.7;
+    }
+    if input.strafe_left {
+        wish -= right_dir * 0.7;
+    }
+    if wish.length() > 1.0 {
+        wish = wish.normalized();
     }
 
-    // Movimiento lateral (strafe)
-    if rl.is_key_down(KeyboardKey::KEY_D) {
-        let strafe_angle = player.a + PI / 2.0;
-        let new_x = player.pos.x + MOVE_SPEED * 0.7 * strafe_angle.cos();
-        let new_y = player.pos.y + MOVE_SPEED * 0.7 * strafe_angle.sin();
-        
+    // Acelerar hacia la velocidad deseada o frenar por fricción sin pasarse
+    let speed = if input.sprint { player.move_speed * SPRINT_MULTIPLIER } else { player.move_speed };
+    let target_velocity = wish * speed;
+    let max_change = if wish.length_sqr() > 0.0 { MOVE_ACCELERATION } else { MOVE_FRICTION };
+    player.velocity = approach(player.velocity, target_velocity, max_change * frames);
+
+    let travel = player.velocity * frames;
+    if travel.length_sqr() == 0.0 {
+        return;
+    }
+
+    // Avanzar en subpasos de medio radio como máximo: con un radio pequeño y
+    // mucha velocidad un paso completo podría cruzar la esquina de una pared
+    let max_step = (player.radius * 0.5).max(1.0);
+    let steps = (travel.length() / max_step).ceil().max(1.0) as usize;
+    let mut step = travel / steps as f32;
+
+    for _ in 0..steps {
+        let new_x = player.pos.x + step.x;
+        let new_y = player.pos.y + step.y;
+
         if can_move_to_safe(new_x, new_y, maze, block_size, player.radius) {
             player.pos.x = new_x;
             player.pos.y = new_y;
-        }
-    }
-    
-    if rl.is_key_down(KeyboardKey::KEY_A) {
-        let strafe_angle = player.a - PI / 2.0;
-        let new_x = player.pos.x + MOVE_SPEED * 0.7 * strafe_angle.cos();
-        let new_y = player.pos.y + MOVE_SPEED * 0.7 * strafe_angle.sin();
-        
-        if can_move_to_safe(new_x, new_y, maze, block_size, player.radius) {
+        } else if can_move_to_safe(new_x, player.pos.y, maze, block_size, player.radius) {
+            // Deslizar sobre la pared, perdiendo la velocidad contra ella
             player.pos.x = new_x;
+            player.velocity.y = 0.0;
+            step.y = 0.0;
+        } else if can_move_to_safe(player.pos.x, new_y, maze, block_size, player.radius) {
             player.pos.y = new_y;
+            player.velocity.x = 0.0;
+            step.x = 0.0;
+        } else {
+            player.velocity = Vector2::zero();
+            break;
         }
     }
 }
 
-// Función de colisión segura que verifica múltiples puntos del jugador
-fn can_move_to_safe(x: f32, y: f32, maze: &Maze, block_size: usize, radius: f32) -> bool {
+// Deadzone radial reescalada: la respuesta empieza en 0 justo en el borde
+fn apply_deadzone(stick: Vector2, deadzone: f32) -> Vector2 {
+    let length = stick.length();
+    if length <= deadzone {
+        return Vector2::zero();
+    }
+
+    let scaled = ((length - deadzone) / (1.0 - deadzone).max(f32::EPSILON)).min(1.0);
+    stick * (scaled / length)
+}
+
+/// Apretar los dos gatillos del gamepad a la vez reinicia el nivel
+pub fn gamepad_reset_pressed(rl: &RaylibHandle) -> bool {
+    let left = GamepadButton::GAMEPAD_BUTTON_LEFT_TRIGGER_2;
+    let right = GamepadButton::GAMEPAD_BUTTON_RIGHT_TRIGGER_2;
+
+    rl.is_gamepad_available(GAMEPAD_ID) && (
+        (rl.is_gamepad_button_pressed(GAMEPAD_ID, left) && rl.is_gamepad_button_down(GAMEPAD_ID, right)) ||
+        (rl.is_gamepad_button_pressed(GAMEPAD_ID, right) && rl.is_gamepad_button_down(GAMEPAD_ID, left))
+    )
+}
+
+/// Rueda hacia adelante acerca la vista (FOV más estrecho), hacia atrás la abre
+pub fn adjust_fov(player: &mut Player, wheel_move: f32) {
+    player.fov = (player.fov - wheel_move * FOV_STEP).clamp(MIN_FOV, MAX_FOV);
+}
+
+// Mueve `current` hacia `target` como máximo `max_step`, llegando exactamente al destino
+fn approach(current: Vector2, target: Vector2, max_step: f32) -> Vector2 {
+    let delta = target - current;
+    let distance = delta.length();
+
+    if distance <= max_step {
+        target
+    } else {
+        current + delta * (max_step / distance)
+    }
+}
+
+/// Colisión círculo contra casillas: el círculo del jugador en (x, y) no puede
+/// solaparse con el cuadrado de ninguna casilla no caminable
+pub fn can_move_to_safe(x: f32, y: f32, maze: &Maze, block_size: usize, radius: f32) -> bool {
     // Verificar que las coordenadas estén dentro de rangos razonables
     if x < 0.0 || y < 0.0 || x > 10000.0 || y > 10000.0 {
         return false;
@@ -129,23 +392,39 @@ fn can_move_to_safe(x: f32, y: f32, maze: &Maze, block_size: usize, radius: f32)
         return false;
     }
 
-    // Verificar múltiples puntos del jugador para colisión más precisa
-    let points = [
-        (x, y),                                    // Centro
-        (x + radius, y),                           // Derecha
-        (x - radius, y),                           // Izquierda  
-        (x, y + radius),                           // Abajo
-        (x, y - radius),                           // Arriba
-        (x + radius * 0.7, y + radius * 0.7),     // Diagonal SE
-        (x - radius * 0.7, y - radius * 0.7),     // Diagonal NW
-        (x + radius * 0.7, y - radius * 0.7),     // Diagonal NE
-        (x - radius * 0.7, y + radius * 0.7),     // Diagonal SW
-    ];
+    if !is_position_walkable_safe(x, y, maze, block_size) {
+        return false;
+    }
 
-    // Todos los puntos deben ser válidos para permitir el movimiento
-    points.iter().all(|&(px, py)| {
-        is_position_walkable_safe(px, py, maze, block_size)
-    })
+    // Solo las casillas que toca la caja del círculo pueden solaparse con él;
+    // fuera del laberinto cuenta como pared
+    let block = block_size as f32;
+    let first_col = ((x - radius) / block).floor() as i64;
+    let last_col = ((x + radius) / block).floor() as i64;
+    let first_row = ((y - radius) / block).floor() as i64;
+    let last_row = ((y + radius) / block).floor() as i64;
+
+    for row in first_row..=last_row {
+        for col in first_col..=last_col {
+            let walkable = usize::try_from(row).ok()
+                .zip(usize::try_from(col).ok())
+                .and_then(|(row, col)| maze.get(row)?.get(col).copied())
+                .is_some_and(|cell| TileProperties::of(cell).walkable);
+            if walkable {
+                continue;
+            }
+
+            // Punto del cuadrado más cercano al centro del círculo
+            let closest_x = x.clamp(col as f32 * block, (col + 1) as f32 * block);
+            let closest_y = y.clamp(row as f32 * block, (row + 1) as f32 * block);
+            let (dx, dy) = (x - closest_x, y - closest_y);
+            if dx * dx + dy * dy < radius * radius {
+                return false;
+            }
+        }
+    }
+
+    true
 }
 
 // Verifica si una posición específica es caminable
@@ -166,15 +445,8 @@ fn is_position_walkable_safe(x: f32, y: f32, maze: &Maze, block_size: usize) ->
             return false;
         }
         
-        let cell = row[grid_x];
-        
         // Las trampas son técnicamente caminables, pero peligrosas
-        if cell == 't' {
-            return true;
-        }
-        
-        // Permitir movimiento a través de espacios vacíos y elementos interactivos
-        matches!(cell, ' ' | 'k' | 't' | 'l' | 'c' | 'e')
+        TileProperties::of(row[grid_x]).walkable
     } else {
         false
     }
@@ -224,7 +496,7 @@ pub fn process_mouse_input_safe(player: &mut Player, mouse_delta: f32) {
 
 // Funciones de compatibilidad
 pub fn process_events_with_maze(player: &mut Player, rl: &RaylibHandle, maze: &Maze, block_size: usize) {
-    process_events_with_maze_safe(player, rl, maze, block_size);
+    apply_input(player, &InputState::from_raylib(rl, &KeyBindings::default(), 0.0, 0.0), maze, block_size, 1.0 / REFERENCE_FPS);
 }
 
 pub fn process_mouse_input(player: &mut Player, mouse_delta: f32) {
@@ -237,5 +509,194 @@ pub fn can_move_to_with_maze(x: f32, y: f32, maze: &Maze, block_size: usize) ->
 
 // Función legacy para process_events
 pub fn process_events(player: &mut Player, rl: &RaylibHandle, game_state: &crate::game_state::GameState, block_size: usize) {
-    process_events_with_maze_safe(player, rl, &game_state.data.maze, block_size);
-}
\ No newline at end of file
+    process_events_with_maze(player, rl, &game_state.data.maze, block_size);
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAME: f32 = 1.0 / REFERENCE_FPS;
+
+    fn open_room() -> Maze {
+        ["#######", "#     #", "#     #", "#     #", "#######"]
+            .iter()
+            .map(|row| row.chars().collect())
+            .collect()
+    }
+
+    #[test]
+    fn velocity_ramps_up_and_stops_exactly_on_release() {
+        let maze = open_room();
+        let mut player = Player::new_with_pos(150.0, 250.0, 0.0);
+        let forward = InputState { forward: true, ..InputState::default() };
+
+        apply_input(&mut player, &forward, &maze, 100, FRAME);
+        assert_eq!(player.velocity.length(), MOVE_ACCELERATION);
+
+        for _ in 0..5 {
+            apply_input(&mut player, &forward, &maze, 100, FRAME);
+        }
+        assert_eq!(player.velocity.length(), MAX_MOVE_SPEED);
+
+        for _ in 0..10 {
+            apply_input(&mut player, &InputState::default(), &maze, 100, FRAME);
+        }
+        assert_eq!(player.velocity, Vector2::zero());
+
+        let resting_pos = player.pos;
+        apply_input(&mut player, &InputState::default(), &maze, 100, FRAME);
+        assert_eq!(player.pos, resting_pos);
+    }
+
+    #[test]
+    fn diagonal_movement_is_no_faster_and_still_slides_along_walls() {
+        let maze = open_room();
+        let diagonal = InputState { forward: true, strafe_left: true, ..InputState::default() };
+
+        // Adelante + lateral suman un solo vector normalizado a la velocidad máxima
+        let mut player = Player::new_with_pos(350.0, 250.0, 0.0);
+        for _ in 0..6 {
+            apply_input(&mut player, &diagonal, &maze, 100, FRAME);
+        }
+        assert!((player.velocity.length() - MAX_MOVE_SPEED).abs() < 1e-3);
+
+        // Contra la pared de arriba el mismo vector desliza hacia adelante
+        let mut player = Player::new_with_pos(150.0, 150.0, 0.0);
+        for _ in 0..20 {
+            apply_input(&mut player, &diagonal, &maze, 100, FRAME);
+        }
+        assert!(player.pos.y >= 100.0 + player.radius - 1e-3);
+        assert!(player.pos.x > 300.0);
+        assert!(player.velocity.x > 0.0);
+    }
+
+    #[test]
+    fn small_fast_player_does_not_cut_wall_corners() {
+        // Pilar en la casilla (2, 2): su esquina superior izquierda está en (200, 200)
+        let maze: Maze = ["#####", "#   #", "# # #", "#   #", "#####"]
+            .iter()
+            .map(|row| row.chars().collect())
+            .collect();
+        let mut player = Player::new_with_pos(193.0, 213.0, 0.0);
+        player.radius = 6.0;
+        player.move_speed = 40.0;
+
+        // Sin entrada la fricción deja la velocidad en (20, -20): el destino está
+        // libre pero el trayecto pasa por dentro del pilar
+        let direction = Vector2::new(1.0, -1.0).normalized();
+        player.velocity = direction * (20.0 * 2.0_f32.sqrt() + MOVE_FRICTION);
+        apply_input(&mut player, &InputState::default(), &maze, 100, FRAME);
+
+        assert!(player.pos.x < 200.0, "el jugador cruzó la esquina: {:?}", player.pos);
+        assert!(can_move_to_safe(player.pos.x, player.pos.y, &maze, 100, player.radius));
+    }
+
+    #[test]
+    fn player_circle_collides_with_wall_corners_but_fits_narrow_corridors() {
+        // Pilar en la casilla (2, 2) con la esquina superior izquierda en (200, 200)
+        let maze: Maze = ["#####", "#   #", "# # #", "#   #", "#####"]
+            .iter()
+            .map(|row| row.chars().collect())
+            .collect();
+        let radius = 20.0;
+
+        // En diagonal a la esquina: a 0.9 radios choca, a 1.05 radios queda libre
+        let near = 200.0 - radius * 0.9 / 2.0_f32.sqrt();
+        let far = 200.0 - radius * 1.05 / 2.0_f32.sqrt();
+        assert!(!can_move_to_safe(near, near, &maze, 100, radius));
+        assert!(can_move_to_safe(far, far, &maze, 100, radius));
+
+        // Pasillo de una casilla de ancho: entra si el diámetro cabe, aunque
+        // quede a un pixel de ambas paredes
+        let corridor: Maze = ["#####", "#   #", "#####"]
+            .iter()
+            .map(|row| row.chars().collect())
+            .collect();
+        assert!(can_move_to_safe(250.0, 150.0, &corridor, 100, 49.0));
+        assert!(!can_move_to_safe(250.0, 150.0, &corridor, 100, 51.0));
+        assert!(!can_move_to_safe(250.0, 120.0, &corridor, 100, 25.0));
+
+        // Recorrer el pasillo a lo largo sin atascarse
+        let mut player = Player::new_with_pos(150.0, 150.0, 0.0);
+        player.radius = 45.0;
+        let forward = InputState { forward: true, ..InputState::default() };
+        for _ in 0..60 {
+            apply_input(&mut player, &forward, &corridor, 100, FRAME);
+        }
+        assert!(player.pos.x > 340.0, "se atascó en {:?}", player.pos);
+    }
+
+    #[test]
+    fn distance_and_turning_per_second_do_not_depend_on_the_frame_rate() {
+        let corridor: Maze = [format!("#{}#", " ".repeat(28))]
+            .iter()
+            .map(|row| row.chars().collect())
+            .collect();
+        let run_one_second = |fps: usize, input: InputState| {
+            let mut player = Player::new_with_pos(150.0, 50.0, 0.0);
+            for _ in 0..fps {
+                apply_input(&mut player, &input, &corridor, 100, 1.0 / fps as f32);
+            }
+            (player.pos.x - 150.0, player.a)
+        };
+        let walk_one_second = |fps| {
+            let (distance, _) = run_one_second(fps, InputState { forward: true, ..InputState::default() });
+            let (_, angle) = run_one_second(fps, InputState { turn_right: true, ..InputState::default() });
+            (distance, angle)
+        };
+
+        let (distance_60, angle_60) = walk_one_second(60);
+        assert!(distance_60 > MAX_MOVE_SPEED * 50.0);
+        for fps in [30, 144] {
+            let (distance, angle) = walk_one_second(fps);
+            assert!((distance - distance_60).abs() < distance_60 * 0.02, "{} FPS: {} vs {}", fps, distance, distance_60);
+            assert!((angle - angle_60).abs() < 1e-3, "{} FPS: {} vs {}", fps, angle, angle_60);
+        }
+    }
+
+    #[test]
+    fn wheel_zoom_changes_fov_within_bounds() {
+        let mut player = Player::new();
+        let start = player.fov;
+
+        adjust_fov(&mut player, 1.0);
+        assert!(player.fov < start);
+
+        adjust_fov(&mut player, 100.0);
+        assert_eq!(player.fov, MIN_FOV);
+        adjust_fov(&mut player, -100.0);
+        assert_eq!(player.fov, MAX_FOV);
+    }
+
+    #[test]
+    fn pitch_is_clamped_and_moves_the_horizon() {
+        let maze = open_room();
+        let mut player = Player::new_with_pos(150.0, 250.0, 0.0);
+        let look_up = InputState { look_up: true, ..InputState::default() };
+
+        for _ in 0..100 {
+            apply_input(&mut player, &look_up, &maze, 100, FRAME);
+        }
+        assert_eq!(player.pitch, MAX_PITCH);
+        assert!(player.horizon(900) > 450.0);
+
+        let flick_down = InputState { mouse_delta_y: 10_000.0, ..InputState::default() };
+        apply_input(&mut player, &flick_down, &maze, 100, FRAME);
+        assert_eq!(player.pitch, -MAX_PITCH);
+        assert!(player.horizon(900) < 450.0);
+    }
+
+    #[test]
+    fn gamepad_sticks_respect_the_deadzone() {
+        let drift = Vector2::new(0.15, -0.15);
+        let idle = InputState::default().with_sticks(drift, drift, DEFAULT_GAMEPAD_DEADZONE, 1.0);
+        assert!(!idle.is_moving());
+        assert_eq!((idle.mouse_delta_x, idle.mouse_delta_y), (0.0, 0.0));
+
+        // Adelante con el izquierdo y derecha a fondo con el derecho, sobre el teclado
+        let keyboard = InputState { strafe_left: true, mouse_delta_x: 3.0, ..InputState::default() };
+        let input = keyboard.with_sticks(Vector2::new(0.1, -0.9), Vector2::new(1.0, 0.0), DEFAULT_GAMEPAD_DEADZONE, 1.0);
+        assert!(input.forward && input.strafe_left && !input.strafe_right && !input.backward);
+        assert!((input.mouse_delta_x - (3.0 + GAMEPAD_LOOK_SENSITIVITY)).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,278 @@
+// replay.rs - Grabación y reproducción de la entrada de una partida
+
+use std::fs;
+use crate::player::InputState;
+
+const REPLAY_HEADER: &str = "REPLAY v3";
+// Los replays v1 no tienen columnas de mirada vertical ni los v2 de sprint;
+// se leen con la mirada quieta y sin correr
+const REPLAY_HEADER_V1: &str = "REPLAY v1";
+const REPLAY_HEADER_V2: &str = "REPLAY v2";
+const FRAME_COLUMNS_V1: usize = 9;
+const FRAME_COLUMNS_V2: usize = 12;
+const FRAME_COLUMNS: usize = 13;
+
+/// Entrada de un frame junto con el delta_time con el que se simuló
+#[derive(Debug, Clone, Copy)]
+pub struct ReplayFrame {
+    pub delta_time: f32,
+    pub input: InputState,
+}
+
+/// Registro de la entrada de un nivel. Como `GameState::tick` es determinista,
+/// volver a alimentarle estos frames reproduce la partida exactamente.
+#[derive(Debug, Clone)]
+pub struct Replay {
+    pub level: usize,
+    // Semilla del azar del nivel (laberinto aleatorio y trampas de
+    // teletransporte); al reproducir se vuelve a usar la misma
+    pub seed: u64,
+    pub frames: Vec<ReplayFrame>,
+}
+
+impl Replay {
+    pub fn new(level: usize, seed: u64) -> Self {
+        Replay {
+            level,
+            seed,
+            frames: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, delta_time: f32, input: &InputState) {
+        self.frames.push(ReplayFrame { delta_time, input: *input });
+    }
+
+    /// Serializa como log de texto: una línea por frame con su marca de tiempo
+    pub fn to_log(&self) -> String {
+        let mut log = format!("{}\nlevel {}\nseed {}\n", REPLAY_HEADER, self.level, self.seed);
+        log.push_str("# tiempo delta adelante atras izq der girar_izq girar_der mouse_x mirar_arriba mirar_abajo mouse_y correr\n");
+
+        let mut time = 0.0;
+        for frame in &self.frames {
+            time += frame.delta_time;
+            let input = &frame.input;
+            log.push_str(&format!(
+                "{} {} {} {} {} {} {} {} {} {} {} {} {}\n",
+                time,
+                frame.delta_time,
+                input.forward as u8,
+                input.backward as u8,
+                input.strafe_left as u8,
+                input.strafe_right as u8,
+                input.turn_left as u8,
+                input.turn_right as u8,
+                input.mouse_delta_x,
+                input.look_up as u8,
+                input.look_down as u8,
+                input.mouse_delta_y,
+                input.sprint as u8,
+            ));
+        }
+
+        log
+    }
+
+    pub fn from_log(log: &str) -> Result<Self, String> {
+        let mut lines = log.lines().filter(|line| !line.trim().is_empty() && !line.starts_with('#'));
+
+        let columns = match lines.next() {
+            Some(REPLAY_HEADER) => FRAME_COLUMNS,
+            Some(REPLAY_HEADER_V2) => FRAME_COLUMNS_V2,
+            Some(REPLAY_HEADER_V1) => FRAME_COLUMNS_V1,
+            _ => return Err("Encabezado de replay inválido".to_string()),
+        };
+
+        let level = parse_field(lines.next(), "level")?;
+        let seed = parse_field(lines.next(), "seed")?;
+        let mut replay = Replay::new(level, seed);
+
+        for (index, line) in lines.enumerate() {
+            let values: Vec<&str> = line.split_whitespace().collect();
+            if values.len() != columns {
+                return Err(format!("Frame {} con {} columnas", index, values.len()));
+            }
+
+            let flag = |i: usize| values.get(i) == Some(&"1");
+            let number = |i: usize| match values.get(i) {
+                Some(value) => value.parse::<f32>()
+                    .map_err(|_| format!("Frame {}: valor inválido '{}'", index, value)),
+                None => Ok(0.0),
+            };
+
+            replay.frames.push(ReplayFrame {
+                delta_time: number(1)?,
+                input: InputState {
+                    forward: flag(2),
+                    backward: flag(3),
+                    strafe_left: flag(4),
+                    strafe_right: flag(5),
+                    turn_left: flag(6),
+                    turn_right: flag(7),
+                    mouse_delta_x: number(8)?,
+                    look_up: flag(9),
+                    look_down: flag(10),
+                    mouse_delta_y: number(11)?,
+                    sprint: flag(12),
+                },
+            });
+        }
+
+        Ok(replay)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        fs::write(path, self.to_log()).map_err(|e| format!("No se pudo guardar el replay {}: {}", path, e))
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let log = fs::read_to_string(path).map_err(|e| format!("No se pudo leer el replay {}: {}", path, e))?;
+        Replay::from_log(&log)
+    }
+}
+
+fn parse_field<T: std::str::FromStr>(line: Option<&str>, name: &str) -> Result<T, String> {
+    line.and_then(|line| line.strip_prefix(name))
+        .and_then(|value| value.trim().parse().ok())
+        .ok_or_else(|| format!("Falta el campo '{}' en el replay", name))
+}
+
+/// Entrega los frames grabados en orden; sustituye a la entrada en vivo
+pub struct ReplayPlayer {
+    replay: Replay,
+    cursor: usize,
+}
+
+impl ReplayPlayer {
+    pub fn new(replay: Replay) -> Self {
+        ReplayPlayer { replay, cursor: 0 }
+    }
+
+    pub fn level(&self) -> usize {
+        self.replay.level
+    }
+
+    /// Semilla del laberinto aleatorio con que se grabó
+    pub fn seed(&self) -> u64 {
+        self.replay.seed
+    }
+
+    pub fn next_frame(&mut self) -> Option<ReplayFrame> {
+        let frame = self.replay.frames.get(self.cursor).copied();
+        if frame.is_some() {
+            self.cursor += 1;
+        }
+        frame
+    }
+}
+
+/// Modo de replay elegido por línea de comandos
+pub enum ReplayMode {
+    Off,
+    Record(String),
+    Playback(String),
+}
+
+impl ReplayMode {
+    /// `--record <archivo>` graba la partida, `--replay <archivo>` la reproduce
+    pub fn from_args<I: Iterator<Item = String>>(mut args: I) -> Self {
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--record" => {
+                    if let Some(path) = args.next() {
+                        return ReplayMode::Record(path);
+                    }
+                },
+                "--replay" => {
+                    if let Some(path) = args.next() {
+                        return ReplayMode::Playback(path);
+                    }
+                },
+                _ => {}
+            }
+        }
+
+        ReplayMode::Off
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_state::{GameMode, GameState};
+    use raylib::prelude::*;
+
+    fn scripted_run(inputs: &[InputState]) -> (GameState, Replay) {
+        let mut state = GameState::new();
+        state.mode = GameMode::Playing;
+        state.data.maze = ["#######", "#     #", "#  k  #", "#     #", "#######"]
+            .iter()
+            .map(|row| row.chars().collect())
+            .collect();
+        state.data.player.pos = Vector2::new(150.0, 150.0);
+
+        let mut replay = Replay::new(1, 0);
+        for input in inputs {
+            replay.record(0.016, input);
+            state.tick(input, 0.016, 100);
+        }
+
+        (state, replay)
+    }
+
+    #[test]
+    fn log_round_trip_reproduces_the_run() {
+        let mut inputs = vec![InputState { forward: true, mouse_delta_x: -13.5, ..InputState::default() }; 12];
+        inputs.extend(vec![InputState { strafe_right: true, turn_left: true, ..InputState::default() }; 8]);
+        inputs.extend(vec![InputState { look_up: true, mouse_delta_y: 4.0, ..InputState::default() }; 5]);
+        inputs.extend(vec![InputState { forward: true, sprint: true, ..InputState::default() }; 6]);
+        inputs.extend(vec![InputState::default(); 6]);
+
+        let (recorded_state, replay) = scripted_run(&inputs);
+        let parsed = Replay::from_log(&replay.to_log()).unwrap();
+        assert_eq!(parsed.level, 1);
+        assert_eq!(parsed.frames.len(), inputs.len());
+
+        let mut player = ReplayPlayer::new(parsed);
+        let mut replayed_inputs = Vec::new();
+        while let Some(frame) = player.next_frame() {
+            replayed_inputs.push(frame.input);
+        }
+        let (replayed_state, _) = scripted_run(&replayed_inputs);
+
+        assert_eq!(replayed_state.data.player.pos, recorded_state.data.player.pos);
+        assert_eq!(replayed_state.data.player.a, recorded_state.data.player.a);
+        assert_eq!(replayed_state.data.player.pitch, recorded_state.data.player.pitch);
+        assert_eq!(replayed_state.data.keys_collected, recorded_state.data.keys_collected);
+    }
+
+    #[test]
+    fn invalid_logs_are_rejected() {
+        assert!(Replay::from_log("").is_err());
+        assert!(Replay::from_log("REPLAY v1\nlevel 1\nseed 0\n0.1 0.1 1 0\n").is_err());
+        assert!(Replay::from_log("REPLAY v2\nlevel 1\nseed 0\n0.1 0.1 1 0 0 0 0 0 0\n").is_err());
+    }
+
+    #[test]
+    fn version_one_logs_still_load_without_vertical_look() {
+        let log = "REPLAY v1\nlevel 2\nseed 0\n0.1 0.1 1 0 0 0 0 1 -2.5\n";
+        let replay = Replay::from_log(log).unwrap();
+        let input = replay.frames[0].input;
+
+        assert_eq!(replay.level, 2);
+        assert!(input.forward && input.turn_right);
+        assert_eq!(input.mouse_delta_x, -2.5);
+        assert!(!input.look_up && !input.look_down);
+        assert_eq!(input.mouse_delta_y, 0.0);
+    }
+
+    #[test]
+    fn version_two_logs_still_load_without_sprint() {
+        let log = "REPLAY v2\nlevel 1\nseed 0\n0.1 0.1 1 0 0 0 0 0 0 1 0 3.5\n";
+        let input = Replay::from_log(log).unwrap().frames[0].input;
+
+        assert!(input.forward && input.look_up);
+        assert_eq!(input.mouse_delta_y, 3.5);
+        assert!(!input.sprint);
+    }
+}
@@ -0,0 +1,138 @@
+// score.rs - Puntuación por nivel y récord de puntos de la partida
+
+use std::fs;
+use std::path::Path;
+
+pub const HIGH_SCORE_FILE: &str = "assets/config/highscore.json";
+
+// Puntos fijos por completar un nivel
+const LEVEL_BASE_POINTS: i32 = 1000;
+// Bonus por cada vida que queda al llegar a la salida
+const POINTS_PER_LIFE: i32 = 250;
+// Bonus por cada segundo que sobra respecto al tiempo par del nivel
+const POINTS_PER_SPARE_SECOND: i32 = 10;
+// Penalización por cada trampa pisada en el nivel
+const POINTS_PER_TRAP: i32 = 150;
+
+/// Tiempo de referencia en segundos: lo que sobre de él da puntos
+pub fn par_seconds(level: usize) -> f32 {
+    match level {
+        1 => 90.0,
+        2 => 150.0,
+        3 => 210.0,
+        _ => 120.0,
+    }
+}
+
+/// Desglose de los puntos de un nivel, para mostrarlo en la victoria
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LevelScore {
+    pub base: i32,
+    pub lives_bonus: i32,
+    pub time_bonus: i32,
+    pub trap_penalty: i32,
+}
+
+impl LevelScore {
+    pub fn new(level: usize, lives: i32, level_time: f32, traps_triggered: u32) -> Self {
+        let spare_seconds = (par_seconds(level) - level_time).max(0.0) as i32;
+        LevelScore {
+            base: LEVEL_BASE_POINTS,
+            lives_bonus: lives.max(0) * POINTS_PER_LIFE,
+            time_bonus: spare_seconds * POINTS_PER_SPARE_SECOND,
+            trap_penalty: traps_triggered as i32 * POINTS_PER_TRAP,
+        }
+    }
+
+    /// Total del nivel; las trampas nunca lo dejan en negativo
+    pub fn total(&self) -> i32 {
+        (self.base + self.lives_bonus + self.time_bonus - self.trap_penalty).max(0)
+    }
+}
+
+/// Mejor puntuación de una partida completa guardada entre sesiones
+#[derive(Debug, Clone, Default)]
+pub struct HighScore {
+    best: i32,
+}
+
+impl HighScore {
+    /// Carga el récord; un archivo ausente o con errores empieza en cero
+    pub fn load() -> Self {
+        fs::read_to_string(HIGH_SCORE_FILE)
+            .map(|text| HighScore::parse(&text))
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        if let Some(dir) = Path::new(HIGH_SCORE_FILE).parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+
+        if let Err(e) = fs::write(HIGH_SCORE_FILE, self.to_json()) {
+            eprintln!("No se pudo guardar la puntuación máxima: {}", e);
+        }
+    }
+
+    /// Lee `{"high_score": puntos}`; cualquier otra cosa cuenta como cero
+    pub fn parse(text: &str) -> Self {
+        let body = text.trim().trim_start_matches('{').trim_end_matches('}');
+        let best = body
+            .split_once(':')
+            .filter(|(key, _)| key.trim().trim_matches('"') == "high_score")
+            .and_then(|(_, value)| value.trim().parse::<i32>().ok())
+            .unwrap_or(0)
+            .max(0);
+
+        HighScore { best }
+    }
+
+    pub fn to_json(&self) -> String {
+        format!("{{\n  \"high_score\": {}\n}}\n", self.best)
+    }
+
+    pub fn best(&self) -> i32 {
+        self.best
+    }
+
+    /// Guarda la puntuación si supera el récord; devuelve si fue récord
+    pub fn record(&mut self, score: i32) -> bool {
+        if score <= self.best {
+            return false;
+        }
+
+        self.best = score;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn level_score_rewards_lives_and_spare_time_and_charges_traps() {
+        let score = LevelScore::new(1, 3, 60.5, 2);
+        assert_eq!(score.lives_bonus, 3 * POINTS_PER_LIFE);
+        assert_eq!(score.time_bonus, 29 * POINTS_PER_SPARE_SECOND);
+        assert_eq!(score.trap_penalty, 2 * POINTS_PER_TRAP);
+        assert_eq!(score.total(), 1000 + 750 + 290 - 300);
+
+        // Pasado el tiempo par no hay bonus, y el total no baja de cero
+        let slow = LevelScore::new(1, 0, 500.0, 20);
+        assert_eq!(slow.time_bonus, 0);
+        assert_eq!(slow.total(), 0);
+    }
+
+    #[test]
+    fn only_higher_scores_replace_the_record_and_survive_a_round_trip() {
+        let mut high_score = HighScore::default();
+        assert!(high_score.record(4200));
+        assert!(!high_score.record(4200));
+        assert!(!high_score.record(100));
+
+        assert_eq!(HighScore::parse(&high_score.to_json()).best(), 4200);
+        assert_eq!(HighScore::parse("{\"high_score\": lots}").best(), 0);
+        assert_eq!(HighScore::parse("{\"other\": 50}").best(), 0);
+    }
+}
@@ -0,0 +1,296 @@
+// settings.rs - Preferencias del jugador guardadas entre partidas
+
+use std::fs;
+use std::path::Path;
+use crate::audio::{AudioSettings, AUDIO_SETTINGS_FILE};
+use crate::game_state::{MAX_LIVES, STARTING_LIVES};
+use crate::palette::PaletteKind;
+use crate::player::{key_name, KeyBindings, DEFAULT_GAMEPAD_DEADZONE};
+use crate::sprite_manager::DEFAULT_MAX_RENDERED_SPRITES;
+use crate::texture_manager::TextureFiltering;
+use crate::ui::{MinimapCorner, MinimapOptions, MinimapOrientation, MinimapZoom};
+
+pub const SETTINGS_FILE: &str = "assets/config/settings.toml";
+
+/// Resoluciones internas del mundo respecto a la ventana; el HUD va siempre completo
+pub const RENDER_SCALES: [f32; 3] = [0.5, 0.75, 1.0];
+
+/// Escala máxima del texto y los paneles del HUD (1 = fuente de 5x7 pixeles)
+pub const MAX_HUD_SCALE: u32 = 3;
+
+// Campo de visión elegible en grados y paso de las teclas `[` / `]`
+pub const MIN_FOV_DEGREES: f32 = 60.0;
+pub const MAX_FOV_DEGREES: f32 = 110.0;
+const FOV_STEP_DEGREES: f32 = 5.0;
+
+#[derive(Debug, Clone)]
+pub struct Settings {
+    pub palette: PaletteKind,
+    pub minimap: MinimapOptions,
+    // Se completaron los tres niveles en modo hardcore alguna vez
+    pub hardcore_cleared: bool,
+    // Límite de sprites dibujados por frame, para mapas con muchos objetos
+    pub max_rendered_sprites: usize,
+    // Inclinación mínima de los sticks del gamepad (0 a 0.9)
+    pub gamepad_deadzone: f32,
+    // Fracción de la ventana a la que se renderiza el mundo (ver RENDER_SCALES)
+    pub render_scale: f32,
+    // Campo de visión con el que empieza el jugador, en grados
+    pub fov_degrees: f32,
+    // Escala del HUD y las notificaciones, de 1 a MAX_HUD_SCALE
+    pub hud_scale: u32,
+    // Vidas al empezar en dificultad normal (fácil y difícil las ajustan)
+    pub starting_lives: i32,
+    // Muestreo de las texturas de pared; el vecino más cercano es el aspecto retro
+    pub texture_filtering: TextureFiltering,
+    // Volúmenes de música y efectos y silencio general
+    pub audio: AudioSettings,
+    // Teclas de movimiento (`key_forward = "W"`, ...)
+    pub keys: KeyBindings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            palette: PaletteKind::Default,
+            minimap: MinimapOptions::default(),
+            hardcore_cleared: false,
+            max_rendered_sprites: DEFAULT_MAX_RENDERED_SPRITES,
+            gamepad_deadzone: DEFAULT_GAMEPAD_DEADZONE,
+            render_scale: 1.0,
+            fov_degrees: MIN_FOV_DEGREES,
+            hud_scale: 1,
+            starting_lives: STARTING_LIVES,
+            texture_filtering: TextureFiltering::Nearest,
+            audio: AudioSettings::default(),
+            keys: KeyBindings::default(),
+        }
+    }
+}
+
+impl Settings {
+    /// Carga los settings; un archivo ausente o con errores usa los valores
+    /// por defecto. Los volúmenes de un audio.toml antiguo se respetan hasta
+    /// que settings.toml tenga los suyos
+    pub fn load() -> Self {
+        let text = fs::read_to_string(SETTINGS_FILE).unwrap_or_default();
+        let legacy_audio = fs::read_to_string(AUDIO_SETTINGS_FILE).unwrap_or_default();
+        Settings::parse_with_legacy_audio(&text, &legacy_audio)
+    }
+
+    pub fn save(&self) {
+        if let Some(dir) = Path::new(SETTINGS_FILE).parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+
+        if let Err(e) = fs::write(SETTINGS_FILE, self.to_toml()) {
+            eprintln!("No se pudieron guardar los settings: {}", e);
+        }
+    }
+
+    // Las líneas de audio.toml van antes: si settings.toml repite una clave, gana
+    fn parse_with_legacy_audio(text: &str, legacy_audio: &str) -> Self {
+        let mut settings = Settings::parse(text);
+        settings.audio = AudioSettings::parse(&format!("{}\n{}", legacy_audio, text));
+        settings
+    }
+
+    /// Lee líneas `clave = valor` (subconjunto de TOML); ignora lo que no reconoce
+    pub fn parse(text: &str) -> Self {
+        let mut settings = Settings { audio: AudioSettings::parse(text), ..Settings::default() };
+
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let value = value.trim().trim_matches('"');
+
+            match key.trim() {
+                "palette" => {
+                    settings.palette = PaletteKind::from_name(value).unwrap_or(settings.palette);
+                },
+                "minimap_corner" => {
+                    settings.minimap.corner = MinimapCorner::from_name(value).unwrap_or(settings.minimap.corner);
+                },
+                "minimap_orientation" => {
+                    settings.minimap.orientation = MinimapOrientation::from_name(value)
+                        .unwrap_or(settings.minimap.orientation);
+                },
+                "minimap_zoom" => {
+                    settings.minimap.zoom = MinimapZoom::from_name(value).unwrap_or(settings.minimap.zoom);
+                },
+                "max_rendered_sprites" => {
+                    settings.max_rendered_sprites = value.parse().unwrap_or(settings.max_rendered_sprites);
+                },
+                "gamepad_deadzone" => {
+                    settings.gamepad_deadzone = value
+                        .parse::<f32>()
+                        .map(|deadzone| deadzone.clamp(0.0, 0.9))
+                        .unwrap_or(settings.gamepad_deadzone);
+                },
+                "render_scale" => {
+                    settings.render_scale = value
+                        .parse::<f32>()
+                        .ok()
+                        .filter(|scale| RENDER_SCALES.contains(scale))
+                        .unwrap_or(settings.render_scale);
+                },
+                "fov" => {
+                    settings.fov_degrees = value
+                        .parse::<f32>()
+                        .ok()
+                        .filter(|degrees| degrees.is_finite())
+                        .map(|degrees| degrees.clamp(MIN_FOV_DEGREES, MAX_FOV_DEGREES))
+                        .unwrap_or(settings.fov_degrees);
+                },
+                "hud_scale" => {
+                    settings.hud_scale = value
+                        .parse::<u32>()
+                        .map(|scale| scale.clamp(1, MAX_HUD_SCALE))
+                        .unwrap_or(settings.hud_scale);
+                },
+                "starting_lives" => {
+                    settings.starting_lives = value
+                        .parse::<i32>()
+                        .map(|lives| lives.clamp(1, MAX_LIVES))
+                        .unwrap_or(settings.starting_lives);
+                },
+                "texture_filtering" => {
+                    settings.texture_filtering = TextureFiltering::from_name(value).unwrap_or(settings.texture_filtering);
+                },
+                "hardcore_cleared" => {
+                    settings.hardcore_cleared = value == "true";
+                },
+                key => {
+                    // Una tecla desconocida deja la que había
+                    if let Some(action) = key.strip_prefix("key_") {
+                        settings.keys.bind(action, value);
+                    }
+                },
+            }
+        }
+
+        settings
+    }
+
+    pub fn to_toml(&self) -> String {
+        let mut text = format!(
+            "palette = \"{}\"\nminimap_corner = \"{}\"\nminimap_orientation = \"{}\"\nminimap_zoom = \"{}\"\nhardcore_cleared = {}\nmax_rendered_sprites = {}\ngamepad_deadzone = {}\nrender_scale = {}\nfov = {}\nhud_scale = {}\nstarting_lives = {}\ntexture_filtering = \"{}\"\n",
+            self.palette.name(),
+            self.minimap.corner.name(),
+            self.minimap.orientation.name(),
+            self.minimap.zoom.name(),
+            self.hardcore_cleared,
+            self.max_rendered_sprites,
+            self.gamepad_deadzone,
+            self.render_scale,
+            self.fov_degrees,
+            self.hud_scale,
+            self.starting_lives,
+            self.texture_filtering.name(),
+        );
+        text.push_str(&self.audio.to_toml());
+        for (action, key) in self.keys.entries() {
+            text.push_str(&format!("key_{} = \"{}\"\n", action, key_name(key)));
+        }
+        text
+    }
+
+    /// Pasa a la siguiente escala de RENDER_SCALES, volviendo a la primera al final
+    pub fn next_render_scale(&mut self) {
+        let current = RENDER_SCALES.iter().position(|&scale| scale == self.render_scale).unwrap_or(0);
+        self.render_scale = RENDER_SCALES[(current + 1) % RENDER_SCALES.len()];
+    }
+
+    /// Agranda el HUD un paso, volviendo a 1 después de MAX_HUD_SCALE
+    pub fn next_hud_scale(&mut self) {
+        self.hud_scale = self.hud_scale % MAX_HUD_SCALE + 1;
+    }
+
+    /// Abre (`steps` positivo) o cierra el campo de visión de 5 en 5 grados
+    pub fn adjust_fov(&mut self, steps: i32) {
+        self.fov_degrees = (self.fov_degrees + steps as f32 * FOV_STEP_DEGREES)
+            .clamp(MIN_FOV_DEGREES, MAX_FOV_DEGREES);
+    }
+
+    pub fn fov_radians(&self) -> f32 {
+        self.fov_degrees.to_radians()
+    }
+
+    /// Tamaño del framebuffer del mundo para una ventana dada
+    pub fn render_size(&self, width: u32, height: u32) -> (u32, u32) {
+        let scaled = |size: u32| ((size as f32 * self.render_scale) as u32).max(1);
+        (scaled(width), scaled(height))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use raylib::prelude::KeyboardKey;
+
+    #[test]
+    fn fov_is_kept_in_range_and_survives_a_round_trip() {
+        let mut settings = Settings::default();
+        assert_eq!(settings.fov_degrees, 60.0);
+        settings.adjust_fov(3);
+        assert_eq!(Settings::parse(&settings.to_toml()).fov_degrees, 75.0);
+
+        settings.adjust_fov(100);
+        assert_eq!(settings.fov_degrees, MAX_FOV_DEGREES);
+        settings.adjust_fov(-100);
+        assert_eq!(settings.fov_degrees, MIN_FOV_DEGREES);
+
+        assert_eq!(Settings::parse("fov = 500").fov_degrees, MAX_FOV_DEGREES);
+        assert_eq!(Settings::parse("fov = ancho").fov_degrees, MIN_FOV_DEGREES);
+    }
+
+    #[test]
+    fn hud_scale_cycles_and_is_kept_in_range() {
+        let mut settings = Settings::default();
+        assert_eq!(settings.hud_scale, 1);
+        settings.next_hud_scale();
+        assert_eq!(Settings::parse(&settings.to_toml()).hud_scale, 2);
+        settings.next_hud_scale();
+        settings.next_hud_scale();
+        assert_eq!(settings.hud_scale, 1);
+
+        assert_eq!(Settings::parse("hud_scale = 9").hud_scale, MAX_HUD_SCALE);
+        assert_eq!(Settings::parse("hud_scale = 0").hud_scale, 1);
+    }
+
+    #[test]
+    fn texture_filtering_defaults_to_nearest_and_survives_a_round_trip() {
+        let mut settings = Settings::default();
+        assert_eq!(settings.texture_filtering, TextureFiltering::Nearest);
+        settings.texture_filtering = TextureFiltering::Bilinear;
+        assert_eq!(Settings::parse(&settings.to_toml()).texture_filtering, TextureFiltering::Bilinear);
+        assert_eq!(Settings::parse("texture_filtering = suave").texture_filtering, TextureFiltering::Nearest);
+    }
+
+    #[test]
+    fn audio_and_key_bindings_share_the_settings_file() {
+        let mut settings = Settings::default();
+        settings.audio.music_volume = 0.5;
+        settings.audio.muted = true;
+        assert!(settings.keys.bind("forward", "i"));
+        assert!(!settings.keys.bind("jump", "SPACE"));
+        assert!(!settings.keys.bind("sprint", "F13"));
+
+        let loaded = Settings::parse(&settings.to_toml());
+        assert_eq!(loaded.audio, settings.audio);
+        assert_eq!(loaded.keys, settings.keys);
+        assert_eq!(loaded.keys.forward, KeyboardKey::KEY_I);
+        assert_eq!(loaded.keys.sprint, KeyboardKey::KEY_LEFT_SHIFT);
+
+        // Un archivo viejo sin audio toma los volúmenes de audio.toml
+        let legacy = Settings::parse_with_legacy_audio("palette = \"default\"", "music_volume = 0.7\nsfx_volume = 0.4");
+        assert_eq!((legacy.audio.music_volume, legacy.audio.sfx_volume), (0.7, 0.4));
+        let current = Settings::parse_with_legacy_audio("music_volume = 0.1", "music_volume = 0.7");
+        assert_eq!(current.audio.music_volume, 0.1);
+    }
+}
@@ -6,6 +6,9 @@ use std::collections::HashMap;
 use crate::maze::Maze;
 use crate::player::Player;
 use crate::framebuffer::Framebuffer;
+use crate::translucency::{TranslucentKind, TranslucentQueue};
+use crate::caster::{floor_line, project_height, FrameContext, PointLight, MAX_POINT_LIGHTS};
+use crate::audio::{AmbientSound, AmbientSource};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum SpriteType {
@@ -14,9 +17,26 @@ pub enum SpriteType {
     ExitPortal,
     ExtraLife,
     TrapSpike,
+    // Trampa de veneno: quita una vida tras unos segundos
+    TrapPoison,
+    // Trampa que teletransporta al jugador a otra casilla
+    TrapTeleport,
+    // Entidad que persigue al jugador; su posición la lleva game_state
+    Enemy,
 }
 
 impl SpriteType {
+    pub const ALL: [SpriteType; 8] = [
+        SpriteType::KeyGold,
+        SpriteType::Checkpoint,
+        SpriteType::ExitPortal,
+        SpriteType::ExtraLife,
+        SpriteType::TrapSpike,
+        SpriteType::TrapPoison,
+        SpriteType::TrapTeleport,
+        SpriteType::Enemy,
+    ];
+
     pub fn from_char(c: char) -> Option<Self> {
         match c {
             'k' => Some(SpriteType::KeyGold),
@@ -24,17 +44,47 @@ impl SpriteType {
             'e' => Some(SpriteType::ExitPortal),
             'l' => Some(SpriteType::ExtraLife),
             't' => Some(SpriteType::TrapSpike),
+            'p' => Some(SpriteType::TrapPoison),
+            'x' => Some(SpriteType::TrapTeleport),
             _ => None,
         }
     }
 
     pub fn get_file_path(&self) -> &'static str {
         match self {
-            SpriteType::KeyGold => "assets/Sprites/key_gold.png",
-            SpriteType::Checkpoint => "assets/Sprites/checkpoint.png",
-            SpriteType::ExitPortal => "assets/Sprites/exit_portal.png",
-            SpriteType::ExtraLife => "assets/Sprites/extra_life.png",
-            SpriteType::TrapSpike => "assets/Sprites/trap_spike.png",
+            SpriteType::KeyGold => "assets/sprites/key_gold.png",
+            SpriteType::Checkpoint => "assets/sprites/checkpoint.png",
+            SpriteType::ExitPortal => "assets/sprites/exit_portal.png",
+            SpriteType::ExtraLife => "assets/sprites/extra_life.png",
+            SpriteType::TrapSpike => "assets/sprites/trap_spike.png",
+            SpriteType::TrapPoison => "assets/sprites/trap_poison.png",
+            SpriteType::TrapTeleport => "assets/sprites/trap_teleport.png",
+            SpriteType::Enemy => "assets/sprites/enemy.png",
+        }
+    }
+
+    /// Frames de la tira horizontal del PNG (1 = imagen fija). Al cambiar el
+    /// arte de un tipo por una tira basta con subir el número
+    pub fn frame_count(&self) -> usize {
+        match self {
+            // Bandera que ondea
+            SpriteType::Checkpoint => 6,
+            SpriteType::KeyGold
+            | SpriteType::ExitPortal
+            | SpriteType::ExtraLife
+            | SpriteType::TrapSpike
+            | SpriteType::TrapPoison
+            | SpriteType::TrapTeleport
+            | SpriteType::Enemy => 1,
+        }
+    }
+
+    /// Velocidad de la animación de la tira, en frames por segundo
+    pub fn animation_fps(&self) -> f32 {
+        match self {
+            SpriteType::ExitPortal => 12.0,
+            SpriteType::Checkpoint => 6.0,
+            _ => 8.0,
         }
     }
 
@@ -45,16 +95,57 @@ impl SpriteType {
             SpriteType::ExitPortal => Color::GREEN,
             SpriteType::ExtraLife => Color::PURPLE,
             SpriteType::TrapSpike => Color::RED,
+            SpriteType::TrapPoison => Color::LIME,
+            SpriteType::TrapTeleport => Color::SKYBLUE,
+            SpriteType::Enemy => Color::new(25, 22, 20, 255),
         }
     }
 
-    pub fn get_base_scale(&self) -> f32 {
+    /// Altura del sprite en unidades de mundo (una pared mide WALL_WORLD_HEIGHT)
+    pub fn world_height(&self) -> f32 {
         match self {
-            SpriteType::KeyGold => 0.8,
-            SpriteType::Checkpoint => 1.2,
-            SpriteType::ExitPortal => 1.5,
-            SpriteType::ExtraLife => 0.9,
-            SpriteType::TrapSpike => 1.0,
+            SpriteType::KeyGold => 40.0,
+            SpriteType::Checkpoint => 70.0,
+            SpriteType::ExitPortal => 90.0,
+            SpriteType::ExtraLife => 40.0,
+            SpriteType::TrapSpike => 35.0,
+            SpriteType::TrapPoison => 30.0,
+            SpriteType::TrapTeleport => 20.0,
+            SpriteType::Enemy => 95.0,
+        }
+    }
+
+    /// Radio de contacto para recoger el objeto; None si no se recoge
+    /// tocándolo (las trampas y la salida dependen de la casilla)
+    pub fn pickup_radius(&self) -> Option<f32> {
+        match self {
+            SpriteType::KeyGold => Some(35.0),
+            SpriteType::ExtraLife => Some(35.0),
+            SpriteType::Checkpoint => Some(40.0),
+            SpriteType::ExitPortal
+            | SpriteType::TrapSpike
+            | SpriteType::TrapPoison
+            | SpriteType::TrapTeleport
+            | SpriteType::Enemy => None,
+        }
+    }
+
+    /// Resplandor de color que el objeto proyecta sobre las paredes: color,
+    /// alcance en unidades de mundo e intensidad
+    pub fn light(&self) -> Option<(Color, f32, f32)> {
+        match self {
+            SpriteType::ExitPortal => Some((Color::new(60, 255, 120, 255), 320.0, 0.6)),
+            SpriteType::ExtraLife => Some((Color::new(200, 80, 255, 255), 180.0, 0.4)),
+            _ => None,
+        }
+    }
+
+    /// Zumbido en bucle que emite el objeto, si tiene uno
+    pub fn ambient_sound(&self) -> Option<AmbientSound> {
+        match self {
+            SpriteType::ExitPortal => Some(AmbientSound::PortalHum),
+            SpriteType::Checkpoint => Some(AmbientSound::LightBuzz),
+            _ => None,
         }
     }
 
@@ -63,8 +154,25 @@ impl SpriteType {
             SpriteType::KeyGold | 
             SpriteType::Checkpoint | 
             SpriteType::ExitPortal | 
-            SpriteType::ExtraLife
-        )
+            SpriteType::ExtraLife |
+            SpriteType::TrapTeleport |
+            SpriteType::Enemy
+        ) || self.frame_count() > 1
+    }
+
+    /// Trampas que se activan al pisar su casilla
+    pub fn is_trap(&self) -> bool {
+        matches!(self, SpriteType::TrapSpike | SpriteType::TrapPoison | SpriteType::TrapTeleport)
+    }
+
+    /// Se dibuja aunque se haya alcanzado el límite de sprites
+    pub fn is_never_culled(&self) -> bool {
+        matches!(self, SpriteType::ExitPortal)
+    }
+
+    /// Objetos que el jugador recoge o activa (se resaltan al apuntarles)
+    pub fn is_interactable(&self) -> bool {
+        !self.is_trap() && *self != SpriteType::Enemy
     }
 }
 
@@ -73,10 +181,13 @@ pub struct Sprite {
     pub sprite_type: SpriteType,
     pub world_x: f32,
     pub world_y: f32,
+    // Multiplicador de animación sobre la altura en mundo del tipo
     pub scale: f32,
     pub rotation: f32,
     pub active: bool,
     pub animation_time: f32,
+    // Trampa desarmada: se dibuja hundida en el suelo
+    pub retracted: bool,
 }
 
 impl Sprite {
@@ -85,10 +196,11 @@ impl Sprite {
             sprite_type,
             world_x,
             world_y,
-            scale: sprite_type.get_base_scale(),
+            scale: 1.0,
             rotation: 0.0,
             active: true,
             animation_time: 0.0,
+            retracted: false,
         }
     }
 
@@ -102,32 +214,48 @@ impl Sprite {
         match self.sprite_type {
             SpriteType::KeyGold => {
                 self.rotation = self.animation_time * 45.0;
-                self.scale = self.sprite_type.get_base_scale() + 
-                           (self.animation_time * 2.0).sin() * 0.1;
+                self.scale = 1.0 + (self.animation_time * 2.0).sin() * 0.12;
             },
             SpriteType::Checkpoint => {
-                self.scale = self.sprite_type.get_base_scale() + 
-                           (self.animation_time * 4.0).sin() * 0.2;
+                self.scale = 1.0 + (self.animation_time * 4.0).sin() * 0.16;
             },
             SpriteType::ExitPortal => {
                 self.rotation = self.animation_time * 30.0;
-                self.scale = self.sprite_type.get_base_scale() + 
-                           (self.animation_time * 3.0).sin() * 0.3;
+                self.scale = 1.0 + (self.animation_time * 3.0).sin() * 0.2;
             },
             SpriteType::ExtraLife => {
-                self.scale = self.sprite_type.get_base_scale() + 
-                           (self.animation_time * 6.0).sin() * 0.15;
+                self.scale = 1.0 + (self.animation_time * 6.0).sin() * 0.16;
+            },
+            SpriteType::TrapTeleport => {
+                self.rotation = self.animation_time * 90.0;
+            },
+            SpriteType::Enemy => {
+                // Respiración lenta e irregular
+                self.scale = 1.0 + (self.animation_time * 1.7).sin() * (self.animation_time * 0.6).cos() * 0.05;
             },
             _ => {}
         }
     }
 
+    /// Altura en pantalla con la misma proyección que las paredes
+    pub fn projected_height(&self, distance: f32, screen_height: u32) -> f32 {
+        project_height(self.sprite_type.world_height() * self.scale, distance, screen_height)
+    }
+
     pub fn distance_to_player(&self, player: &Player) -> f32 {
         let dx = self.world_x - player.pos.x;
         let dy = self.world_y - player.pos.y;
         (dx * dx + dy * dy).sqrt()
     }
 
+    /// Profundidad sobre la dirección de la mirada, comparable con el
+    /// z-buffer de paredes (que usa distancia perpendicular)
+    pub fn depth_from_player(&self, player: &Player) -> f32 {
+        let dx = self.world_x - player.pos.x;
+        let dy = self.world_y - player.pos.y;
+        (dx * player.a.cos() + dy * player.a.sin()).max(1.0)
+    }
+
     pub fn is_visible_to_player(&self, player: &Player) -> bool {
         let dx = self.world_x - player.pos.x;
         let dy = self.world_y - player.pos.y;
@@ -152,10 +280,13 @@ pub struct RealSpriteInfo {
     pub width: i32,
     pub height: i32,
     pub fallback_color: Color,
+    // Frames de animación colocados uno al lado del otro; 1 = sprite fijo
+    pub frame_count: i32,
 }
 
 impl RealSpriteInfo {
-    pub fn from_png(path: &str, fallback_color: Color) -> Result<Self, String> {
+    /// Carga una tira horizontal de `frame_count` frames del mismo ancho
+    pub fn from_png_strip(path: &str, frame_count: usize, fallback_color: Color) -> Result<Self, String> {
         match image::open(path) {
             Ok(img) => {
                 let rgba_img = img.to_rgba8();
@@ -166,14 +297,28 @@ impl RealSpriteInfo {
                     width: width as i32,
                     height: height as i32,
                     fallback_color,
+                    frame_count: (frame_count as i32).clamp(1, width.max(1) as i32),
                 })
             },
             Err(e) => Err(format!("Error loading sprite PNG {}: {}", path, e))
         }
     }
 
-    pub fn get_pixel_color(&self, tx: f32, ty: f32) -> Color {
-        let x = (tx.clamp(0.0, 1.0) * (self.width - 1) as f32) as usize;
+    /// Ancho de un solo frame de la tira
+    pub fn frame_width(&self) -> i32 {
+        self.width / self.frame_count.max(1)
+    }
+
+    /// Color en el frame que toca a `animation_time` segundos con `fps` frames por segundo
+    pub fn get_animated_pixel_color(&self, animation_time: f32, fps: f32, tx: f32, ty: f32) -> Color {
+        let frame = ((animation_time.max(0.0) * fps) as i32).rem_euclid(self.frame_count.max(1));
+        self.get_frame_pixel_color(frame, tx, ty)
+    }
+
+    // tx recorre solo el ancho de un frame dentro de la tira
+    fn get_frame_pixel_color(&self, frame: i32, tx: f32, ty: f32) -> Color {
+        let frame_width = self.frame_width();
+        let x = (frame * frame_width) as usize + (tx.clamp(0.0, 1.0) * (frame_width - 1).max(0) as f32) as usize;
         let y = (ty.clamp(0.0, 1.0) * (self.height - 1) as f32) as usize;
         
         let index = (y * self.width as usize + x) * 4;
@@ -195,36 +340,95 @@ pub struct SpriteManager {
     real_sprites: HashMap<SpriteType, RealSpriteInfo>,
     sprites: Vec<Sprite>,
     z_buffer: Vec<f32>,
+    // Requisitos pendientes de la salida; con Some el portal se dibuja con
+    // candado y con None brilla abierto
+    exit_lock: Option<String>,
+    // Cuántos sprites se dibujan como mucho por frame (los más cercanos)
+    max_rendered_sprites: usize,
 }
 
+pub const DEFAULT_MAX_RENDERED_SPRITES: usize = 64;
+
+// Distancia a la que se muestra el texto de requisitos sobre el portal
+const EXIT_LOCK_LABEL_DISTANCE: f32 = 350.0;
+// Brillo extra del portal abierto sobre su PNG
+const OPEN_EXIT_GLOW: f32 = 1.5;
+
 impl SpriteManager {
     pub fn new(_rl: &mut RaylibHandle, _thread: &RaylibThread) -> Self {
-        let mut sprite_manager = SpriteManager {
+        // Los PNG de sprites llegan luego desde el AssetLoader
+        Self::empty()
+    }
+
+    fn empty() -> Self {
+        SpriteManager {
             real_sprites: HashMap::new(),
             sprites: Vec::new(),
             z_buffer: Vec::new(),
-        };
-        
-        sprite_manager.load_all_sprites();
-        sprite_manager
-    }
-
-    fn load_all_sprites(&mut self) {
-        let sprite_types = [
-            SpriteType::KeyGold,
-            SpriteType::Checkpoint,
-            SpriteType::ExitPortal,
-            SpriteType::ExtraLife,
-            SpriteType::TrapSpike,
-        ];
-
-        for sprite_type in sprite_types.iter() {
-            let path = sprite_type.get_file_path();
-            let fallback_color = sprite_type.get_fallback_color();
-            
-            if let Ok(sprite_info) = RealSpriteInfo::from_png(path, fallback_color) {
-                self.real_sprites.insert(*sprite_type, sprite_info);
-            }
+            exit_lock: None,
+            max_rendered_sprites: DEFAULT_MAX_RENDERED_SPRITES,
+        }
+    }
+
+    /// Registra un sprite decodificado en segundo plano
+    pub fn insert_real_sprite(&mut self, sprite_type: SpriteType, sprite_info: RealSpriteInfo) {
+        self.real_sprites.insert(sprite_type, sprite_info);
+    }
+
+    /// Tipos sin PNG cargado, que se dibujan con su forma procedural
+    pub fn missing_sprites(&self) -> Vec<SpriteType> {
+        SpriteType::ALL
+            .iter()
+            .copied()
+            .filter(|sprite_type| !self.real_sprites.contains_key(sprite_type))
+            .collect()
+    }
+
+    pub fn set_max_rendered_sprites(&mut self, max_rendered_sprites: usize) {
+        self.max_rendered_sprites = max_rendered_sprites;
+    }
+
+    /// Marca el portal de salida como bloqueado con el texto de lo que falta
+    pub fn set_exit_lock(&mut self, label: Option<String>) {
+        self.exit_lock = label;
+    }
+
+    /// Marca como retraídas las trampas de las casillas dadas y el resto como armadas
+    pub fn set_retracted_traps(&mut self, cells: &[(usize, usize)], block_size: usize) {
+        for sprite in self.sprites.iter_mut().filter(|sprite| sprite.sprite_type.is_trap()) {
+            let cell = (
+                (sprite.world_x / block_size as f32) as usize,
+                (sprite.world_y / block_size as f32) as usize,
+            );
+            sprite.retracted = cells.contains(&cell);
+        }
+    }
+
+    /// Desactiva los sprites de las casillas que el juego consumió (objetos
+    /// recogidos, trampas gastadas) para que desaparezcan en el mismo frame
+    pub fn remove_sprites_at_cells(&mut self, cells: impl IntoIterator<Item = (usize, usize)>, block_size: usize) {
+        let half_block = block_size as f32 * 0.5;
+        for (x, y) in cells {
+            let world_x = (x * block_size) as f32 + half_block;
+            let world_y = (y * block_size) as f32 + half_block;
+            self.remove_sprite_at(world_x, world_y, half_block);
+        }
+    }
+
+    /// Coloca un sprite de entidad en cada posición; conserva los existentes
+    /// (y su animación) mientras no cambie la cantidad
+    pub fn set_enemy_positions(&mut self, positions: &[Vector2]) {
+        let count = self.sprites.iter().filter(|sprite| sprite.sprite_type == SpriteType::Enemy).count();
+        if count != positions.len() {
+            self.sprites.retain(|sprite| sprite.sprite_type != SpriteType::Enemy);
+            self.sprites.extend(positions.iter().map(|pos| Sprite::new(SpriteType::Enemy, pos.x, pos.y)));
+            return;
+        }
+
+        let enemies = self.sprites.iter_mut().filter(|sprite| sprite.sprite_type == SpriteType::Enemy);
+        for (sprite, pos) in enemies.zip(positions) {
+            sprite.world_x = pos.x;
+            sprite.world_y = pos.y;
         }
     }
 
@@ -244,6 +448,40 @@ impl SpriteManager {
         }
     }
 
+    /// Fuentes de sonido ambiental de los sprites activos
+    pub fn ambient_sources(&self) -> Vec<AmbientSource> {
+        self.sprites
+            .iter()
+            .filter(|sprite| sprite.active)
+            .filter_map(|sprite| {
+                sprite.sprite_type.ambient_sound().map(|sound| AmbientSource {
+                    sound,
+                    x: sprite.world_x,
+                    y: sprite.world_y,
+                })
+            })
+            .collect()
+    }
+
+    /// Luces de los objetos activos, las MAX_POINT_LIGHTS más cercanas al
+    /// jugador. El portal abierto brilla el doble
+    pub fn point_lights(&self, player_pos: Vector2) -> Vec<PointLight> {
+        let mut lights: Vec<PointLight> = self.sprites
+            .iter()
+            .filter(|sprite| sprite.active)
+            .filter_map(|sprite| {
+                let (color, radius, intensity) = sprite.sprite_type.light()?;
+                let glow = if self.is_open_exit(sprite) { 2.0 } else { 1.0 };
+                Some(PointLight { x: sprite.world_x, y: sprite.world_y, color, radius, intensity: intensity * glow })
+            })
+            .collect();
+
+        let distance = |light: &PointLight| Vector2::new(light.x, light.y).distance_to(player_pos);
+        lights.sort_by(|a, b| distance(a).total_cmp(&distance(b)));
+        lights.truncate(MAX_POINT_LIGHTS);
+        lights
+    }
+
     pub fn update_sprites(&mut self, delta_time: f32) {
         for sprite in &mut self.sprites {
             if sprite.active {
@@ -252,11 +490,14 @@ impl SpriteManager {
         }
     }
 
-    pub fn render_sprites(
+    /// Copia las distancias de las paredes opacas al z-buffer y encola los
+    /// sprites visibles para componerlos junto al resto de elementos translúcidos
+    pub fn queue_visible_sprites(
         &mut self,
-        framebuffer: &mut Framebuffer,
+        framebuffer: &Framebuffer,
         player: &Player,
         wall_distances: &[f32],
+        queue: &mut TranslucentQueue,
     ) {
         if self.sprites.is_empty() {
             return;
@@ -272,19 +513,56 @@ impl SpriteManager {
             }
         }
 
-        let mut sprite_distances: Vec<(usize, f32)> = self.sprites
+        for (i, distance) in self.sprites_to_render(player) {
+            queue.push(distance, TranslucentKind::Sprite(i));
+        }
+    }
+
+    // Sprites visibles con su distancia, recortados a los más cercanos hasta
+    // el límite; los que nunca se descartan no cuentan para el límite
+    fn sprites_to_render(&self, player: &Player) -> Vec<(usize, f32)> {
+        let mut visible: Vec<(usize, f32)> = self.sprites
             .iter()
             .enumerate()
             .filter(|(_, sprite)| sprite.active && sprite.is_visible_to_player(player))
-            .map(|(i, sprite)| (i, sprite.distance_to_player(player)))
+            .map(|(i, sprite)| (i, sprite.depth_from_player(player)))
             .collect();
-        
-        sprite_distances.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+        visible.sort_by(|a, b| a.1.total_cmp(&b.1));
 
-        for (sprite_idx, distance) in sprite_distances {
-            if let Some(sprite) = self.sprites.get(sprite_idx) {
-                self.render_single_sprite(framebuffer, player, sprite, distance);
+        let mut budget = self.max_rendered_sprites;
+        visible.retain(|&(i, _)| {
+            if self.sprites[i].sprite_type.is_never_culled() {
+                true
+            } else if budget > 0 {
+                budget -= 1;
+                true
+            } else {
+                false
             }
+        });
+        visible
+    }
+
+    // Primera y última columna (exclusiva) de [start_x, end_x) en las que el
+    // sprite queda delante de la pared; None si ninguna
+    fn unoccluded_columns(&self, start_x: i32, end_x: i32, distance: f32) -> Option<(i32, i32)> {
+        let in_front = |x: &i32| self.z_buffer.get(*x as usize).is_some_and(|&wall| distance < wall);
+        let first = (start_x..end_x).find(in_front)?;
+        let last = (start_x..end_x).rev().find(in_front)?;
+        Some((first, last + 1))
+    }
+
+    /// Dibuja un sprite previamente encolado con `queue_visible_sprites`
+    pub fn render_queued_sprite(
+        &self,
+        framebuffer: &mut Framebuffer,
+        player: &Player,
+        sprite_index: usize,
+        distance: f32,
+        frame: &FrameContext,
+    ) {
+        if let Some(sprite) = self.sprites.get(sprite_index) {
+            self.render_single_sprite(framebuffer, player, sprite, distance, frame);
         }
     }
 
@@ -294,6 +572,7 @@ impl SpriteManager {
         player: &Player,
         sprite: &Sprite,
         distance: f32,
+        frame: &FrameContext,
     ) {
         let dx = sprite.world_x - player.pos.x;
         let dy = sprite.world_y - player.pos.y;
@@ -312,60 +591,219 @@ impl SpriteManager {
         let screen_x = framebuffer.width as f32 * 0.5 + 
                       (relative_angle / half_fov) * (framebuffer.width as f32 * 0.5);
         
-        let distance_to_projection_plane = 100.0;
-        let sprite_size = (framebuffer.height as f32 * sprite.scale / distance) * distance_to_projection_plane;
+        let sprite_size = sprite.projected_height(distance, framebuffer.height);
         
         if sprite_size >= 1.0 {
+            // Base del sprite en el suelo: misma fila que la base de las paredes
+            // a esa distancia, con el horizonte desplazado por la mirada vertical
+            let floor_y = floor_line(player.horizon(framebuffer.height), distance, framebuffer.height);
+
             self.render_sprite_column(
                 framebuffer,
                 sprite,
-                screen_x as i32,
+                (screen_x as i32, floor_y as i32),
                 sprite_size as u32,
                 distance,
+                frame,
             );
         }
     }
 
+    // `(center_x, floor_y)`: columna central del sprite y fila del suelo donde se apoya
     fn render_sprite_column(
         &self,
         framebuffer: &mut Framebuffer,
         sprite: &Sprite,
-        center_x: i32,
+        (center_x, floor_y): (i32, i32),
         size: u32,
         distance: f32,
+        frame: &FrameContext,
     ) {
-        let half_size = size as i32 / 2;
-        let start_x = (center_x - half_size).max(0) as u32;
-        let end_x = (center_x + half_size).min(framebuffer.width as i32) as u32;
+        // Trabajar en i32 con los bordes sin recortar: evita underflow en u32 cuando
+        // el sprite sale de pantalla y no deforma las coordenadas de textura
+        let height = size.min(1 << 20) as i32;
+        if height == 0 {
+            return;
+        }
+        let width = self.projected_width(sprite.sprite_type, height);
         
-        let center_y = framebuffer.height as i32 / 2;
-        let start_y = (center_y - half_size).max(0) as u32;
-        let end_y = (center_y + half_size).min(framebuffer.height as i32) as u32;
+        // Una trampa retraída asoma solo la punta: se baja y se recorta en el suelo
+        let sunk = if sprite.retracted { height.saturating_mul(7) / 10 } else { 0 };
+        
+        let left = center_x.saturating_sub(width / 2);
+        let top = floor_y.saturating_sub(height).saturating_add(sunk);
+        
+        let start_x = left.max(0);
+        let end_x = left.saturating_add(width).min(framebuffer.width as i32);
+        let start_y = top.max(0);
+        let end_y = top.saturating_add(height).min(floor_y).min(framebuffer.height as i32);
+        
+        if start_x >= end_x || start_y >= end_y {
+            return;
+        }
+
+        // Tapado entero por paredes: ni se recorren sus pixeles. Si asoma, se
+        // sigue comprobando el z-buffer por pixel para columnas intermedias
+        let Some((start_x, end_x)) = self.unoccluded_columns(start_x, end_x, distance) else {
+            return;
+        };
         
         let distance_factor = (distance / 400.0).min(0.7).max(0.0);
-        let brightness = 1.0 - distance_factor * 0.3;
+        let brightness = (1.0 - distance_factor * 0.3) * if sprite.retracted { 0.6 } else { 1.0 };
+        let is_locked_exit = sprite.sprite_type == SpriteType::ExitPortal && self.exit_lock.is_some();
         
         for y in start_y..end_y {
             for x in start_x..end_x {
                 if (x as usize) < self.z_buffer.len() && distance < self.z_buffer[x as usize] {
-                    let tx = (x - start_x) as f32 / (end_x - start_x) as f32;
-                    let ty = (y - start_y) as f32 / (end_y - start_y) as f32;
+                    let tx = (x - left) as f32 / width as f32;
+                    let ty = (y - top) as f32 / height as f32;
                     
-                    let color = self.get_sprite_color(sprite, tx, ty, brightness);
+                    let padlock = if is_locked_exit { padlock_color(tx, ty, brightness) } else { None };
+                    let color = padlock.unwrap_or_else(|| self.get_sprite_color(sprite, tx, ty, brightness));
+                    let color = frame.fog.apply(color, distance);
                     
-                    if color.a > 0 {
+                    if color.a == 255 {
                         framebuffer.set_current_color(color);
-                        framebuffer.set_pixel(x, y);
+                        framebuffer.set_pixel(x as u32, y as u32);
+                    } else if color.a > 0 {
+                        framebuffer.set_current_color(color);
+                        framebuffer.blend_pixel(x as u32, y as u32);
+                    }
+                }
+            }
+        }
+
+        // Resaltar el objeto que está bajo la mira. Los sprites se apoyan en el
+        // suelo, por debajo del horizonte, así que solo cuenta la columna central
+        let screen_center_x = framebuffer.width as i32 / 2;
+        let is_targeted = sprite.sprite_type.is_interactable()
+            && (left..left.saturating_add(width)).contains(&screen_center_x)
+            && self.z_buffer.get(screen_center_x as usize).is_some_and(|&wall| distance < wall);
+
+        if is_targeted {
+            self.render_target_outline(framebuffer, sprite, (left, top, width, height), distance);
+        }
+
+        if is_locked_exit && distance < EXIT_LOCK_LABEL_DISTANCE {
+            self.render_exit_lock_label(framebuffer, center_x, top, distance);
+        }
+    }
+
+    // Requisitos pendientes escritos sobre el portal, solo si se ve su centro
+    fn render_exit_lock_label(&self, framebuffer: &mut Framebuffer, center_x: i32, top: i32, distance: f32) {
+        let Some(label) = self.exit_lock.as_deref() else {
+            return;
+        };
+
+        let portal_visible = center_x >= 0 &&
+            self.z_buffer.get(center_x as usize).is_some_and(|&wall| distance < wall);
+        if !portal_visible {
+            return;
+        }
+
+        let text_width = label.len() as i32 * 6;
+        let max_x = (framebuffer.width as i32 - text_width).max(0);
+        let text_x = (center_x - text_width / 2).clamp(0, max_x);
+        let text_y = (top - 12).clamp(0, framebuffer.height as i32 - 7);
+
+        framebuffer.set_current_color(Color::new(0, 0, 0, 160));
+        for y in (text_y - 2).max(0)..(text_y + 9).min(framebuffer.height as i32) {
+            for x in (text_x - 3).max(0)..(text_x + text_width + 2).min(framebuffer.width as i32) {
+                framebuffer.blend_pixel(x as u32, y as u32);
+            }
+        }
+
+        framebuffer.set_current_color(Color::new(255, 80, 80, 255));
+        crate::ui::render_text(framebuffer, label, text_x as u32, text_y as u32);
+    }
+
+    // Marco pulsante alrededor del sprite apuntado; el z-buffer lo recorta tras paredes
+    fn render_target_outline(
+        &self,
+        framebuffer: &mut Framebuffer,
+        sprite: &Sprite,
+        (left, top, width, height): (i32, i32, i32, i32),
+        distance: f32,
+    ) {
+        const OUTLINE_WIDTH: i32 = 2;
+
+        let pulse = (sprite.animation_time * 6.0).sin() * 0.5 + 0.5;
+        let outline_color = Color::new(255, 255, (120.0 + pulse * 135.0) as u8, 255);
+        framebuffer.set_current_color(outline_color);
+
+        let right = left.saturating_add(width);
+        let bottom = top.saturating_add(height);
+
+        // Solo las cuatro franjas del borde, recortadas a la pantalla
+        let mut fill = |x0: i32, x1: i32, y0: i32, y1: i32| {
+            let x_range = x0.max(0)..x1.min(framebuffer.width as i32);
+            for y in y0.max(0)..y1.min(framebuffer.height as i32) {
+                for x in x_range.clone() {
+                    if (x as usize) < self.z_buffer.len() && distance < self.z_buffer[x as usize] {
+                        framebuffer.set_pixel(x as u32, y as u32);
                     }
                 }
             }
+        };
+
+        let inner_top = top.saturating_add(OUTLINE_WIDTH);
+        let inner_bottom = bottom.saturating_sub(OUTLINE_WIDTH);
+        fill(left, right, top, inner_top);
+        fill(left, right, inner_bottom.max(inner_top), bottom);
+        fill(left, left.saturating_add(OUTLINE_WIDTH), inner_top, inner_bottom);
+        fill(right.saturating_sub(OUTLINE_WIDTH).max(left), right, inner_top, inner_bottom);
+    }
+
+    // Ancho en pantalla para una altura proyectada: los PNG conservan su
+    // proporción y los sprites procedurales siguen siendo cuadrados
+    fn projected_width(&self, sprite_type: SpriteType, height: i32) -> i32 {
+        match self.real_sprites.get(&sprite_type) {
+            Some(real) if real.frame_width() > 0 && real.height > 0 => {
+                let width = height as i64 * real.frame_width() as i64 / real.height as i64;
+                width.clamp(1, 1 << 22) as i32
+            },
+            _ => height,
         }
     }
 
+    /// Dibuja un tipo de sprite como icono plano (para el HUD)
+    pub fn render_icon(&self, framebuffer: &mut Framebuffer, sprite_type: SpriteType, x: u32, y: u32, size: u32) {
+        let icon = Sprite::new(sprite_type, 0.0, 0.0);
+
+        for dy in 0..size {
+            for dx in 0..size {
+                let tx = dx as f32 / size as f32;
+                let ty = dy as f32 / size as f32;
+                let color = self.get_sprite_color(&icon, tx, ty, 1.0);
+
+                if color.a == 255 {
+                    framebuffer.set_current_color(color);
+                    framebuffer.set_pixel(x + dx, y + dy);
+                } else if color.a > 0 {
+                    framebuffer.set_current_color(color);
+                    framebuffer.blend_pixel(x + dx, y + dy);
+                }
+            }
+        }
+    }
+
+    // El portal está abierto cuando ya no le queda ningún requisito pendiente
+    fn is_open_exit(&self, sprite: &Sprite) -> bool {
+        sprite.sprite_type == SpriteType::ExitPortal && self.exit_lock.is_none()
+    }
+
     fn get_sprite_color(&self, sprite: &Sprite, tx: f32, ty: f32, brightness: f32) -> Color {
+        // El portal abierto late más brillante para que se note que ya se puede usar
+        let brightness = if self.is_open_exit(sprite) {
+            brightness * (OPEN_EXIT_GLOW + (sprite.animation_time * 6.0).sin() * 0.25)
+        } else {
+            brightness
+        };
+
         // Intentar usar sprite PNG real
         if let Some(real_sprite) = self.real_sprites.get(&sprite.sprite_type) {
-            let color = real_sprite.get_pixel_color(tx, ty);
+            let fps = sprite.sprite_type.animation_fps();
+            let color = real_sprite.get_animated_pixel_color(sprite.animation_time, fps, tx, ty);
             if color.a > 0 {
                 return Color::new(
                     ((color.r as f32) * brightness).min(255.0) as u8,
@@ -394,11 +832,21 @@ impl SpriteManager {
             },
             SpriteType::ExitPortal => {
                 let center_dist = ((tx - 0.5).powi(2) + (ty - 0.5).powi(2)).sqrt() * 2.0;
-                if center_dist <= 1.0 {
+                if center_dist > 1.0 {
+                    (0.0, 0)
+                } else if self.is_open_exit(sprite) {
+                    // Abierto: remolino rápido hacia dentro con el centro blanco
+                    let wave = (center_dist * 10.0 - sprite.animation_time * 8.0).sin() * 0.5 + 0.5;
+                    let glow = (1.0 - center_dist) * 255.0;
+                    return Color::new(
+                        ((glow + 80.0 * wave) * brightness).min(255.0) as u8,
+                        (255.0 * brightness).min(255.0) as u8,
+                        ((glow + 200.0 * wave) * brightness).min(255.0) as u8,
+                        (255.0 * (1.0 - center_dist * center_dist)) as u8,
+                    );
+                } else {
                     let wave = (center_dist * 6.0 + sprite.animation_time * 3.0).sin() * 0.5 + 0.5;
                     (wave, (255.0 * (1.0 - center_dist)) as u8)
-                } else {
-                    (0.0, 0)
                 }
             },
             SpriteType::ExtraLife => {
@@ -409,6 +857,34 @@ impl SpriteManager {
                 let spike_pattern = ((tx * 5.0) as i32 % 2 == 0) && (ty > 0.3);
                 if spike_pattern { (1.0, 255) } else { (0.0, 0) }
             },
+            SpriteType::TrapPoison => {
+                // Charco con burbujas más claras
+                let puddle = ((tx - 0.5) / 0.5).powi(2) + ((ty - 0.8) / 0.2).powi(2) <= 1.0;
+                let bubble = ((tx * 7.0).sin() * (ty * 9.0 + sprite.animation_time * 2.0).cos()).abs() > 0.8;
+                if puddle { (if bubble { 1.0 } else { 0.6 }, 255) } else { (0.0, 0) }
+            },
+            SpriteType::TrapTeleport => {
+                // Anillo plano que gira en el suelo
+                let dx = tx - 0.5;
+                let dy = (ty - 0.75) * 2.0;
+                let ring = (dx * dx + dy * dy).sqrt();
+                if (0.3..=0.45).contains(&ring) {
+                    let spin = (dy.atan2(dx) * 3.0 + sprite.animation_time * 5.0).sin() * 0.3 + 0.7;
+                    (spin, 255)
+                } else {
+                    (0.0, 0)
+                }
+            },
+            SpriteType::Enemy => {
+                // Silueta alta y oscura con ojos y sonrisa que brillan
+                let body = ((tx - 0.5) / 0.3).powi(2) + ((ty - 0.55) / 0.45).powi(2) <= 1.0;
+                let eye = ((tx - 0.5).abs() - 0.1).powi(2) + (ty - 0.3).powi(2) < 0.0015;
+                let smile = (0.35..0.65).contains(&tx) && (ty - 0.42 - (tx - 0.5).powi(2) * 2.0).abs() < 0.015;
+                if eye || smile {
+                    return Color::new(255, 250, 235, 255);
+                }
+                if body { (1.0, 255) } else { (0.0, 0) }
+            },
         };
 
         Color::new(
@@ -451,4 +927,367 @@ impl Drop for SpriteManager {
         self.real_sprites.clear();
         self.sprites.clear();
     }
-}
\ No newline at end of file
+}
+
+// Candado rojo centrado sobre el portal: arco arriba, cuerpo abajo y el ojo
+// de la cerradura hueco para que se vea el portal a través
+fn padlock_color(tx: f32, ty: f32, brightness: f32) -> Option<Color> {
+    let dx = tx - 0.5;
+    let in_body = dx.abs() <= 0.16 && (0.45..=0.72).contains(&ty);
+    let keyhole = (dx * dx + (ty - 0.56).powi(2)).sqrt() <= 0.035 ||
+        (dx.abs() <= 0.015 && (0.56..=0.64).contains(&ty));
+
+    let ring = (dx * dx + (ty - 0.45).powi(2)).sqrt();
+    let in_shackle = ty < 0.45 && (0.07..=0.12).contains(&ring);
+
+    if (in_body && !keyhole) || in_shackle {
+        let shade = if in_shackle { 0.8 } else { 1.0 } * brightness;
+        Some(Color::new((220.0 * shade) as u8, (30.0 * shade) as u8, (30.0 * shade) as u8, 255))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::translucency::QueuedWallColumn;
+    use crate::caster::WALL_WORLD_HEIGHT;
+
+    fn manager_with_open_view(width: u32) -> SpriteManager {
+        let mut manager = SpriteManager::empty();
+        manager.z_buffer = vec![f32::INFINITY; width as usize];
+        manager
+    }
+
+    /// Pixeles que dejaron de estar en negro, fila a fila
+    fn lit_pixels(framebuffer: &mut Framebuffer) -> Vec<(u32, u32)> {
+        let mut cells = Vec::new();
+        for y in 0..framebuffer.height {
+            for x in 0..framebuffer.width {
+                if framebuffer.get_pixel(x, y) != Some(Color::BLACK) {
+                    cells.push((x, y));
+                }
+            }
+        }
+        cells
+    }
+
+    #[test]
+    fn one_pixel_sprite_draws_a_single_pixel() {
+        let mut framebuffer = Framebuffer::new(64, 48);
+        let manager = manager_with_open_view(64);
+        let sprite = Sprite::new(SpriteType::KeyGold, 0.0, 0.0);
+
+        manager.render_sprite_column(&mut framebuffer, &sprite, (20, 40), 1, 50.0, &FrameContext::default());
+        assert_eq!(lit_pixels(&mut framebuffer), vec![(20, 39)]);
+
+        // Con altura 0 no se dibuja nada más
+        manager.render_sprite_column(&mut framebuffer, &sprite, (20, 40), 0, 50.0, &FrameContext::default());
+        assert_eq!(lit_pixels(&mut framebuffer), vec![(20, 39)]);
+    }
+
+    #[test]
+    fn targeted_sprite_gets_a_two_pixel_ring_clipped_by_walls() {
+        let mut manager = manager_with_open_view(64);
+        let key = Sprite::new(SpriteType::KeyGold, 0.0, 0.0);
+        let outline = Color::new(255, 255, 187, 255);
+        let ring = |framebuffer: &mut Framebuffer| -> Vec<(u32, u32)> {
+            lit_pixels(framebuffer).into_iter().filter(|&(x, y)| framebuffer.get_pixel(x, y) == Some(outline)).collect()
+        };
+
+        // Llave de 20x20 sobre la columna central: marco de 2px y el centro libre
+        let mut framebuffer = Framebuffer::new(64, 48);
+        manager.render_sprite_column(&mut framebuffer, &key, (32, 40), 20, 50.0, &FrameContext::default());
+        let cells = ring(&mut framebuffer);
+        assert_eq!(cells.len(), 20 * 20 - 16 * 16);
+        assert!(cells.iter().all(|&(x, y)| !((24..40).contains(&x) && (22..38).contains(&y))));
+
+        // Una pared más cercana tapa la mitad izquierda del marco
+        manager.z_buffer[..32].fill(30.0);
+        let mut framebuffer = Framebuffer::new(64, 48);
+        manager.render_sprite_column(&mut framebuffer, &key, (32, 40), 20, 50.0, &FrameContext::default());
+        let cells = ring(&mut framebuffer);
+        assert!(!cells.is_empty() && cells.iter().all(|&(x, _)| x >= 32));
+    }
+
+    #[test]
+    fn sprites_and_glass_columns_come_out_back_to_front() {
+        let mut manager = manager_with_open_view(64);
+        manager.sprites.push(Sprite::new(SpriteType::KeyGold, 100.0, 0.0));
+        manager.sprites.push(Sprite::new(SpriteType::KeyGold, 400.0, 0.0));
+        let player = Player::new_with_pos(0.0, 0.0, 0.0);
+        let framebuffer = Framebuffer::new(64, 48);
+
+        // Un vidrio entre las dos llaves, encolado durante el pase de paredes
+        let mut queue = TranslucentQueue::new();
+        queue.push(250.0, TranslucentKind::WallColumn(QueuedWallColumn {
+            column: 32,
+            top: 0.0,
+            height: 48.0,
+            impact: 'v',
+            region: None,
+            texture_x: 0.5,
+            brightness: 1.0,
+            opaque: false,
+        }));
+        manager.queue_visible_sprites(&framebuffer, &player, &[500.0; 64], &mut queue);
+        assert_eq!(manager.z_buffer, vec![500.0; 64]);
+
+        let order: Vec<String> = queue.drain_back_to_front()
+            .iter()
+            .map(|item| match item.kind {
+                TranslucentKind::Sprite(i) => format!("llave {}", manager.sprites[i].world_x),
+                TranslucentKind::WallColumn(_) => "vidrio".to_string(),
+            })
+            .collect();
+        assert_eq!(order, ["llave 400", "vidrio", "llave 100"]);
+        assert!(queue.drain_back_to_front().is_empty());
+    }
+
+    #[test]
+    fn only_the_nearest_sprites_are_rendered_but_never_the_exit() {
+        let mut manager = SpriteManager::empty();
+        manager.set_max_rendered_sprites(2);
+        manager.sprites.push(Sprite::new(SpriteType::ExitPortal, 900.0, 0.0));
+        for x in [500.0, 100.0, 300.0, 200.0, 400.0] {
+            manager.sprites.push(Sprite::new(SpriteType::KeyGold, x, 0.0));
+        }
+        let player = Player::new_with_pos(0.0, 0.0, 0.0);
+
+        let rendered: Vec<f32> = manager.sprites_to_render(&player)
+            .iter()
+            .map(|&(i, _)| manager.sprites[i].world_x)
+            .collect();
+        assert_eq!(rendered, vec![100.0, 200.0, 900.0]);
+
+        manager.set_max_rendered_sprites(0);
+        assert_eq!(manager.sprites_to_render(&player).len(), 1);
+    }
+
+    #[test]
+    fn sprite_height_matches_wall_projection_at_same_distance() {
+        let portal = Sprite::new(SpriteType::ExitPortal, 0.0, 0.0);
+
+        // A la distancia del plano de proyección una pared ocupa media pantalla
+        // y el portal, de 90 unidades, el 90% de eso
+        for (distance, wall, sprite) in [(25.0, 1800.0, 1620.0), (100.0, 450.0, 405.0), (900.0, 50.0, 45.0)] {
+            assert!((project_height(WALL_WORLD_HEIGHT, distance, 900) - wall).abs() < 1e-3, "distancia {}", distance);
+            assert!((portal.projected_height(distance, 900) - sprite).abs() < 1e-3, "distancia {}", distance);
+        }
+    }
+
+    #[test]
+    fn sprites_stand_on_the_floor_below_eye_level() {
+        let player = Player::new_with_pos(0.0, 0.0, 0.0);
+        let horizon = player.horizon(900);
+        let key = Sprite::new(SpriteType::KeyGold, 0.0, 0.0);
+        let portal = Sprite::new(SpriteType::ExitPortal, 0.0, 0.0);
+
+        for (distance, expected_floor) in [(60.0, 825.0), (250.0, 540.0), (800.0, 478.125)] {
+            let floor = floor_line(horizon, distance, 900);
+            assert!((floor - expected_floor).abs() < 1e-3, "distancia {}: {}", distance, floor);
+
+            // La llave queda entera por debajo del horizonte; el portal sube más
+            let key_top = floor - key.projected_height(distance, 900);
+            let portal_top = floor - portal.projected_height(distance, 900);
+            assert!(key_top > horizon, "distancia {}", distance);
+            assert!(portal_top < key_top);
+        }
+    }
+
+    #[test]
+    fn z_buffer_follows_the_world_resolution_when_it_changes() {
+        let mut manager = manager_with_open_view(64);
+        manager.sprites.push(Sprite::new(SpriteType::KeyGold, 100.0, 0.0));
+        let player = Player::new_with_pos(0.0, 0.0, 0.0);
+
+        for width in [32, 48, 64] {
+            let framebuffer = Framebuffer::new(width, 24);
+            let walls = vec![75.0; width as usize];
+            manager.queue_visible_sprites(&framebuffer, &player, &walls, &mut TranslucentQueue::new());
+            assert_eq!(manager.z_buffer, walls);
+        }
+    }
+
+    #[test]
+    fn strip_sprites_play_their_frames_and_keep_transparent_pixels() {
+        // Tira de 2 frames de 2x1: rojo rojo | azul transparente
+        let red = [200, 0, 0, 255];
+        let blue = [0, 0, 200, 255];
+        let strip = RealSpriteInfo {
+            pixels: [red, red, blue, [0, 0, 0, 0]].concat(),
+            width: 4,
+            height: 1,
+            fallback_color: Color::RED,
+            frame_count: 2,
+        };
+        let mut manager = manager_with_open_view(64);
+        manager.insert_real_sprite(SpriteType::TrapSpike, strip);
+        let mut sprite = Sprite::new(SpriteType::TrapSpike, 0.0, 0.0);
+
+        assert_eq!(manager.get_sprite_color(&sprite, 1.0, 0.5, 1.0), Color::new(200, 0, 0, 255));
+        // El ancho proyectado es el de un frame, no el de la tira entera
+        assert_eq!(manager.projected_width(SpriteType::TrapSpike, 10), 20);
+
+        sprite.animation_time = 1.5 / SpriteType::TrapSpike.animation_fps();
+        assert_eq!(manager.get_sprite_color(&sprite, 0.0, 0.5, 1.0), Color::new(0, 0, 200, 255));
+        // Un pixel transparente del frame deja ver el patrón procedural (aquí vacío)
+        assert_eq!(manager.get_sprite_color(&sprite, 1.0, 0.5, 1.0).a, 0);
+    }
+
+    #[test]
+    fn the_checkpoint_flag_ships_as_an_animated_strip() {
+        let sprite_type = SpriteType::Checkpoint;
+        let strip = RealSpriteInfo::from_png_strip(sprite_type.get_file_path(), sprite_type.frame_count(), Color::CYAN).unwrap();
+        assert!(strip.frame_count > 1);
+        assert_eq!(strip.frame_width() * strip.frame_count, strip.width);
+
+        // El borde de la bandera se mueve entre frames y el ciclo se repite
+        let fps = sprite_type.animation_fps();
+        let edge = |time: f32| -> Vec<Color> {
+            (0..32).map(|y| strip.get_animated_pixel_color(time, fps, 0.8, y as f32 / 32.0)).collect()
+        };
+        assert_ne!(edge(0.0), edge(1.5 / fps));
+        assert_eq!(edge(0.5 / fps), edge((strip.frame_count as f32 + 0.5) / fps));
+    }
+
+    #[test]
+    fn exit_portal_lights_up_once_its_requirements_are_met() {
+        let mut manager = manager_with_open_view(64);
+        let portal = Sprite::new(SpriteType::ExitPortal, 0.0, 0.0);
+        let total = |color: Color| color.r as u32 + color.g as u32 + color.b as u32;
+
+        manager.set_exit_lock(Some("FALTA LA LLAVE".to_string()));
+        let locked: Vec<Color> = [0.5, 0.3, 0.1]
+            .iter()
+            .map(|&tx| manager.get_sprite_color(&portal, tx, 0.5, 1.0))
+            .collect();
+
+        manager.set_exit_lock(None);
+        let open: Vec<Color> = [0.5, 0.3, 0.1]
+            .iter()
+            .map(|&tx| manager.get_sprite_color(&portal, tx, 0.5, 1.0))
+            .collect();
+
+        for (locked, open) in locked.iter().zip(&open) {
+            assert!(total(*open) > total(*locked), "{:?} vs {:?}", open, locked);
+        }
+        // Las llaves no cambian de aspecto con el estado de la salida
+        let key = Sprite::new(SpriteType::KeyGold, 0.0, 0.0);
+        manager.set_exit_lock(Some("FALTA LA LLAVE".to_string()));
+        let key_locked = manager.get_sprite_color(&key, 0.5, 0.5, 1.0);
+        manager.set_exit_lock(None);
+        assert_eq!(manager.get_sprite_color(&key, 0.5, 0.5, 1.0), key_locked);
+    }
+
+    #[test]
+    fn only_the_nearest_glowing_objects_light_the_walls() {
+        let mut manager = SpriteManager::empty();
+        manager.sprites.push(Sprite::new(SpriteType::KeyGold, 0.0, 0.0));
+        for x in 1..=MAX_POINT_LIGHTS + 2 {
+            manager.sprites.push(Sprite::new(SpriteType::ExtraLife, x as f32 * 100.0, 0.0));
+        }
+        manager.sprites.push(Sprite::new(SpriteType::ExitPortal, 50.0, 0.0));
+
+        let lights = manager.point_lights(Vector2::new(0.0, 0.0));
+        assert_eq!(lights.len(), MAX_POINT_LIGHTS);
+        assert_eq!(lights[0].x, 50.0);
+        assert!(lights.iter().all(|light| light.x < 700.0));
+
+        manager.set_exit_lock(Some("FALTA LA LLAVE".to_string()));
+        let locked = manager.point_lights(Vector2::new(0.0, 0.0))[0].intensity;
+        assert_eq!(lights[0].intensity, locked * 2.0);
+    }
+
+    #[test]
+    fn picked_up_sprites_become_inactive_right_away() {
+        let maze: Maze = ["#####", "#kcl#", "#####"].iter().map(|row| row.chars().collect()).collect();
+        let mut manager = SpriteManager::empty();
+        manager.load_sprites_from_maze(&maze, 100);
+        assert_eq!(manager.sprite_count(), 3);
+
+        manager.remove_sprites_at_cells([(1, 1), (3, 1)], 100);
+
+        let active: Vec<SpriteType> = manager.sprites.iter().filter(|s| s.active).map(|s| s.sprite_type).collect();
+        assert_eq!(active, vec![SpriteType::Checkpoint]);
+        // Una casilla ya vaciada no se lleva otro sprite
+        manager.remove_sprites_at_cells([(1, 1)], 100);
+        assert_eq!(manager.sprite_count(), 1);
+    }
+
+    #[test]
+    fn png_sprites_keep_their_aspect_ratio_and_clip_at_walls_and_edges() {
+        // PNG el doble de ancho que de alto, todo opaco
+        let png = RealSpriteInfo {
+            pixels: [200, 0, 0, 255].repeat(8),
+            width: 4,
+            height: 2,
+            fallback_color: Color::new(200, 0, 0, 255),
+            frame_count: 1,
+        };
+        let mut manager = manager_with_open_view(64);
+        assert_eq!(manager.missing_sprites(), SpriteType::ALL.to_vec());
+        manager.insert_real_sprite(SpriteType::TrapSpike, png);
+        assert!(!manager.missing_sprites().contains(&SpriteType::TrapSpike));
+        assert_eq!(manager.missing_sprites().len(), SpriteType::ALL.len() - 1);
+        manager.z_buffer[..10].fill(30.0);
+        let sprite = Sprite::new(SpriteType::TrapSpike, 0.0, 0.0);
+
+        // 10 de alto y 20 de ancho, centrado en la columna 32
+        let mut framebuffer = Framebuffer::new(64, 48);
+        manager.render_sprite_column(&mut framebuffer, &sprite, (32, 40), 10, 50.0, &FrameContext::default());
+        let cells = lit_pixels(&mut framebuffer);
+        assert_eq!(cells.len(), 20 * 10);
+        assert_eq!((cells[0], cells[cells.len() - 1]), ((22, 30), (41, 39)));
+
+        // Muy ancho: se corta en el borde derecho y detrás de la pared de la izquierda
+        let mut framebuffer = Framebuffer::new(64, 48);
+        manager.render_sprite_column(&mut framebuffer, &sprite, (32, 40), 40, 50.0, &FrameContext::default());
+        let columns: Vec<u32> = lit_pixels(&mut framebuffer).iter().map(|&(x, _)| x).collect();
+        assert_eq!((columns.iter().min(), columns.iter().max()), (Some(&10), Some(&63)));
+    }
+
+    #[test]
+    fn sprites_straddling_a_wall_edge_are_clipped_per_column() {
+        // Pared a distancia 30 en las columnas 0..20, pasillo abierto después
+        let mut manager = manager_with_open_view(64);
+        manager.z_buffer[..20].fill(30.0);
+
+        assert_eq!(manager.unoccluded_columns(10, 40, 50.0), Some((20, 40)));
+        assert_eq!(manager.unoccluded_columns(10, 40, 20.0), Some((10, 40)));
+        assert_eq!(manager.unoccluded_columns(0, 20, 50.0), None);
+
+        let mut framebuffer = Framebuffer::new(64, 48);
+        let sprite = Sprite::new(SpriteType::TrapSpike, 0.0, 0.0);
+        manager.render_sprite_column(&mut framebuffer, &sprite, (25, 40), 30, 50.0, &FrameContext::default());
+
+        let background = Framebuffer::new(64, 48).get_pixel(0, 0);
+        let drawn_in = |framebuffer: &mut Framebuffer, columns: std::ops::Range<u32>| {
+            columns.flat_map(|x| (0..48).map(move |y| (x, y)))
+                .any(|(x, y)| framebuffer.get_pixel(x, y) != background)
+        };
+        assert!(!drawn_in(&mut framebuffer, 0..20));
+        assert!(drawn_in(&mut framebuffer, 20..40));
+    }
+
+    #[test]
+    fn offscreen_sprites_are_clipped_without_underflow() {
+        let mut framebuffer = Framebuffer::new(64, 48);
+        let manager = manager_with_open_view(64);
+        let sprite = Sprite::new(SpriteType::TrapSpike, 0.0, 0.0);
+
+        // Fuera por los lados, por arriba o por abajo: no se toca ningún pixel
+        manager.render_sprite_column(&mut framebuffer, &sprite, (-500, 40), 20, 50.0, &FrameContext::default());
+        manager.render_sprite_column(&mut framebuffer, &sprite, (500, 40), 20, 50.0, &FrameContext::default());
+        manager.render_sprite_column(&mut framebuffer, &sprite, (32, i32::MIN), 20, 50.0, &FrameContext::default());
+        manager.render_sprite_column(&mut framebuffer, &sprite, (32, i32::MAX), 20, 50.0, &FrameContext::default());
+        assert!(lit_pixels(&mut framebuffer).is_empty());
+
+        // Enorme: se recorta a todo el ancho y a lo que hay sobre el suelo
+        manager.render_sprite_column(&mut framebuffer, &sprite, (0, 40), u32::MAX, 1.0, &FrameContext::default());
+        let cells = lit_pixels(&mut framebuffer);
+        assert_eq!(cells.len(), 64 * 40);
+        assert_eq!((cells[0], cells[cells.len() - 1]), ((0, 0), (63, 39)));
+    }
+}
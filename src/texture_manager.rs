@@ -3,16 +3,52 @@
 use raylib::prelude::*;
 use image::{DynamicImage, ImageBuffer, Rgba};
 use std::collections::HashMap;
+use crate::game_state::RANDOM_LEVEL;
+use crate::maze::TileProperties;
 
 pub struct RealTextureInfo {
     pub pixels: Vec<u8>,
     pub width: i32,
     pub height: i32,
     pub fallback_color: Color,
+    // Frames de animación colocados uno al lado del otro; 1 = textura fija
+    pub frame_count: i32,
+}
+
+// Frames por segundo de las texturas de pared animadas
+pub const WALL_ANIMATION_FPS: f32 = 8.0;
+
+/// Muestreo de las texturas de pared: el vecino más cercano da el aspecto
+/// pixelado (y es más rápido); el bilineal suaviza las paredes de cerca
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextureFiltering {
+    Nearest,
+    Bilinear,
+}
+
+impl TextureFiltering {
+    /// Nombre usado en el archivo de settings
+    pub fn name(&self) -> &'static str {
+        match self {
+            TextureFiltering::Nearest => "nearest",
+            TextureFiltering::Bilinear => "bilinear",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [TextureFiltering::Nearest, TextureFiltering::Bilinear]
+            .into_iter()
+            .find(|filtering| filtering.name() == name)
+    }
 }
 
 impl RealTextureInfo {
     pub fn from_png(path: &str, fallback_color: Color) -> Result<Self, String> {
+        Self::from_png_strip(path, 1, fallback_color)
+    }
+
+    /// Carga una tira horizontal de `frame_count` frames del mismo ancho
+    pub fn from_png_strip(path: &str, frame_count: usize, fallback_color: Color) -> Result<Self, String> {
         match image::open(path) {
             Ok(img) => {
                 let rgba_img = img.to_rgba8();
@@ -23,14 +59,62 @@ impl RealTextureInfo {
                     width: width as i32,
                     height: height as i32,
                     fallback_color,
+                    frame_count: (frame_count as i32).clamp(1, width.max(1) as i32),
                 })
             },
             Err(e) => Err(format!("Error loading PNG {}: {}", path, e))
         }
     }
 
-    pub fn get_pixel_color(&self, texture_x: f32, texture_y: f32) -> Color {
-        let x = (texture_x.clamp(0.0, 1.0) * (self.width - 1) as f32) as usize;
+    /// Frame que toca mostrar a `animation_time` segundos
+    pub fn frame_at(&self, animation_time: f32) -> i32 {
+        ((animation_time.max(0.0) * WALL_ANIMATION_FPS) as i32).rem_euclid(self.frame_count.max(1))
+    }
+
+    pub fn get_animated_pixel_color(&self, animation_time: f32, texture_x: f32, texture_y: f32, filtering: TextureFiltering) -> Color {
+        let frame = self.frame_at(animation_time);
+        match filtering {
+            TextureFiltering::Nearest => self.get_frame_pixel_color(frame, texture_x, texture_y),
+            TextureFiltering::Bilinear => self.get_frame_pixel_bilinear(frame, texture_x, texture_y),
+        }
+    }
+
+    // Mezcla los cuatro texels vecinos; en los bordes se repite el último
+    // texel del frame en vez de leer fuera de él o del frame de al lado
+    fn get_frame_pixel_bilinear(&self, frame: i32, texture_x: f32, texture_y: f32) -> Color {
+        let frame_width = self.width / self.frame_count.max(1);
+        if frame_width < 1 || self.height < 1 {
+            return self.fallback_color;
+        }
+
+        let u = texture_x.clamp(0.0, 1.0) * (frame_width - 1) as f32;
+        let v = texture_y.clamp(0.0, 1.0) * (self.height - 1) as f32;
+        let (x0, y0) = (u as i32, v as i32);
+        let (x1, y1) = ((x0 + 1).min(frame_width - 1), (y0 + 1).min(self.height - 1));
+        let (fx, fy) = (u - x0 as f32, v - y0 as f32);
+
+        let texel = |x: i32, y: i32| {
+            let index = ((y * self.width + frame * frame_width + x) * 4) as usize;
+            self.pixels.get(index..index + 4).map(|rgba| [rgba[0], rgba[1], rgba[2], rgba[3]])
+        };
+        let (Some(top_left), Some(top_right), Some(bottom_left), Some(bottom_right)) =
+            (texel(x0, y0), texel(x1, y0), texel(x0, y1), texel(x1, y1))
+        else {
+            return self.fallback_color;
+        };
+
+        let channel = |i: usize| {
+            let top = top_left[i] as f32 * (1.0 - fx) + top_right[i] as f32 * fx;
+            let bottom = bottom_left[i] as f32 * (1.0 - fx) + bottom_right[i] as f32 * fx;
+            (top * (1.0 - fy) + bottom * fy).round() as u8
+        };
+        Color::new(channel(0), channel(1), channel(2), channel(3))
+    }
+
+    // texture_x recorre solo el ancho de un frame dentro de la tira
+    fn get_frame_pixel_color(&self, frame: i32, texture_x: f32, texture_y: f32) -> Color {
+        let frame_width = self.width / self.frame_count.max(1);
+        let x = (frame * frame_width) as usize + (texture_x.clamp(0.0, 1.0) * (frame_width - 1) as f32) as usize;
         let y = (texture_y.clamp(0.0, 1.0) * (self.height - 1) as f32) as usize;
         
         let index = (y * self.width as usize + x) * 4;
@@ -48,8 +132,48 @@ impl RealTextureInfo {
     }
 }
 
+// Texturas de pared por tipo de celda (se decodifican en el AssetLoader):
+// (carácter, archivo, frames). Con más de un frame el PNG es una tira
+// horizontal que se anima a WALL_ANIMATION_FPS
+pub const WALL_TEXTURE_FILES: [(char, &str, usize); 11] = [
+    ('#', "assets/textures/walls/wall_yellow.png", 1),
+    ('W', "assets/textures/walls/wall_yellow.png", 1),
+    ('w', "assets/textures/walls/wall_yellow.png", 1),
+    ('+', "assets/textures/walls/wall_yellow.png", 1),
+    ('-', "assets/textures/walls/wall_yellow.png", 1),
+    ('|', "assets/textures/walls/wall_yellow.png", 1),
+    ('r', "assets/textures/walls/wall_red.png", 1),
+    ('b', "assets/textures/walls/wall_blue.png", 1),
+    ('g', "assets/textures/walls/wall_green.png", 1),
+    ('D', "assets/textures/walls/door.png", 1),
+    ('P', "assets/textures/walls/exit_portal_strip.png", 8),
+];
+
+// Texturas específicas de región: (región, carácter de pared, archivo)
+pub const REGION_TEXTURE_FILES: [(char, char, &str); 2] = [
+    ('1', 'b', "assets/textures/walls/wall_red.png"),
+    ('2', 'b', "assets/textures/walls/wall_yellow.png"),
+];
+
+// Tema de paredes por nivel: (nivel, archivo). Mientras el nivel está cargado
+// reemplaza la textura de las paredes comunes (THEMED_WALL_CHARS); el nivel 1
+// y los niveles sin entrada usan WALL_TEXTURE_FILES tal cual
+pub const LEVEL_THEME_FILES: [(usize, &str); 3] = [
+    (2, "assets/textures/walls/wall_red.png"),
+    (3, "assets/textures/walls/wall_blue.png"),
+    (RANDOM_LEVEL, "assets/textures/walls/wall_green.png"),
+];
+
+// Paredes que cambian con el tema; las de color (r, b, g) y las puertas no
+const THEMED_WALL_CHARS: [char; 6] = ['#', 'W', 'w', '+', '-', '|'];
+
 pub struct TextureManager {
     real_textures: HashMap<char, RealTextureInfo>,
+    region_textures: HashMap<(char, char), RealTextureInfo>,
+    theme_textures: HashMap<usize, RealTextureInfo>,
+    // Nivel cuyo tema está activo
+    theme: usize,
+    filtering: TextureFiltering,
     fallback_colors: HashMap<char, Color>,
     default_texture_size: usize,
 }
@@ -58,58 +182,104 @@ impl TextureManager {
     pub fn new(_rl: &mut RaylibHandle, _thread: &RaylibThread) -> Self {
         let mut texture_manager = TextureManager {
             real_textures: HashMap::new(),
+            region_textures: HashMap::new(),
+            theme_textures: HashMap::new(),
+            theme: 1,
+            filtering: TextureFiltering::Nearest,
             fallback_colors: HashMap::new(),
             default_texture_size: 64,
         };
 
+        // Las texturas reales llegan luego desde el AssetLoader
         texture_manager.init_fallback_colors();
-        texture_manager.load_real_textures();
         
         texture_manager
     }
 
     fn init_fallback_colors(&mut self) {
         self.fallback_colors.insert('#', Color::new(200, 200, 120, 255));
+        self.fallback_colors.insert('W', Color::new(200, 200, 120, 255));
+        self.fallback_colors.insert('w', Color::new(170, 170, 100, 255));
         self.fallback_colors.insert('+', Color::new(180, 180, 100, 255)); 
         self.fallback_colors.insert('-', Color::new(220, 220, 140, 255));
         self.fallback_colors.insert('|', Color::new(190, 190, 110, 255));
         self.fallback_colors.insert('r', Color::new(180, 60, 60, 255));
         self.fallback_colors.insert('b', Color::new(60, 60, 180, 255));
+        self.fallback_colors.insert('g', Color::new(70, 160, 80, 255));
+        self.fallback_colors.insert('D', Color::new(125, 80, 45, 255));
         self.fallback_colors.insert('e', Color::new(100, 255, 100, 255));
+        self.fallback_colors.insert('P', Color::new(150, 50, 30, 255));
+        self.fallback_colors.insert('v', Color::new(150, 210, 230, 110));
     }
 
-    fn load_real_textures(&mut self) {
-        let texture_mappings = [
-            ('#', "assets/textures/walls/wall_yellow.png"),
-            ('+', "assets/textures/walls/wall_yellow.png"),
-            ('-', "assets/textures/walls/wall_yellow.png"),
-            ('|', "assets/textures/walls/wall_yellow.png"),
-            ('r', "assets/textures/walls/wall_red.png"),
-            ('b', "assets/textures/walls/wall_blue.png"),
-        ];
-
-        for (wall_char, texture_path) in texture_mappings.iter() {
-            let fallback_color = *self.fallback_colors.get(wall_char).unwrap_or(&Color::GRAY);
-            
-            if let Ok(texture_info) = RealTextureInfo::from_png(texture_path, fallback_color) {
-                self.real_textures.insert(*wall_char, texture_info);
-            }
+    /// Registra una textura decodificada en segundo plano
+    pub fn insert_real_texture(&mut self, wall_char: char, mut texture_info: RealTextureInfo) {
+        texture_info.fallback_color = *self.fallback_colors.get(&wall_char).unwrap_or(&Color::GRAY);
+        self.real_textures.insert(wall_char, texture_info);
+    }
+
+    /// Registra la textura de un carácter de pared dentro de una región
+    pub fn insert_region_texture(&mut self, region: char, wall_char: char, mut texture_info: RealTextureInfo) {
+        texture_info.fallback_color = *self.fallback_colors.get(&wall_char).unwrap_or(&Color::GRAY);
+        self.region_textures.insert((region, wall_char), texture_info);
+    }
+
+    /// Registra la textura de paredes del tema de un nivel
+    pub fn insert_theme_texture(&mut self, level: usize, mut texture_info: RealTextureInfo) {
+        texture_info.fallback_color = *self.fallback_colors.get(&'#').unwrap_or(&Color::GRAY);
+        self.theme_textures.insert(level, texture_info);
+    }
+
+    /// Activa el tema de paredes del nivel; sin PNG de tema (o sin entrada en
+    /// LEVEL_THEME_FILES) siguen las texturas de siempre
+    pub fn set_theme(&mut self, level: usize) {
+        self.theme = level;
+    }
+
+    pub fn set_filtering(&mut self, filtering: TextureFiltering) {
+        self.filtering = filtering;
+    }
+
+    // Textura real de un carácter con el tema activo aplicado
+    fn texture_for(&self, ch: char) -> Option<&RealTextureInfo> {
+        THEMED_WALL_CHARS
+            .contains(&ch)
+            .then(|| self.theme_textures.get(&self.theme))
+            .flatten()
+            .or_else(|| self.real_textures.get(&ch))
+    }
+
+    /// Color de pared teniendo en cuenta la región; sin región (o sin textura
+    /// para esa región) se usa la textura normal del carácter
+    pub fn get_wall_color_in_region(
+        &self,
+        ch: char,
+        region: Option<char>,
+        texture_x: f32,
+        texture_y: f32,
+        animation_time: f32,
+    ) -> Color {
+        if let Some(region_texture) = region.and_then(|region| self.region_textures.get(&(region, ch))) {
+            return region_texture.get_animated_pixel_color(animation_time, texture_x, texture_y, self.filtering);
         }
+
+        self.get_wall_color_textured(ch, texture_x, texture_y, animation_time)
     }
 
-    pub fn get_wall_color_textured(&self, ch: char, texture_x: f32, texture_y: f32) -> Color {
-        if let Some(real_texture) = self.real_textures.get(&ch) {
-            real_texture.get_pixel_color(texture_x, texture_y)
+    /// `animation_time` (segundos de juego) elige el frame de las texturas animadas
+    pub fn get_wall_color_textured(&self, ch: char, texture_x: f32, texture_y: f32, animation_time: f32) -> Color {
+        if let Some(real_texture) = self.texture_for(ch) {
+            real_texture.get_animated_pixel_color(animation_time, texture_x, texture_y, self.filtering)
         } else {
-            self.get_wall_color_simple(ch, texture_x, texture_y)
+            self.get_wall_color_simple(ch, texture_x, texture_y, animation_time)
         }
     }
 
-    pub fn get_wall_color_simple(&self, ch: char, texture_x: f32, texture_y: f32) -> Color {
+    pub fn get_wall_color_simple(&self, ch: char, texture_x: f32, texture_y: f32, animation_time: f32) -> Color {
         let base_color = self.fallback_colors.get(&ch).copied().unwrap_or(Color::GRAY);
         
         let variation_factor = match ch {
-            '#' | '+' | '-' | '|' => {
+            '#' | 'W' | 'w' | '+' | '-' | '|' => {
                 let grid_x = (texture_x * 8.0) as i32;
                 let grid_y = (texture_y * 8.0) as i32;
                 let is_border = (grid_x % 8 == 0) || (grid_y % 8 == 0);
@@ -137,14 +307,22 @@ impl TextureManager {
                 
                 0.9 + (panel_x % 2) as f32 * 0.1 + center_dist * 0.2
             },
-            'e' => {
-                let time = std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap_or_default()
-                    .as_secs_f32();
-                let pulse = (time * 3.0).sin() * 0.3 + 0.7;
-                pulse
+            'g' => {
+                // Azulejos cuadrados con junta oscura
+                let tile_x = (texture_x * 6.0) % 1.0;
+                let tile_y = (texture_y * 6.0) % 1.0;
+                let is_grout = tile_x < 0.08 || tile_y < 0.08;
+
+                if is_grout { 0.6 } else { 0.95 + tile_x * 0.1 }
+            },
+            'D' => {
+                // Tablones verticales con un marco metálico más oscuro
+                let is_frame = !(0.06..0.94).contains(&texture_x) || !(0.05..0.95).contains(&texture_y);
+                let is_gap = (texture_x * 7.0) % 1.0 < 0.08;
+
+                if is_frame { 0.7 } else if is_gap { 0.55 } else { 1.0 }
             },
+            'e' | 'P' => (animation_time * 3.0).sin() * 0.3 + 0.7,
             _ => 1.0
         };
 
@@ -166,11 +344,12 @@ impl TextureManager {
     }
 
     pub fn has_texture(&self, ch: char) -> bool {
-        self.real_textures.contains_key(&ch)
+        self.texture_for(ch).is_some()
     }
 
     pub fn is_wall_cell(&self, cell: char) -> bool {
-        matches!(cell, '#' | '+' | '-' | '|' | 'r' | 'b' | 'g' | 'e')
+        let props = TileProperties::of(cell);
+        props.blocks_sight || props.is_low_wall()
     }
 
     pub fn get_texture_size(&self) -> usize {
@@ -183,4 +362,163 @@ impl Drop for TextureManager {
         self.real_textures.clear();
         self.fallback_colors.clear();
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fallback_only_manager() -> TextureManager {
+        let mut manager = TextureManager {
+            real_textures: HashMap::new(),
+            region_textures: HashMap::new(),
+            theme_textures: HashMap::new(),
+            theme: 1,
+            filtering: TextureFiltering::Nearest,
+            fallback_colors: HashMap::new(),
+            default_texture_size: 64,
+        };
+        manager.init_fallback_colors();
+        manager
+    }
+
+    #[test]
+    fn every_wall_tile_has_a_texture_and_fallback_color() {
+        let manager = fallback_only_manager();
+        let wall_chars: Vec<char> = (' '..='~')
+            .filter(|&cell| TileProperties::of(cell).height > 0.0 && !TileProperties::of(cell).translucent)
+            .collect();
+
+        assert!(wall_chars.contains(&'g') && wall_chars.contains(&'w'));
+        for cell in wall_chars {
+            assert!(manager.is_wall_cell(cell));
+            assert!(
+                WALL_TEXTURE_FILES.iter().any(|(wall_char, _, _)| *wall_char == cell),
+                "'{}' no tiene textura",
+                cell
+            );
+            assert_ne!(manager.get_wall_color_fallback(cell), Color::GRAY, "'{}' no tiene color", cell);
+        }
+    }
+
+    #[test]
+    fn green_walls_fall_back_to_green() {
+        let color = fallback_only_manager().get_wall_color_textured('g', 0.5, 0.5, 0.0);
+        assert!(color.g > color.r && color.g > color.b);
+    }
+
+    // Textura de 1x1 de un solo color
+    fn solid_texture(color: [u8; 4]) -> RealTextureInfo {
+        RealTextureInfo { pixels: color.to_vec(), width: 1, height: 1, fallback_color: Color::GRAY, frame_count: 1 }
+    }
+
+    #[test]
+    fn level_themes_swap_common_walls_and_fall_back_when_missing() {
+        let mut manager = fallback_only_manager();
+        manager.insert_real_texture('#', solid_texture([200, 200, 0, 255]));
+        manager.insert_real_texture('b', solid_texture([0, 0, 200, 255]));
+        manager.insert_theme_texture(2, solid_texture([200, 0, 0, 255]));
+
+        let wall = |manager: &TextureManager, ch| manager.get_wall_color_textured(ch, 0.5, 0.5, 0.0);
+        assert_eq!(wall(&manager, '#'), Color::new(200, 200, 0, 255));
+
+        manager.set_theme(2);
+        assert_eq!(wall(&manager, '#'), Color::new(200, 0, 0, 255));
+        assert_eq!(wall(&manager, 'b'), Color::new(0, 0, 200, 255));
+
+        // El tema del nivel 3 no se cargó: vuelven las texturas normales
+        manager.set_theme(3);
+        assert_eq!(wall(&manager, '#'), Color::new(200, 200, 0, 255));
+    }
+
+    #[test]
+    fn region_textures_replace_only_their_own_walls() {
+        let mut manager = fallback_only_manager();
+        manager.insert_real_texture('#', solid_texture([200, 200, 0, 255]));
+        manager.insert_real_texture('b', solid_texture([0, 0, 200, 255]));
+        manager.insert_region_texture('2', 'b', solid_texture([250, 220, 0, 255]));
+
+        let wall = |ch, region| manager.get_wall_color_in_region(ch, region, 0.5, 0.5, 0.0);
+        assert_eq!(wall('b', Some('2')), Color::new(250, 220, 0, 255));
+        assert_eq!(wall('b', Some('1')), Color::new(0, 0, 200, 255));
+        assert_eq!(wall('b', None), Color::new(0, 0, 200, 255));
+        assert_eq!(wall('#', Some('2')), Color::new(200, 200, 0, 255));
+    }
+
+    #[test]
+    fn every_region_texture_is_used_by_a_shipped_level() {
+        let maze = crate::maze::load_maze("assets/levels/level3.txt").unwrap();
+        let regions = crate::maze::load_region_map("assets/levels/level3_regions.txt").unwrap();
+
+        // Regiones que tocan al menos una pared del carácter de su textura
+        let mut used = Vec::new();
+        for (y, row) in maze.iter().enumerate() {
+            for (x, &cell) in row.iter().enumerate() {
+                if let Some(region) = crate::maze::region_at(&regions, x, y) {
+                    assert!(REGION_TEXTURE_FILES.iter().any(|(r, _, _)| *r == region), "región '{}' sin texturas", region);
+                    used.push((region, cell));
+                }
+            }
+        }
+
+        for (region, wall_char, path) in REGION_TEXTURE_FILES.iter() {
+            assert!(used.contains(&(*region, *wall_char)), "'{}' en la región '{}' no aparece", wall_char, region);
+            assert!(std::path::Path::new(path).exists(), "{}", path);
+        }
+    }
+
+    #[test]
+    fn bilinear_filtering_blends_neighbours_without_bleeding_into_the_next_frame() {
+        // Tira de 2 frames de 2x1: negro, blanco | rojo, rojo
+        let strip = RealTextureInfo {
+            pixels: [[0, 0, 0, 255], [255, 255, 255, 255], [255, 0, 0, 255], [255, 0, 0, 255]].concat(),
+            width: 4,
+            height: 1,
+            fallback_color: Color::GRAY,
+            frame_count: 2,
+        };
+        let mut manager = fallback_only_manager();
+        manager.insert_real_texture('#', strip);
+
+        assert_eq!(manager.get_wall_color_textured('#', 0.5, 0.0, 0.0), Color::BLACK);
+
+        manager.set_filtering(TextureFiltering::Bilinear);
+        assert_eq!(manager.get_wall_color_textured('#', 0.5, 0.0, 0.0), Color::new(128, 128, 128, 255));
+        assert_eq!(manager.get_wall_color_textured('#', 1.0, 1.0, 0.0), Color::WHITE);
+        assert_eq!(manager.get_wall_color_textured('#', 7.0, -3.0, 0.0), Color::WHITE);
+    }
+
+    #[test]
+    fn strip_textures_pick_the_frame_from_animation_time() {
+        // Tira de 2 frames de 2x1: rojo | azul
+        let red = [255, 0, 0, 255];
+        let blue = [0, 0, 255, 255];
+        let strip = RealTextureInfo {
+            pixels: [red, red, blue, blue].concat(),
+            width: 4,
+            height: 1,
+            fallback_color: Color::GRAY,
+            frame_count: 2,
+        };
+
+        let frame_time = 1.0 / WALL_ANIMATION_FPS;
+        assert_eq!(strip.get_animated_pixel_color(0.0, 1.0, 0.0, TextureFiltering::Nearest), Color::new(255, 0, 0, 255));
+        assert_eq!(strip.get_animated_pixel_color(frame_time * 1.5, 0.0, 0.0, TextureFiltering::Nearest), Color::new(0, 0, 255, 255));
+        assert_eq!(strip.frame_at(frame_time * 2.5), 0);
+    }
+
+    #[test]
+    fn the_portal_frame_wall_ships_as_an_animated_strip() {
+        let (_, path, frames) = WALL_TEXTURE_FILES.iter().find(|(wall_char, _, _)| *wall_char == 'P').unwrap();
+        assert!(*frames > 1);
+        let strip = RealTextureInfo::from_png_strip(path, *frames, Color::GRAY).unwrap();
+        assert_eq!(strip.frame_count, *frames as i32);
+
+        let frame_time = 1.0 / WALL_ANIMATION_FPS;
+        let column = |time: f32| -> Vec<Color> {
+            (0..8).map(|y| strip.get_animated_pixel_color(time, 0.3, y as f32 / 8.0, TextureFiltering::Nearest)).collect()
+        };
+        assert_ne!(column(0.0), column(frame_time * 2.5));
+        assert_eq!(column(0.0), column(frame_time * *frames as f32));
+    }
+}
@@ -0,0 +1,100 @@
+// transition.rs - Fundido a negro entre niveles
+
+// Segundos de cada mitad del fundido
+pub const FADE_SECONDS: f32 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FadePhase {
+    Out,
+    In,
+}
+
+/// Fundido a negro y vuelta: la pantalla se oscurece, se cambia lo que haya
+/// que cambiar con la pantalla en negro y luego se aclara
+#[derive(Debug, Clone, Copy)]
+pub struct ScreenFade {
+    phase: FadePhase,
+    elapsed: f32,
+}
+
+impl ScreenFade {
+    pub fn new() -> Self {
+        ScreenFade {
+            phase: FadePhase::Out,
+            elapsed: 0.0,
+        }
+    }
+
+    /// Avanza el fundido; devuelve true el frame en que la pantalla queda en
+    /// negro. Con `skip` se salta lo que falte (y también avisa si no había
+    /// llegado a negro todavía)
+    pub fn update(&mut self, delta_time: f32, skip: bool) -> bool {
+        let was_fading_out = self.phase == FadePhase::Out;
+
+        if skip {
+            self.phase = FadePhase::In;
+            self.elapsed = FADE_SECONDS;
+            return was_fading_out;
+        }
+
+        self.elapsed += delta_time;
+        if was_fading_out && self.elapsed >= FADE_SECONDS {
+            self.phase = FadePhase::In;
+            self.elapsed = 0.0;
+            return true;
+        }
+        false
+    }
+
+    /// Cuánto negro cubre la pantalla, de 0 a 1
+    pub fn darkness(&self) -> f32 {
+        let progress = (self.elapsed / FADE_SECONDS).clamp(0.0, 1.0);
+        match self.phase {
+            FadePhase::Out => progress,
+            FadePhase::In => 1.0 - progress,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.phase == FadePhase::In && self.elapsed >= FADE_SECONDS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fade_goes_to_black_once_and_back() {
+        let mut fade = ScreenFade::new();
+        assert_eq!(fade.darkness(), 0.0);
+
+        let mut blacked_out = 0;
+        let mut darkest = 0.0_f32;
+        for _ in 0..70 {
+            if fade.update(1.0 / 60.0, false) {
+                blacked_out += 1;
+                assert_eq!(fade.darkness(), 1.0);
+            }
+            darkest = darkest.max(fade.darkness());
+        }
+
+        assert_eq!(blacked_out, 1);
+        assert_eq!(darkest, 1.0);
+        assert!(fade.is_finished());
+        assert_eq!(fade.darkness(), 0.0);
+    }
+
+    #[test]
+    fn skipping_still_reports_the_black_frame_only_once() {
+        let mut fade = ScreenFade::new();
+        fade.update(0.1, false);
+        assert!(fade.update(0.0, true));
+        assert!(fade.is_finished());
+
+        let mut fade = ScreenFade::new();
+        assert!(fade.update(FADE_SECONDS, false));
+        assert!(!fade.update(0.0, true));
+        assert!(fade.is_finished());
+    }
+}
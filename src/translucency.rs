@@ -0,0 +1,54 @@
+// translucency.rs - Cola de elementos translúcidos ordenados por profundidad
+
+/// Columna de una pared translúcida (vidrio) o de un muro bajo, que el rayo
+/// atraviesa para seguir hasta la pared de detrás
+#[derive(Debug, Clone, Copy)]
+pub struct QueuedWallColumn {
+    pub column: u32,
+    // Borde superior sin recortar y altura proyectada, para mapear la textura
+    pub top: f32,
+    pub height: f32,
+    pub impact: char,
+    pub region: Option<char>,
+    pub texture_x: f32,
+    pub brightness: f32,
+    // Los muros bajos tapan lo de detrás en vez de mezclarse
+    pub opaque: bool,
+}
+
+/// Fragmento que se compone después de las paredes opacas
+#[derive(Debug, Clone, Copy)]
+pub enum TranslucentKind {
+    /// Índice del sprite dentro del SpriteManager
+    Sprite(usize),
+    WallColumn(QueuedWallColumn),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TranslucentItem {
+    pub distance: f32,
+    pub kind: TranslucentKind,
+}
+
+/// Lista unificada de sprites y columnas de vidrio de un frame. Se llena
+/// durante el pase de paredes y se resuelve en un solo pase de atrás hacia
+/// adelante, así un sprite detrás de un vidrio queda cubierto por él.
+pub struct TranslucentQueue {
+    items: Vec<TranslucentItem>,
+}
+
+impl TranslucentQueue {
+    pub fn new() -> Self {
+        TranslucentQueue { items: Vec::new() }
+    }
+
+    pub fn push(&mut self, distance: f32, kind: TranslucentKind) {
+        self.items.push(TranslucentItem { distance, kind });
+    }
+
+    /// Devuelve los elementos del más lejano al más cercano y vacía la cola
+    pub fn drain_back_to_front(&mut self) -> Vec<TranslucentItem> {
+        self.items.sort_by(|a, b| b.distance.total_cmp(&a.distance));
+        std::mem::take(&mut self.items)
+    }
+}
@@ -1,18 +1,33 @@
 // ui.rs - Sistema de UI optimizado
 
 use raylib::prelude::*;
+use std::collections::{HashSet, VecDeque};
+use crate::caster::cast_ray_textured;
 use crate::framebuffer::Framebuffer;
-use crate::maze::Maze;
+use crate::line::{draw_line, draw_line_clipped};
+use crate::maze::{Maze, MinimapTile};
 use crate::player::Player;
-use crate::game_state::GameData;
+use crate::game_state::{Difficulty, GameData, DAMAGE_FLASH_SECONDS, POISON_DELAY_SECONDS};
+use crate::palette::Palette;
+use crate::score::LevelScore;
 
 // Bitmap font 5x7 para caracteres ASCII básicos
 const FONT_WIDTH: u32 = 5;
 const FONT_HEIGHT: u32 = 7;
 
-// Función para obtener bitmap de caracteres esenciales
+// Avance horizontal de un carácter a escala 1 (glifo más un pixel de separación)
+const FONT_ADVANCE: u32 = FONT_WIDTH + 1;
+
+// Bitmap de cada carácter; las vocales con tilde usan la letra sin tilde y lo
+// desconocido se dibuja como una caja
 fn get_char_bitmap(c: char) -> [u8; 35] {
-    match c.to_ascii_uppercase() {
+    let c = match c {
+        'Á' => 'A', 'É' => 'E', 'Í' => 'I', 'Ó' => 'O', 'Ú' | 'Ü' => 'U',
+        'á' => 'a', 'é' => 'e', 'í' => 'i', 'ó' => 'o', 'ú' | 'ü' => 'u',
+        other => other,
+    };
+
+    match c {
         'A' => [0,1,1,1,0, 1,0,0,0,1, 1,0,0,0,1, 1,1,1,1,1, 1,0,0,0,1, 1,0,0,0,1, 1,0,0,0,1],
         'B' => [1,1,1,1,0, 1,0,0,0,1, 1,0,0,0,1, 1,1,1,1,0, 1,0,0,0,1, 1,0,0,0,1, 1,1,1,1,0],
         'C' => [0,1,1,1,0, 1,0,0,0,1, 1,0,0,0,0, 1,0,0,0,0, 1,0,0,0,0, 1,0,0,0,1, 0,1,1,1,0],
@@ -22,12 +37,15 @@ fn get_char_bitmap(c: char) -> [u8; 35] {
         'G' => [0,1,1,1,0, 1,0,0,0,1, 1,0,0,0,0, 1,0,1,1,1, 1,0,0,0,1, 1,0,0,0,1, 0,1,1,1,0],
         'H' => [1,0,0,0,1, 1,0,0,0,1, 1,0,0,0,1, 1,1,1,1,1, 1,0,0,0,1, 1,0,0,0,1, 1,0,0,0,1],
         'I' => [1,1,1,1,1, 0,0,1,0,0, 0,0,1,0,0, 0,0,1,0,0, 0,0,1,0,0, 0,0,1,0,0, 1,1,1,1,1],
+        'J' => [0,0,1,1,1, 0,0,0,1,0, 0,0,0,1,0, 0,0,0,1,0, 0,0,0,1,0, 1,0,0,1,0, 0,1,1,0,0],
         'K' => [1,0,0,0,1, 1,0,0,1,0, 1,0,1,0,0, 1,1,0,0,0, 1,0,1,0,0, 1,0,0,1,0, 1,0,0,0,1],
         'L' => [1,0,0,0,0, 1,0,0,0,0, 1,0,0,0,0, 1,0,0,0,0, 1,0,0,0,0, 1,0,0,0,0, 1,1,1,1,1],
         'M' => [1,0,0,0,1, 1,1,0,1,1, 1,0,1,0,1, 1,0,1,0,1, 1,0,0,0,1, 1,0,0,0,1, 1,0,0,0,1],
         'N' => [1,0,0,0,1, 1,1,0,0,1, 1,0,1,0,1, 1,0,1,0,1, 1,0,0,1,1, 1,0,0,0,1, 1,0,0,0,1],
+        'Ñ' => [0,1,1,0,1, 1,0,1,1,0, 1,0,0,0,1, 1,1,0,0,1, 1,0,1,0,1, 1,0,0,1,1, 1,0,0,0,1],
         'O' => [0,1,1,1,0, 1,0,0,0,1, 1,0,0,0,1, 1,0,0,0,1, 1,0,0,0,1, 1,0,0,0,1, 0,1,1,1,0],
         'P' => [1,1,1,1,0, 1,0,0,0,1, 1,0,0,0,1, 1,1,1,1,0, 1,0,0,0,0, 1,0,0,0,0, 1,0,0,0,0],
+        'Q' => [0,1,1,1,0, 1,0,0,0,1, 1,0,0,0,1, 1,0,0,0,1, 1,0,1,0,1, 1,0,0,1,0, 0,1,1,0,1],
         'R' => [1,1,1,1,0, 1,0,0,0,1, 1,0,0,0,1, 1,1,1,1,0, 1,0,1,0,0, 1,0,0,1,0, 1,0,0,0,1],
         'S' => [0,1,1,1,1, 1,0,0,0,0, 1,0,0,0,0, 0,1,1,1,0, 0,0,0,0,1, 0,0,0,0,1, 1,1,1,1,0],
         'T' => [1,1,1,1,1, 0,0,1,0,0, 0,0,1,0,0, 0,0,1,0,0, 0,0,1,0,0, 0,0,1,0,0, 0,0,1,0,0],
@@ -47,56 +65,206 @@ fn get_char_bitmap(c: char) -> [u8; 35] {
         '7' => [1,1,1,1,1, 0,0,0,0,1, 0,0,0,1,0, 0,0,1,0,0, 0,1,0,0,0, 0,1,0,0,0, 0,1,0,0,0],
         '8' => [0,1,1,1,0, 1,0,0,0,1, 1,0,0,0,1, 0,1,1,1,0, 1,0,0,0,1, 1,0,0,0,1, 0,1,1,1,0],
         '9' => [0,1,1,1,0, 1,0,0,0,1, 1,0,0,0,1, 0,1,1,1,1, 0,0,0,0,1, 1,0,0,0,1, 0,1,1,1,0],
+        'a' => [0,0,0,0,0, 0,0,0,0,0, 0,1,1,1,0, 0,0,0,0,1, 0,1,1,1,1, 1,0,0,0,1, 0,1,1,1,1],
+        'b' => [1,0,0,0,0, 1,0,0,0,0, 1,0,1,1,0, 1,1,0,0,1, 1,0,0,0,1, 1,0,0,0,1, 1,1,1,1,0],
+        'c' => [0,0,0,0,0, 0,0,0,0,0, 0,1,1,1,0, 1,0,0,0,0, 1,0,0,0,0, 1,0,0,0,1, 0,1,1,1,0],
+        'd' => [0,0,0,0,1, 0,0,0,0,1, 0,1,1,0,1, 1,0,0,1,1, 1,0,0,0,1, 1,0,0,0,1, 0,1,1,1,1],
+        'e' => [0,0,0,0,0, 0,0,0,0,0, 0,1,1,1,0, 1,0,0,0,1, 1,1,1,1,1, 1,0,0,0,0, 0,1,1,1,0],
+        'f' => [0,0,1,1,0, 0,1,0,0,1, 0,1,0,0,0, 1,1,1,0,0, 0,1,0,0,0, 0,1,0,0,0, 0,1,0,0,0],
+        'g' => [0,0,0,0,0, 0,1,1,1,1, 1,0,0,0,1, 1,0,0,0,1, 0,1,1,1,1, 0,0,0,0,1, 0,1,1,1,0],
+        'h' => [1,0,0,0,0, 1,0,0,0,0, 1,0,1,1,0, 1,1,0,0,1, 1,0,0,0,1, 1,0,0,0,1, 1,0,0,0,1],
+        'i' => [0,0,1,0,0, 0,0,0,0,0, 0,1,1,0,0, 0,0,1,0,0, 0,0,1,0,0, 0,0,1,0,0, 0,1,1,1,0],
+        'j' => [0,0,0,1,0, 0,0,0,0,0, 0,0,1,1,0, 0,0,0,1,0, 0,0,0,1,0, 1,0,0,1,0, 0,1,1,0,0],
+        'k' => [1,0,0,0,0, 1,0,0,0,0, 1,0,0,1,0, 1,0,1,0,0, 1,1,0,0,0, 1,0,1,0,0, 1,0,0,1,0],
+        'l' => [0,1,1,0,0, 0,0,1,0,0, 0,0,1,0,0, 0,0,1,0,0, 0,0,1,0,0, 0,0,1,0,0, 0,1,1,1,0],
+        'm' => [0,0,0,0,0, 0,0,0,0,0, 1,1,0,1,0, 1,0,1,0,1, 1,0,1,0,1, 1,0,0,0,1, 1,0,0,0,1],
+        'n' => [0,0,0,0,0, 0,0,0,0,0, 1,0,1,1,0, 1,1,0,0,1, 1,0,0,0,1, 1,0,0,0,1, 1,0,0,0,1],
+        'ñ' => [0,1,1,0,1, 1,0,1,1,0, 0,0,0,0,0, 1,0,1,1,0, 1,1,0,0,1, 1,0,0,0,1, 1,0,0,0,1],
+        'o' => [0,0,0,0,0, 0,0,0,0,0, 0,1,1,1,0, 1,0,0,0,1, 1,0,0,0,1, 1,0,0,0,1, 0,1,1,1,0],
+        'p' => [0,0,0,0,0, 0,0,0,0,0, 1,1,1,1,0, 1,0,0,0,1, 1,1,1,1,0, 1,0,0,0,0, 1,0,0,0,0],
+        'q' => [0,0,0,0,0, 0,0,0,0,0, 0,1,1,0,1, 1,0,0,1,1, 0,1,1,1,1, 0,0,0,0,1, 0,0,0,0,1],
+        'r' => [0,0,0,0,0, 0,0,0,0,0, 1,0,1,1,0, 1,1,0,0,1, 1,0,0,0,0, 1,0,0,0,0, 1,0,0,0,0],
+        's' => [0,0,0,0,0, 0,0,0,0,0, 0,1,1,1,0, 1,0,0,0,0, 0,1,1,1,0, 0,0,0,0,1, 1,1,1,1,0],
+        't' => [0,1,0,0,0, 0,1,0,0,0, 1,1,1,0,0, 0,1,0,0,0, 0,1,0,0,0, 0,1,0,0,1, 0,0,1,1,0],
+        'u' => [0,0,0,0,0, 0,0,0,0,0, 1,0,0,0,1, 1,0,0,0,1, 1,0,0,0,1, 1,0,0,1,1, 0,1,1,0,1],
+        'v' => [0,0,0,0,0, 0,0,0,0,0, 1,0,0,0,1, 1,0,0,0,1, 1,0,0,0,1, 0,1,0,1,0, 0,0,1,0,0],
+        'w' => [0,0,0,0,0, 0,0,0,0,0, 1,0,0,0,1, 1,0,0,0,1, 1,0,1,0,1, 1,0,1,0,1, 0,1,0,1,0],
+        'x' => [0,0,0,0,0, 0,0,0,0,0, 1,0,0,0,1, 0,1,0,1,0, 0,0,1,0,0, 0,1,0,1,0, 1,0,0,0,1],
+        'y' => [0,0,0,0,0, 0,0,0,0,0, 1,0,0,0,1, 1,0,0,0,1, 0,1,1,1,1, 0,0,0,0,1, 0,1,1,1,0],
+        'z' => [0,0,0,0,0, 0,0,0,0,0, 1,1,1,1,1, 0,0,0,1,0, 0,0,1,0,0, 0,1,0,0,0, 1,1,1,1,1],
         ':' => [0,0,0,0,0, 0,0,1,0,0, 0,0,1,0,0, 0,0,0,0,0, 0,0,1,0,0, 0,0,1,0,0, 0,0,0,0,0],
+        '.' => [0,0,0,0,0, 0,0,0,0,0, 0,0,0,0,0, 0,0,0,0,0, 0,0,0,0,0, 0,0,0,0,0, 0,0,1,0,0],
         '!' => [0,0,1,0,0, 0,0,1,0,0, 0,0,1,0,0, 0,0,1,0,0, 0,0,1,0,0, 0,0,0,0,0, 0,0,1,0,0],
         '/' => [0,0,0,0,1, 0,0,0,1,0, 0,0,0,1,0, 0,0,1,0,0, 0,1,0,0,0, 0,1,0,0,0, 1,0,0,0,0],
         '-' => [0,0,0,0,0, 0,0,0,0,0, 0,0,0,0,0, 1,1,1,1,1, 0,0,0,0,0, 0,0,0,0,0, 0,0,0,0,0],
         '(' => [0,0,0,1,0, 0,0,1,0,0, 0,1,0,0,0, 0,1,0,0,0, 0,1,0,0,0, 0,0,1,0,0, 0,0,0,1,0],
         ')' => [0,1,0,0,0, 0,0,1,0,0, 0,0,0,1,0, 0,0,0,1,0, 0,0,0,1,0, 0,0,1,0,0, 0,1,0,0,0],
+        ',' => [0,0,0,0,0, 0,0,0,0,0, 0,0,0,0,0, 0,0,0,0,0, 0,0,1,0,0, 0,0,1,0,0, 0,1,0,0,0],
+        ';' => [0,0,0,0,0, 0,0,1,0,0, 0,0,1,0,0, 0,0,0,0,0, 0,0,1,0,0, 0,0,1,0,0, 0,1,0,0,0],
+        '\'' => [0,0,1,0,0, 0,0,1,0,0, 0,1,0,0,0, 0,0,0,0,0, 0,0,0,0,0, 0,0,0,0,0, 0,0,0,0,0],
+        '"' => [0,1,0,1,0, 0,1,0,1,0, 0,0,0,0,0, 0,0,0,0,0, 0,0,0,0,0, 0,0,0,0,0, 0,0,0,0,0],
+        '?' => [0,1,1,1,0, 1,0,0,0,1, 0,0,0,0,1, 0,0,0,1,0, 0,0,1,0,0, 0,0,0,0,0, 0,0,1,0,0],
+        '¿' => [0,0,1,0,0, 0,0,0,0,0, 0,0,1,0,0, 0,1,0,0,0, 1,0,0,0,0, 1,0,0,0,1, 0,1,1,1,0],
+        '¡' => [0,0,1,0,0, 0,0,0,0,0, 0,0,1,0,0, 0,0,1,0,0, 0,0,1,0,0, 0,0,1,0,0, 0,0,1,0,0],
+        '%' => [1,1,0,0,0, 1,1,0,0,1, 0,0,0,1,0, 0,0,1,0,0, 0,1,0,0,0, 1,0,0,1,1, 0,0,0,1,1],
+        '+' => [0,0,0,0,0, 0,0,1,0,0, 0,0,1,0,0, 1,1,1,1,1, 0,0,1,0,0, 0,0,1,0,0, 0,0,0,0,0],
+        '=' => [0,0,0,0,0, 0,0,0,0,0, 1,1,1,1,1, 0,0,0,0,0, 1,1,1,1,1, 0,0,0,0,0, 0,0,0,0,0],
+        '<' => [0,0,0,1,0, 0,0,1,0,0, 0,1,0,0,0, 1,0,0,0,0, 0,1,0,0,0, 0,0,1,0,0, 0,0,0,1,0],
+        '>' => [0,1,0,0,0, 0,0,1,0,0, 0,0,0,1,0, 0,0,0,0,1, 0,0,0,1,0, 0,0,1,0,0, 0,1,0,0,0],
+        '_' => [0,0,0,0,0, 0,0,0,0,0, 0,0,0,0,0, 0,0,0,0,0, 0,0,0,0,0, 0,0,0,0,0, 1,1,1,1,1],
+        '#' => [0,1,0,1,0, 0,1,0,1,0, 1,1,1,1,1, 0,1,0,1,0, 1,1,1,1,1, 0,1,0,1,0, 0,1,0,1,0],
+        '*' => [0,0,0,0,0, 0,0,1,0,0, 1,0,1,0,1, 0,1,1,1,0, 1,0,1,0,1, 0,0,1,0,0, 0,0,0,0,0],
+        '[' => [0,1,1,1,0, 0,1,0,0,0, 0,1,0,0,0, 0,1,0,0,0, 0,1,0,0,0, 0,1,0,0,0, 0,1,1,1,0],
+        ']' => [0,1,1,1,0, 0,0,0,1,0, 0,0,0,1,0, 0,0,0,1,0, 0,0,0,1,0, 0,0,0,1,0, 0,1,1,1,0],
         ' ' => [0,0,0,0,0, 0,0,0,0,0, 0,0,0,0,0, 0,0,0,0,0, 0,0,0,0,0, 0,0,0,0,0, 0,0,0,0,0],
         _ => [1,1,1,1,1, 1,0,0,0,1, 1,0,1,0,1, 1,0,0,0,1, 1,0,1,0,1, 1,0,0,0,1, 1,1,1,1,1],
     }
 }
 
-fn render_char(framebuffer: &mut Framebuffer, c: char, x: u32, y: u32) {
+// Cada pixel del glifo se dibuja como un cuadrado de `scale` x `scale`
+fn render_char(framebuffer: &mut Framebuffer, c: char, x: u32, y: u32, scale: u32) {
     let bitmap = get_char_bitmap(c);
     
     for row in 0..FONT_HEIGHT {
         for col in 0..FONT_WIDTH {
             let index = (row * FONT_WIDTH + col) as usize;
             if bitmap[index] == 1 {
-                if x + col < framebuffer.width && y + row < framebuffer.height {
-                    framebuffer.set_pixel(x + col, y + row);
+                for dy in 0..scale {
+                    for dx in 0..scale {
+                        let px = x + col * scale + dx;
+                        let py = y + row * scale + dy;
+                        if px < framebuffer.width && py < framebuffer.height {
+                            framebuffer.set_pixel(px, py);
+                        }
+                    }
                 }
             }
         }
     }
 }
 
-pub fn render_fps(framebuffer: &mut Framebuffer, fps: f32) {
+/// Ancho en pixeles de `text` a la escala dada, sin el espacio tras el último carácter
+pub fn text_width(text: &str, scale: u32) -> u32 {
+    (text.chars().count() as u32 * FONT_ADVANCE * scale).saturating_sub(scale)
+}
+
+pub fn render_fps(framebuffer: &mut Framebuffer, fps: f32, palette: &Palette) {
     framebuffer.set_current_color(Color::new(0, 0, 0, 180));
     for y in 5..25 {
         for x in 5..95 {
             if x < framebuffer.width && y < framebuffer.height {
-                framebuffer.set_pixel(x, y);
+                framebuffer.blend_pixel(x, y);
             }
         }
     }
     
-    let color = if fps >= 50.0 {
-        Color::GREEN
-    } else if fps >= 30.0 {
-        Color::YELLOW
-    } else {
-        Color::RED
-    };
-    
-    framebuffer.set_current_color(color);
-    let fps_text = format!("FPS {:.0}", fps);
+    framebuffer.set_current_color(palette.fps_color(fps));
+    // Marca de texto además del color cuando el rendimiento es bajo
+    let fps_text = if fps < 30.0 { format!("FPS {:.0} LENTO", fps) } else { format!("FPS {:.0}", fps) };
     render_text(framebuffer, &fps_text, 8, 10);
 }
 
-pub fn render_welcome_screen(framebuffer: &mut Framebuffer, window: &RaylibHandle) -> Option<usize> {
+/// Lista de F10 con los assets que no cargaron y usan su fallback
+pub fn render_missing_assets(framebuffer: &mut Framebuffer, missing: &[String], palette: &Palette) {
+    let left = 5;
+    let top = 85;
+    let line_height = FONT_HEIGHT + 4;
+    let lines = missing.len().max(1) as u32 + 1;
+    let right = framebuffer.width.min(left + 360);
+
+    framebuffer.set_current_color(Color::new(0, 0, 0, 180));
+    for y in top..(top + lines * line_height + 6).min(framebuffer.height) {
+        for x in left..right {
+            framebuffer.blend_pixel(x, y);
+        }
+    }
+
+    framebuffer.set_current_color(palette.caution);
+    render_text(framebuffer, &format!("ASSETS FALTANTES: {}", missing.len()), left + 4, top + 4);
+
+    framebuffer.set_current_color(palette.text);
+    if missing.is_empty() {
+        render_text(framebuffer, "NINGUNO", left + 4, top + 4 + line_height);
+    }
+    for (i, path) in missing.iter().enumerate() {
+        let y = top + 4 + (i as u32 + 1) * line_height;
+        if y + FONT_HEIGHT >= framebuffer.height {
+            break;
+        }
+        render_text_clipped(framebuffer, path, left + 4, y, right - 4, 1);
+    }
+}
+
+// Frames que recuerda el gráfico de FPS y pixeles de ancho por frame
+pub const FPS_GRAPH_FRAMES: usize = 100;
+const FPS_GRAPH_STEP: u32 = 2;
+const FPS_GRAPH_HEIGHT: u32 = 50;
+// Tiempo de frame que llega al techo del gráfico (20 FPS)
+const FPS_GRAPH_MAX_SECONDS: f32 = 0.05;
+
+/// Gráfico de los últimos tiempos de frame (segundos, del más viejo al más
+/// nuevo) debajo del texto de FPS, con las marcas de 60 y 30 FPS; los tirones
+/// se ven como picos hacia arriba
+pub fn render_fps_graph(framebuffer: &mut Framebuffer, frame_times: &VecDeque<f32>, palette: &Palette) {
+    let (left, top) = (5, 27);
+    let width = FPS_GRAPH_FRAMES as u32 * FPS_GRAPH_STEP;
+    let bottom = top + FPS_GRAPH_HEIGHT - 1;
+    let row_of = |seconds: f32| {
+        let fraction = (seconds / FPS_GRAPH_MAX_SECONDS).clamp(0.0, 1.0);
+        bottom as i32 - (fraction * (FPS_GRAPH_HEIGHT - 1) as f32) as i32
+    };
+
+    framebuffer.set_current_color(Color::new(0, 0, 0, 180));
+    for y in top..top + FPS_GRAPH_HEIGHT {
+        for x in left..left + width {
+            framebuffer.blend_pixel(x, y);
+        }
+    }
+
+    // Líneas de referencia punteadas
+    framebuffer.set_current_color(palette.muted);
+    for (fps, label) in [(60.0, "60"), (30.0, "30")] {
+        let y = row_of(1.0 / fps) as u32;
+        for x in (left..left + width).step_by(4) {
+            framebuffer.set_pixel(x, y);
+        }
+        render_text(framebuffer, label, left + width + 3, y.saturating_sub(FONT_HEIGHT / 2));
+    }
+
+    // Los frames más nuevos quedan a la derecha
+    let first_x = (left + width - frame_times.len() as u32 * FPS_GRAPH_STEP) as i32;
+    let points: Vec<(i32, i32, f32)> = frame_times
+        .iter()
+        .enumerate()
+        .map(|(i, &seconds)| (first_x + (i as u32 * FPS_GRAPH_STEP) as i32, row_of(seconds), seconds))
+        .collect();
+    let clip = (left as i32, top as i32, (left + width) as i32, (top + FPS_GRAPH_HEIGHT) as i32);
+    for pair in points.windows(2) {
+        let ((x0, y0, before), (x1, y1, after)) = (pair[0], pair[1]);
+        // Cada tramo toma el color del más lento de sus dos frames
+        let seconds = before.max(after).max(f32::EPSILON);
+        framebuffer.set_current_color(palette.fps_color(1.0 / seconds));
+        draw_line_clipped(framebuffer, x0, y0, x1, y1, 1, clip);
+    }
+}
+
+// Ventana más chica que se permite al redimensionar: por debajo los menús
+// centrados y el minimapa ya no caben junto al HUD
+pub const MIN_WINDOW_WIDTH: u32 = 800;
+pub const MIN_WINDOW_HEIGHT: u32 = 600;
+
+/// Pantalla de bienvenida. `selected_index` es el nivel resaltado (0-2), compartido
+/// entre teclado y mouse y persistente entre frames.
+/// `hardcore` se alterna con H y `difficulty` se cambia con D; `hardcore_cleared`
+/// muestra la marca de haber terminado alguna vez los tres niveles en hardcore
+pub fn render_welcome_screen(
+    framebuffer: &mut Framebuffer,
+    window: &RaylibHandle,
+    selected_index: &mut usize,
+    hardcore: &mut bool,
+    difficulty: &mut Difficulty,
+    hardcore_cleared: bool,
+) -> Option<usize> {
     render_gradient_background(
         framebuffer,
         Color::new(10, 15, 25, 255),
@@ -134,7 +302,7 @@ pub fn render_welcome_screen(framebuffer: &mut Framebuffer, window: &RaylibHandl
         center_x - 200,
         menu_start_y - 20,
         400,
-        160,
+        205,
         Color::new(80, 80, 120, 255)
     );
     
@@ -145,19 +313,85 @@ pub fn render_welcome_screen(framebuffer: &mut Framebuffer, window: &RaylibHandl
         Color::new(255, 215, 0, 255),
         Color::new(255, 69, 0, 255),
         Color::new(138, 43, 226, 255),
+        Color::new(60, 200, 180, 255),
     ];
     
     let level_descriptions = [
         "1 - THE YELLOW HALLS     (PRINCIPIANTE)",
         "2 - THE RED CHAMBERS     (INTERMEDIO)", 
-        "3 - THE FINAL ESCAPE     (AVANZADO)"
+        "3 - THE FINAL ESCAPE     (AVANZADO)",
+        "4 - RANDOM MAZE          (ALEATORIO)",
     ];
     
+    let entry_x = center_x - 190;
+    let entry_width = 380;
+    let entry_y = |i: usize| menu_start_y + 40 + (i as u32 * 25);
+    
+    // Navegación con flechas (con wrap) y hover del mouse sobre la misma selección
+    let level_count = level_descriptions.len();
+    let mouse = (window.get_mouse_x(), window.get_mouse_y());
+    let hovered = (0..level_count).find(|&i| {
+        let top = entry_y(i) as i32 - 6;
+        let inside_x = mouse.0 >= entry_x as i32 && mouse.0 <= (entry_x + entry_width) as i32;
+        inside_x && mouse.1 >= top && mouse.1 < top + 20
+    });
+    *selected_index = menu_selection(
+        *selected_index,
+        level_count,
+        window.is_key_pressed(KeyboardKey::KEY_UP) || window.is_key_pressed(KeyboardKey::KEY_W),
+        window.is_key_pressed(KeyboardKey::KEY_DOWN) || window.is_key_pressed(KeyboardKey::KEY_S),
+        hovered,
+        window.get_mouse_delta() != Vector2::zero(),
+    );
+    
     for (i, (desc, color)) in level_descriptions.iter().zip(level_colors.iter()).enumerate() {
+        let level_y = entry_y(i);
+        
+        // Resaltar la entrada seleccionada
+        if i == *selected_index {
+            framebuffer.set_current_color(Color::new(50, 50, 80, 255));
+            for y in (level_y - 5)..(level_y + 12) {
+                for x in entry_x..(entry_x + entry_width) {
+                    if x < framebuffer.width && y < framebuffer.height {
+                        framebuffer.set_pixel(x, y);
+                    }
+                }
+            }
+            render_border_frame(framebuffer, entry_x, level_y - 6, entry_width, 18, *color);
+        }
+        
         framebuffer.set_current_color(*color);
-        let level_y = menu_start_y + 40 + (i as u32 * 25);
         render_text_centered(framebuffer, desc, level_y, 1);
     }
+
+    // Modo hardcore: una vida para los tres niveles
+    if window.is_key_pressed(KeyboardKey::KEY_H) {
+        *hardcore = !*hardcore;
+    }
+    if *hardcore {
+        framebuffer.set_current_color(Color::new(255, 60, 60, 255));
+        render_text_centered(framebuffer, "H - HARDCORE: SI (1 VIDA - DESDE EL NIVEL 1)", entry_y(level_count) + 15, 1);
+    } else {
+        framebuffer.set_current_color(Color::new(150, 150, 150, 255));
+        render_text_centered(framebuffer, "H - HARDCORE: NO", entry_y(level_count) + 15, 1);
+    }
+
+    // Dificultad: vidas iniciales, trampas y velocidad de las entidades
+    if window.is_key_pressed(KeyboardKey::KEY_D) {
+        *difficulty = difficulty.next();
+    }
+    framebuffer.set_current_color(match *difficulty {
+        Difficulty::Easy => Color::new(120, 220, 120, 255),
+        Difficulty::Normal => Color::new(200, 200, 250, 255),
+        Difficulty::Hard => Color::new(255, 140, 60, 255),
+    });
+    let difficulty_text = format!("D - DIFICULTAD: {}", difficulty.label());
+    render_text_centered(framebuffer, &difficulty_text, entry_y(level_count) + 32, 1);
+
+    if hardcore_cleared {
+        framebuffer.set_current_color(Color::new(255, 215, 0, 255));
+        render_text_centered(framebuffer, "HARDCORE COMPLETADO", menu_start_y - 40, 1);
+    }
     
     // Controles
     let controls_y = framebuffer.height - 180;
@@ -177,7 +411,7 @@ pub fn render_welcome_screen(framebuffer: &mut Framebuffer, window: &RaylibHandl
         "WASD / ARROWS - MOVERTE Y ROTAR",
         "MOUSE         - MIRAR ALREDEDOR",
         "R             - RESETEAR NIVEL",
-        "",
+        "ESC / P       - PAUSA",
         "JUNTA TODAS LAS MONEDAS Y DESBLOQUEA EL PORTAL!",
         "CUIDADO CON LAS TRAMPAS"
     ];
@@ -193,29 +427,155 @@ pub fn render_welcome_screen(framebuffer: &mut Framebuffer, window: &RaylibHandl
     // Prompt de inicio con animación
     let animation_offset = ((window.get_time() * 3.0).sin() * 10.0) as i32;
     framebuffer.set_current_color(Color::new(100 + animation_offset.abs() as u8, 255, 100, 255));
-    render_text_centered(framebuffer, "PRESIONA 1, 2 O 3 PARA ELEGIR UN NIVEL", framebuffer.height - 40, 1);
+    render_text_centered(framebuffer, "FLECHAS Y ENTER, O 1 A 4 PARA ELEGIR UN NIVEL", framebuffer.height - 40, 1);
     
-    // Detectar entrada de teclado
+    // Confirmar la selección resaltada, o la entrada sobre la que se hizo click
+    if let Some(entry) = hovered.filter(|_| window.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT)) {
+        return Some(entry + 1);
+    }
+    if window.is_key_pressed(KeyboardKey::KEY_ENTER) || window.is_key_pressed(KeyboardKey::KEY_KP_ENTER) {
+        return Some(*selected_index + 1);
+    }
+    
+    // Atajos numéricos
     if window.is_key_pressed(KeyboardKey::KEY_ONE) {
         return Some(1);
     } else if window.is_key_pressed(KeyboardKey::KEY_TWO) {
         return Some(2);
     } else if window.is_key_pressed(KeyboardKey::KEY_THREE) {
         return Some(3);
+    } else if window.is_key_pressed(KeyboardKey::KEY_FOUR) {
+        return Some(4);
     }
     
     None
 }
 
-pub fn render_game_over(framebuffer: &mut Framebuffer, window: &RaylibHandle) -> bool {
+// Selección de un menú tras la entrada de un frame. Las flechas mueven con
+// wrap; la entrada bajo el cursor solo se toma si el mouse se movió este
+// frame, así un cursor quieto sobre el menú no deshace las flechas
+fn menu_selection(selected: usize, count: usize, up: bool, down: bool, hovered: Option<usize>, mouse_moved: bool) -> usize {
+    let mut selected = selected.min(count - 1);
+    if up {
+        selected = (selected + count - 1) % count;
+    }
+    if down {
+        selected = (selected + 1) % count;
+    }
+    hovered.filter(|_| mouse_moved).unwrap_or(selected)
+}
+
+/// Cuenta atrás grande y centrada sobre el mundo; 0 muestra el "GO!"
+pub fn render_level_intro(framebuffer: &mut Framebuffer, countdown: u32) {
+    let center_y = framebuffer.height / 2;
+
+    if countdown == 0 {
+        framebuffer.set_current_color(Color::new(20, 20, 20, 255));
+        render_text_centered(framebuffer, "GO!", center_y - 27, 8);
+        framebuffer.set_current_color(Color::new(120, 255, 120, 255));
+        render_text_centered(framebuffer, "GO!", center_y - 30, 8);
+        return;
+    }
+
+    let number = countdown.to_string();
+    framebuffer.set_current_color(Color::new(20, 20, 20, 255));
+    render_text_centered(framebuffer, "GET READY", center_y - 77, 4);
+    render_text_centered(framebuffer, &number, center_y - 17, 8);
+    framebuffer.set_current_color(Color::new(220, 200, 100, 255));
+    render_text_centered(framebuffer, "GET READY", center_y - 80, 4);
+    framebuffer.set_current_color(Color::WHITE);
+    render_text_centered(framebuffer, &number, center_y - 20, 8);
+}
+
+/// Bordes verdes que laten mientras el jugador está envenenado; se cierran
+/// sobre la pantalla a medida que se acaba el plazo del veneno
+pub fn render_poison_tint(framebuffer: &mut Framebuffer, remaining: f32, animation_time: f32) {
+    let urgency = 1.0 - (remaining / POISON_DELAY_SECONDS).clamp(0.0, 1.0);
+    let pulse = (animation_time * (4.0 + urgency * 8.0)).sin() * 0.5 + 0.5;
+    let max_alpha = 60.0 + urgency * 80.0 + pulse * 30.0;
+    // Fracción del centro a los bordes donde empieza el tinte
+    let inner = 0.7 - urgency * 0.4;
+
+    let half_width = framebuffer.width as f32 * 0.5;
+    let half_height = framebuffer.height as f32 * 0.5;
+    for y in 0..framebuffer.height {
+        let dy = (y as f32 - half_height).abs() / half_height;
+        for x in 0..framebuffer.width {
+            let dx = (x as f32 - half_width).abs() / half_width;
+            let edge = (dx.max(dy) - inner) / (1.0 - inner);
+            if edge <= 0.0 {
+                continue;
+            }
+
+            framebuffer.set_current_color(Color::new(60, 200, 40, (max_alpha * edge.min(1.0)) as u8));
+            framebuffer.blend_pixel(x, y);
+        }
+    }
+}
+
+/// Destello rojo al perder una vida: cubre toda la pantalla, más fuerte en los
+/// bordes, y se apaga a medida que `remaining` llega a cero
+pub fn render_damage_flash(framebuffer: &mut Framebuffer, remaining: f32) {
+    let strength = (remaining / DAMAGE_FLASH_SECONDS).clamp(0.0, 1.0);
+    if strength <= 0.0 {
+        return;
+    }
+
+    let half_width = framebuffer.width as f32 * 0.5;
+    let half_height = framebuffer.height as f32 * 0.5;
+    for y in 0..framebuffer.height {
+        let dy = (y as f32 - half_height) / half_height;
+        for x in 0..framebuffer.width {
+            let dx = (x as f32 - half_width) / half_width;
+            let edge = ((dx * dx + dy * dy).sqrt() / std::f32::consts::SQRT_2).min(1.0);
+            let alpha = strength * (50.0 + 130.0 * edge);
+
+            framebuffer.set_current_color(Color::new(200, 0, 0, alpha as u8));
+            framebuffer.blend_pixel(x, y);
+        }
+    }
+}
+
+pub fn render_loading_screen(framebuffer: &mut Framebuffer, label: &str, progress: f32) {
     render_gradient_background(
         framebuffer,
-        Color::new(60, 10, 10, 255),
-        Color::new(30, 5, 5, 255)
+        Color::new(10, 15, 25, 255),
+        Color::new(40, 30, 50, 255)
     );
     
+    let center_x = framebuffer.width / 2;
     let center_y = framebuffer.height / 2;
     
+    framebuffer.set_current_color(Color::new(220, 200, 100, 255));
+    render_text_centered(framebuffer, label, center_y - 60, 3);
+    
+    // Barra de progreso
+    let bar_width = 400;
+    let bar_height = 20;
+    let bar_x = center_x.saturating_sub(bar_width / 2);
+    let bar_y = center_y;
+    
+    render_border_frame(framebuffer, bar_x, bar_y, bar_width, bar_height, Color::new(100, 100, 150, 255));
+    
+    let filled_width = ((bar_width - 4) as f32 * progress.clamp(0.0, 1.0)) as u32;
+    framebuffer.set_current_color(Color::new(100, 255, 100, 255));
+    for y in (bar_y + 2)..(bar_y + bar_height - 1) {
+        for x in (bar_x + 2)..(bar_x + 2 + filled_width) {
+            if x < framebuffer.width && y < framebuffer.height {
+                framebuffer.set_pixel(x, y);
+            }
+        }
+    }
+}
+
+/// Pantalla de derrota dibujada sobre el último frame del nivel, oscurecido
+pub fn render_game_over(framebuffer: &mut Framebuffer, window: &RaylibHandle) -> bool {
+    let (width, height) = (framebuffer.width, framebuffer.height);
+    render_translucent_rect(framebuffer, 0, 0, width, height, Color::new(40, 5, 5, 170));
+    
+    let center_y = framebuffer.height / 2;
+    
+    render_translucent_rect(framebuffer, 100, center_y - 100, width - 200, 200, Color::new(20, 0, 0, 200));
     render_border_frame(
         framebuffer,
         100,
@@ -243,15 +603,34 @@ pub fn render_game_over(framebuffer: &mut Framebuffer, window: &RaylibHandle) ->
     window.is_key_pressed(KeyboardKey::KEY_SPACE)
 }
 
-pub fn render_victory(framebuffer: &mut Framebuffer, window: &RaylibHandle, level: usize) -> bool {
-    render_gradient_background(
-        framebuffer,
-        Color::new(10, 60, 10, 255),
-        Color::new(5, 30, 5, 255)
-    );
+/// Tiempo de nivel como MM:SS.mmm
+pub fn format_level_time(seconds: f32) -> String {
+    let millis = (seconds.max(0.0) * 1000.0).round() as u64;
+    format!("{:02}:{:02}.{:03}", millis / 60_000, millis / 1000 % 60, millis % 1000)
+}
+
+/// Resultados del nivel que muestra la pantalla de victoria
+#[derive(Debug, Clone, Copy)]
+pub struct VictorySummary {
+    pub level: usize,
+    pub level_time: f32,
+    // Récord guardado del nivel, si ya había uno
+    pub best_time: Option<f32>,
+    pub level_score: Option<LevelScore>,
+    // Puntos de toda la partida y récord guardado
+    pub run_score: i32,
+    pub high_score: i32,
+}
+
+/// Pantalla de victoria dibujada sobre el último frame del nivel, oscurecido
+pub fn render_victory(framebuffer: &mut Framebuffer, window: &RaylibHandle, summary: &VictorySummary) -> bool {
+    let VictorySummary { level, level_time, best_time, level_score, run_score, high_score } = *summary;
+    let (width, height) = (framebuffer.width, framebuffer.height);
+    render_translucent_rect(framebuffer, 0, 0, width, height, Color::new(5, 40, 5, 170));
     
     let center_y = framebuffer.height / 2;
     
+    render_translucent_rect(framebuffer, 100, center_y - 120, width - 200, 240, Color::new(0, 20, 0, 200));
     render_border_frame(
         framebuffer,
         100,
@@ -267,30 +646,200 @@ pub fn render_victory(framebuffer: &mut Framebuffer, window: &RaylibHandle, leve
     
     framebuffer.set_current_color(Color::new(100, 255, 100, 255));
     render_text_centered(framebuffer, "NIVEL COMPLETADO!", center_y - 93, 3);
+
+    framebuffer.set_current_color(Color::new(220, 255, 220, 255));
+    let time_text = match best_time {
+        Some(best) => format!("TIEMPO: {}   RECORD: {}", format_level_time(level_time), format_level_time(best)),
+        None => format!("TIEMPO: {}", format_level_time(level_time)),
+    };
+    render_text_centered(framebuffer, &time_text, center_y - 65, 1);
+
+    // Desglose de puntos del nivel y total de la partida
+    if let Some(score) = level_score {
+        framebuffer.set_current_color(Color::new(180, 220, 180, 255));
+        let breakdown = format!(
+            "BASE {}  VIDAS {}  TIEMPO {}  TRAMPAS -{}",
+            score.base, score.lives_bonus, score.time_bonus, score.trap_penalty
+        );
+        render_text_centered(framebuffer, &breakdown, center_y - 50, 1);
+    }
+    framebuffer.set_current_color(Color::new(255, 230, 120, 255));
+    let level_points = level_score.map_or(0, |score| score.total());
+    let score_text = format!("NIVEL: {}   PARTIDA: {}   RECORD: {}", level_points, run_score, high_score.max(run_score));
+    render_text_centered(framebuffer, &score_text, center_y - 35, 1);
     
     framebuffer.set_current_color(Color::new(150, 255, 150, 255));
     if level < 3 {
         let level_text = format!("NIVEL {} COMPLETO!", level);
-        render_text_centered(framebuffer, &level_text, center_y - 30, 2);
-        render_text_centered(framebuffer, "LISTO PARA LO SIGUIENTE?", center_y - 5, 1);
+        render_text_centered(framebuffer, &level_text, center_y - 10, 2);
+        render_text_centered(framebuffer, "LISTO PARA LO SIGUIENTE?", center_y + 15, 1);
         
         let animation_offset = ((window.get_time() * 4.0).sin() * 30.0) as i32;
         framebuffer.set_current_color(Color::new(255, 255, 100 + animation_offset.abs() as u8, 255));
         let next_level_text = format!("PRESIONA ESPACIO PARA INICIAL NIVEL {}", level + 1);
-        render_text_centered(framebuffer, &next_level_text, center_y + 30, 1);
+        render_text_centered(framebuffer, &next_level_text, center_y + 50, 1);
     } else {
-        render_text_centered(framebuffer, "FELICIDADES!", center_y - 30, 2);
-        render_text_centered(framebuffer, "ESCAPASTE DE LOS BACKROOMS!", center_y - 5, 1);
-        render_text_centered(framebuffer, "ERES LIBRE!", center_y + 15, 1);
+        render_text_centered(framebuffer, "FELICIDADES!", center_y - 10, 2);
+        render_text_centered(framebuffer, "ESCAPASTE DE LOS BACKROOMS!", center_y + 15, 1);
+        render_text_centered(framebuffer, "ERES LIBRE!", center_y + 35, 1);
         
         let animation_offset = ((window.get_time() * 4.0).sin() * 30.0) as i32;
         framebuffer.set_current_color(Color::new(255, 255, 100 + animation_offset.abs() as u8, 255));
-        render_text_centered(framebuffer, "PRESIONA ESPACIO PARA VOLVER A IR AL MENU", center_y + 45, 1);
+        render_text_centered(framebuffer, "PRESIONA ESPACIO PARA VOLVER A IR AL MENU", center_y + 65, 1);
     }
     
     window.is_key_pressed(KeyboardKey::KEY_SPACE)
 }
 
+/// Opciones del menú de pausa, en el orden en que se muestran
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PauseOption {
+    Resume,
+    RestartLevel,
+    Settings,
+    MainMenu,
+}
+
+impl PauseOption {
+    pub const ALL: [PauseOption; 4] = [PauseOption::Resume, PauseOption::RestartLevel, PauseOption::Settings, PauseOption::MainMenu];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PauseOption::Resume => "CONTINUAR",
+            PauseOption::RestartLevel => "REINICIAR NIVEL",
+            PauseOption::Settings => "AJUSTES DEL MINIMAPA",
+            PauseOption::MainMenu => "MENU PRINCIPAL",
+        }
+    }
+}
+
+/// Menú de pausa sobre el nivel congelado y oscurecido. ESC o P vuelven al
+/// juego directamente; flechas y ENTER eligen una opción.
+pub fn render_pause_menu(framebuffer: &mut Framebuffer, window: &RaylibHandle, selected_index: &mut usize) -> Option<PauseOption> {
+    let labels: Vec<String> = PauseOption::ALL.iter().map(|option| option.label().to_string()).collect();
+    render_pause_panel(framebuffer, window, "PAUSA", &labels, selected_index, "ESC / P - CONTINUAR");
+
+    if window.is_key_pressed(KeyboardKey::KEY_ESCAPE) || window.is_key_pressed(KeyboardKey::KEY_P) {
+        return Some(PauseOption::Resume);
+    }
+    if window.is_key_pressed(KeyboardKey::KEY_ENTER) || window.is_key_pressed(KeyboardKey::KEY_KP_ENTER) {
+        return Some(PauseOption::ALL[*selected_index]);
+    }
+
+    None
+}
+
+/// Filas de la pantalla de ajustes del minimapa, en el orden en que se muestran
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MinimapSetting {
+    Corner,
+    Zoom,
+    Orientation,
+    Back,
+}
+
+impl MinimapSetting {
+    pub const ALL: [MinimapSetting; 4] = [
+        MinimapSetting::Corner,
+        MinimapSetting::Zoom,
+        MinimapSetting::Orientation,
+        MinimapSetting::Back,
+    ];
+
+    /// Texto de la fila con el valor actual
+    pub fn row_text(self, options: &MinimapOptions) -> String {
+        match self {
+            MinimapSetting::Corner => format!("POSICION: {}", options.corner.label()),
+            MinimapSetting::Zoom => format!("VISTA: {}", options.zoom.label()),
+            MinimapSetting::Orientation => format!("ROTACION: {}", options.orientation.label()),
+            MinimapSetting::Back => "VOLVER".to_string(),
+        }
+    }
+
+    /// Pasa la opción al siguiente valor; VOLVER no cambia nada
+    pub fn apply(self, options: &mut MinimapOptions) {
+        match self {
+            MinimapSetting::Corner => options.corner = options.corner.next(),
+            MinimapSetting::Zoom => options.zoom = options.zoom.toggled(),
+            MinimapSetting::Orientation => options.orientation = options.orientation.toggled(),
+            MinimapSetting::Back => {},
+        }
+    }
+}
+
+/// Ajustes del minimapa dentro de la pausa. ENTER o las flechas laterales
+/// cambian la fila elegida; ESC vuelve al menú de pausa.
+pub fn render_minimap_settings(
+    framebuffer: &mut Framebuffer,
+    window: &RaylibHandle,
+    selected_index: &mut usize,
+    options: &MinimapOptions,
+) -> Option<MinimapSetting> {
+    let rows: Vec<String> = MinimapSetting::ALL.iter().map(|setting| setting.row_text(options)).collect();
+    render_pause_panel(framebuffer, window, "MINIMAPA", &rows, selected_index, "ENTER - CAMBIAR   ESC - VOLVER");
+
+    if window.is_key_pressed(KeyboardKey::KEY_ESCAPE) || window.is_key_pressed(KeyboardKey::KEY_BACKSPACE) {
+        return Some(MinimapSetting::Back);
+    }
+    let change = [KeyboardKey::KEY_ENTER, KeyboardKey::KEY_KP_ENTER, KeyboardKey::KEY_LEFT, KeyboardKey::KEY_RIGHT]
+        .iter()
+        .any(|&key| window.is_key_pressed(key));
+    if change {
+        return Some(MinimapSetting::ALL[*selected_index]);
+    }
+
+    None
+}
+
+// Recuadro de la pausa con título, filas seleccionables y una línea de ayuda
+fn render_pause_panel(
+    framebuffer: &mut Framebuffer,
+    window: &RaylibHandle,
+    title: &str,
+    rows: &[String],
+    selected_index: &mut usize,
+    hint: &str,
+) {
+    let (width, height) = (framebuffer.width, framebuffer.height);
+    render_translucent_rect(framebuffer, 0, 0, width, height, Color::new(5, 5, 15, 170));
+
+    let center_x = framebuffer.width / 2;
+    let center_y = framebuffer.height / 2;
+
+    render_translucent_rect(framebuffer, center_x - 200, center_y - 125, 400, 250, Color::new(10, 10, 30, 200));
+    render_border_frame(framebuffer, center_x - 200, center_y - 125, 400, 250, Color::new(100, 100, 150, 255));
+
+    framebuffer.set_current_color(Color::new(30, 30, 30, 255));
+    render_text_centered(framebuffer, title, center_y - 102, 4);
+    framebuffer.set_current_color(Color::new(220, 200, 100, 255));
+    render_text_centered(framebuffer, title, center_y - 105, 4);
+
+    let option_count = rows.len();
+    *selected_index = (*selected_index).min(option_count - 1);
+    if window.is_key_pressed(KeyboardKey::KEY_UP) || window.is_key_pressed(KeyboardKey::KEY_W) {
+        *selected_index = (*selected_index + option_count - 1) % option_count;
+    }
+    if window.is_key_pressed(KeyboardKey::KEY_DOWN) || window.is_key_pressed(KeyboardKey::KEY_S) {
+        *selected_index = (*selected_index + 1) % option_count;
+    }
+
+    for (i, row) in rows.iter().enumerate() {
+        let option_y = center_y - 45 + i as u32 * 30;
+
+        if i == *selected_index {
+            render_translucent_rect(framebuffer, center_x - 150, option_y - 8, 300, 24, Color::new(50, 50, 80, 255));
+            render_border_frame(framebuffer, center_x - 150, option_y - 8, 300, 24, Color::new(220, 200, 100, 255));
+            framebuffer.set_current_color(Color::WHITE);
+        } else {
+            framebuffer.set_current_color(Color::new(160, 160, 180, 255));
+        }
+        render_text_centered(framebuffer, row, option_y, 1);
+    }
+
+    framebuffer.set_current_color(Color::new(150, 150, 150, 255));
+    render_text_centered(framebuffer, hint, center_y + 100, 1);
+}
+
 pub fn render_hud(framebuffer: &mut Framebuffer, game_data: &GameData) {
     let hud_height = 120;
     let hud_width = 200;
@@ -300,7 +849,7 @@ pub fn render_hud(framebuffer: &mut Framebuffer, game_data: &GameData) {
     for y in 5..hud_height {
         for x in 5..hud_width {
             if x < framebuffer.width && y < framebuffer.height {
-                framebuffer.set_pixel(x, y);
+                framebuffer.blend_pixel(x, y);
             }
         }
     }
@@ -360,38 +909,284 @@ pub fn render_hud(framebuffer: &mut Framebuffer, game_data: &GameData) {
     }
 }
 
+/// Orientación del minimapa: fijo con el norte arriba, o rotando con el jugador
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MinimapOrientation {
+    NorthUp,
+    PlayerUp,
+}
+
+impl MinimapOrientation {
+    pub fn toggled(self) -> Self {
+        match self {
+            MinimapOrientation::NorthUp => MinimapOrientation::PlayerUp,
+            MinimapOrientation::PlayerUp => MinimapOrientation::NorthUp,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            MinimapOrientation::NorthUp => "NORTE ARRIBA",
+            MinimapOrientation::PlayerUp => "GIRA CON EL JUGADOR",
+        }
+    }
+
+    /// Nombre usado en el archivo de settings
+    pub fn name(&self) -> &'static str {
+        match self {
+            MinimapOrientation::NorthUp => "north_up",
+            MinimapOrientation::PlayerUp => "player_up",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [MinimapOrientation::NorthUp, MinimapOrientation::PlayerUp]
+            .into_iter()
+            .find(|orientation| orientation.name() == name)
+    }
+}
+
+/// Escala del minimapa: todo el laberinto, o solo el entorno del jugador ampliado
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MinimapZoom {
+    Full,
+    Local,
+}
+
+impl MinimapZoom {
+    pub fn toggled(self) -> Self {
+        match self {
+            MinimapZoom::Full => MinimapZoom::Local,
+            MinimapZoom::Local => MinimapZoom::Full,
+        }
+    }
+
+    /// Nombre usado en el archivo de settings
+    pub fn name(&self) -> &'static str {
+        match self {
+            MinimapZoom::Full => "full",
+            MinimapZoom::Local => "local",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            MinimapZoom::Full => "COMPLETO",
+            MinimapZoom::Local => "LOCAL",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [MinimapZoom::Full, MinimapZoom::Local]
+            .into_iter()
+            .find(|zoom| zoom.name() == name)
+    }
+}
+
+/// Esquina de la pantalla donde va el minimapa, u oculto
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MinimapCorner {
+    TopRight,
+    TopLeft,
+    BottomLeft,
+    BottomRight,
+    Hidden,
+}
+
+const MINIMAP_SIZE: u32 = 200;
+const MINIMAP_MARGIN: u32 = 15;
+// Pixeles por casilla en la vista local: unas 11 casillas de lado
+const LOCAL_MINIMAP_SCALE: u32 = 18;
+
+impl MinimapCorner {
+    pub const ALL: [MinimapCorner; 5] = [
+        MinimapCorner::TopRight,
+        MinimapCorner::TopLeft,
+        MinimapCorner::BottomLeft,
+        MinimapCorner::BottomRight,
+        MinimapCorner::Hidden,
+    ];
+
+    pub fn next(self) -> Self {
+        let index = MinimapCorner::ALL.iter().position(|&corner| corner == self).unwrap_or(0);
+        MinimapCorner::ALL[(index + 1) % MinimapCorner::ALL.len()]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MinimapCorner::TopRight => "top_right",
+            MinimapCorner::TopLeft => "top_left",
+            MinimapCorner::BottomLeft => "bottom_left",
+            MinimapCorner::BottomRight => "bottom_right",
+            MinimapCorner::Hidden => "hidden",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            MinimapCorner::TopRight => "ARRIBA DERECHA",
+            MinimapCorner::TopLeft => "ARRIBA IZQUIERDA",
+            MinimapCorner::BottomLeft => "ABAJO IZQUIERDA",
+            MinimapCorner::BottomRight => "ABAJO DERECHA",
+            MinimapCorner::Hidden => "OCULTO",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        MinimapCorner::ALL.iter().copied().find(|corner| corner.name() == name)
+    }
+
+    /// Esquina superior izquierda del minimapa. Arriba a la izquierda se
+    /// coloca debajo del HUD (`hud_bottom`) para no taparlo.
+    pub fn origin(&self, screen_width: u32, screen_height: u32, hud_bottom: u32) -> Option<(u32, u32)> {
+        let right = screen_width.saturating_sub(MINIMAP_SIZE + MINIMAP_MARGIN);
+        let bottom = screen_height.saturating_sub(MINIMAP_SIZE + MINIMAP_MARGIN);
+
+        match self {
+            MinimapCorner::TopRight => Some((right, MINIMAP_MARGIN)),
+            MinimapCorner::TopLeft => Some((MINIMAP_MARGIN, (hud_bottom + MINIMAP_MARGIN).min(bottom))),
+            MinimapCorner::BottomLeft => Some((MINIMAP_MARGIN, bottom)),
+            MinimapCorner::BottomRight => Some((right, bottom)),
+            MinimapCorner::Hidden => None,
+        }
+    }
+}
+
+/// Opciones del minimapa que se guardan en settings
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MinimapOptions {
+    pub corner: MinimapCorner,
+    pub orientation: MinimapOrientation,
+    pub zoom: MinimapZoom,
+}
+
+impl Default for MinimapOptions {
+    fn default() -> Self {
+        MinimapOptions {
+            corner: MinimapCorner::TopRight,
+            orientation: MinimapOrientation::NorthUp,
+            zoom: MinimapZoom::Full,
+        }
+    }
+}
+
+/// Estado de los objetivos que el minimapa marca con pings
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MinimapObjectives {
+    // Tiempo del nivel, para el pulso de las marcas
+    pub animation_time: f32,
+    // Ya se cumplen los requisitos de la salida: su marca se resalta
+    pub exit_open: bool,
+    // Llaves recogidas y pedidas por el nivel, para el contador del minimapa
+    pub keys_collected: i32,
+    pub keys_needed: i32,
+}
+
+/// Dónde se dibuja el minimapa: esquina y lado en pantalla, pixeles por casilla
+/// y bordes (izquierda, arriba, derecha, abajo) recortados a la pantalla
+#[derive(Debug, Clone, Copy)]
+pub struct MinimapView {
+    origin: (u32, u32),
+    size: u32,
+    scale: u32,
+    // Unidades del mundo por casilla, para pasar de posición a casilla
+    block_size: usize,
+    bounds: (i32, i32, i32, i32),
+    zoom: MinimapZoom,
+    orientation: MinimapOrientation,
+}
+
+impl MinimapView {
+    /// Coloca el minimapa en su esquina (arriba a la izquierda, debajo de
+    /// `hud_bottom`) con la escala de su zoom. None si está oculto o no hay laberinto
+    pub fn place(
+        framebuffer: &Framebuffer,
+        options: &MinimapOptions,
+        hud_bottom: u32,
+        maze: &Maze,
+        block_size: usize,
+    ) -> Option<Self> {
+        let origin = options.corner.origin(framebuffer.width, framebuffer.height, hud_bottom)?;
+
+        let maze_height = maze.len();
+        let maze_width = maze.iter().map(|row| row.len()).max().unwrap_or(0);
+        if maze_width == 0 || maze_height == 0 {
+            return None;
+        }
+
+        let scale = match options.zoom {
+            MinimapZoom::Full => {
+                let scale_x = (MINIMAP_SIZE - 20) as f32 / maze_width as f32;
+                let scale_y = (MINIMAP_SIZE - 20) as f32 / maze_height as f32;
+                scale_x.min(scale_y).max(2.0) as u32
+            },
+            MinimapZoom::Local => LOCAL_MINIMAP_SCALE,
+        };
+
+        let (left, top) = (origin.0 as i32, origin.1 as i32);
+        Some(MinimapView {
+            origin,
+            size: MINIMAP_SIZE,
+            scale,
+            block_size,
+            zoom: options.zoom,
+            orientation: options.orientation,
+            bounds: (
+                left,
+                top,
+                (left + MINIMAP_SIZE as i32).min(framebuffer.width as i32),
+                (top + MINIMAP_SIZE as i32).min(framebuffer.height as i32),
+            ),
+        })
+    }
+
+    fn center(&self) -> (f32, f32) {
+        (
+            self.origin.0 as f32 + self.size as f32 * 0.5,
+            self.origin.1 as f32 + self.size as f32 * 0.5,
+        )
+    }
+}
+
+/// `visited` son las casillas que ya pisó el jugador; su suelo se tiñe como rastro
 pub fn render_minimap(
     framebuffer: &mut Framebuffer,
     maze: &Maze,
     player: &Player,
-    block_size: usize,
+    visited: &HashSet<(usize, usize)>,
+    view: &MinimapView,
+    objectives: &MinimapObjectives,
+    palette: &Palette,
 ) {
-    let minimap_size = 200;
-    let minimap_x = framebuffer.width - minimap_size - 15;
-    let minimap_y = 15;
-    
-    let maze_height = maze.len();
-    let maze_width = if maze_height > 0 { maze[0].len() } else { 0 };
-    
-    if maze_width == 0 || maze_height == 0 {
-        return;
-    }
-    
-    let scale_x = (minimap_size - 20) as f32 / maze_width as f32;
-    let scale_y = (minimap_size - 20) as f32 / maze_height as f32;
-    let scale = scale_x.min(scale_y).max(2.0) as u32;
-    
-    let maze_pixel_width = maze_width as u32 * scale;
-    let maze_pixel_height = maze_height as u32 * scale;
-    let offset_x = (minimap_size - maze_pixel_width) / 2;
-    let offset_y = (minimap_size - maze_pixel_height) / 2;
+    let (minimap_x, minimap_y) = view.origin;
+    let (minimap_size, scale, block_size) = (view.size, view.scale, view.block_size);
+
+    // Esquina del laberinto relativa al minimapa: centrado entero, o
+    // desplazado para que el jugador quede en el centro en la vista local
+    let (offset_x, offset_y) = match view.zoom {
+        MinimapZoom::Full => {
+            let maze_pixel_width = maze.iter().map(|row| row.len()).max().unwrap_or(0) as u32 * scale;
+            let maze_pixel_height = maze.len() as u32 * scale;
+            (
+                (minimap_size.saturating_sub(maze_pixel_width) / 2) as i32,
+                (minimap_size.saturating_sub(maze_pixel_height) / 2) as i32,
+            )
+        },
+        MinimapZoom::Local => {
+            let half = minimap_size as f32 * 0.5;
+            (
+                (half - player.pos.x / block_size as f32 * scale as f32) as i32,
+                (half - player.pos.y / block_size as f32 * scale as f32) as i32,
+            )
+        },
+    };
     
     // Fondo del minimapa
     framebuffer.set_current_color(Color::new(0, 0, 0, 200));
     for y in 0..minimap_size {
         for x in 0..minimap_size {
             if minimap_x + x < framebuffer.width && minimap_y + y < framebuffer.height {
-                framebuffer.set_pixel(minimap_x + x, minimap_y + y);
+                framebuffer.blend_pixel(minimap_x + x, minimap_y + y);
             }
         }
     }
@@ -402,116 +1197,390 @@ pub fn render_minimap(
         minimap_y, 
         minimap_size, 
         minimap_size, 
-        Color::new(100, 100, 150, 255)
+        palette.frame
     );
     
-    framebuffer.set_current_color(Color::WHITE);
-    render_text(framebuffer, "MAPA", minimap_x + 5, minimap_y + 5);
+    if view.orientation == MinimapOrientation::PlayerUp {
+        render_minimap_rotated(framebuffer, maze, player, visited, objectives, view, palette);
+        render_minimap_label(framebuffer, view.zoom, minimap_x, minimap_y, palette);
+        render_minimap_key_badge(framebuffer, maze, objectives, minimap_x, minimap_y + minimap_size, palette);
+        return;
+    }
     
+    // Aritmética con signo y recorte al rectángulo del minimapa: en la vista
+    // local las casillas se salen por los bordes y el jugador puede estar junto a ellos
+    let (map_left, map_top, map_right, map_bottom) = view.bounds;
+    let inside_minimap = |x: i32, y: i32| x >= map_left && x < map_right && y >= map_top && y < map_bottom;
+    // Las casillas no pisan el marco
+    let inside_frame = |x: i32, y: i32| x > map_left && x < map_right - 1 && y > map_top && y < map_bottom - 1;
+
     // Renderizar celdas del maze
+    let scale_i = scale as i32;
     for (row_index, row) in maze.iter().enumerate() {
         for (col_index, &cell) in row.iter().enumerate() {
-            if cell != ' ' {
-                let color = match cell {
-                    '#' | '+' | '-' | '|' | 'r' | 'b' | 'g' => Color::new(180, 180, 120, 255),
-                    'k' => Color::GOLD,
-                    'e' => Color::GREEN,
-                    't' => Color::RED,
-                    'l' => Color::PURPLE,
-                    'c' => Color::CYAN,
-                    _ => Color::WHITE,
-                };
+            let cell_x = map_left + offset_x + col_index as i32 * scale_i;
+            let cell_y = map_top + offset_y + row_index as i32 * scale_i;
+            if cell_x + scale_i <= map_left || cell_x >= map_right || cell_y + scale_i <= map_top || cell_y >= map_bottom {
+                continue;
+            }
+
+            // Suelo: solo se dibuja si forma parte del rastro
+            let color = palette.minimap_cell_color(cell)
+                .or_else(|| visited.contains(&(col_index, row_index)).then(|| palette.visited_floor()));
+            if let Some(color) = color {
                 framebuffer.set_current_color(color);
                 
-                let cell_x = minimap_x + offset_x + (col_index as u32 * scale);
-                let cell_y = minimap_y + offset_y + (row_index as u32 * scale);
-                
                 for dy in 0..scale {
                     for dx in 0..scale {
-                        let px = cell_x + dx;
-                        let py = cell_y + dy;
-                        if px < framebuffer.width && py < framebuffer.height {
-                            framebuffer.set_pixel(px, py);
+                        let px = cell_x + dx as i32;
+                        let py = cell_y + dy as i32;
+                        let fx = (dx as f32 + 0.5) / scale as f32;
+                        let fy = (dy as f32 + 0.5) / scale as f32;
+                        if inside_frame(px, py) && minimap_cell_shape(cell, fx, fy, scale) {
+                            framebuffer.set_pixel(px as u32, py as u32);
                         }
                     }
                 }
             }
         }
     }
+
+    render_minimap_label(framebuffer, view.zoom, minimap_x, minimap_y, palette);
+    render_minimap_key_badge(framebuffer, maze, objectives, minimap_x, minimap_y + minimap_size, palette);
+
+    let bounds = view.bounds;
+    let player_origin = (
+        (map_left + offset_x) as f32 + player.pos.x / block_size as f32 * scale as f32,
+        (map_top + offset_y) as f32 + player.pos.y / block_size as f32 * scale as f32,
+    );
+    render_view_cone(framebuffer, maze, player, view, player_origin, player.a, palette);
+    render_objective_markers(framebuffer, maze, objectives, bounds, palette, |cell_x, cell_y| {
+        (
+            (map_left + offset_x) as f32 + cell_x * scale as f32,
+            (map_top + offset_y) as f32 + cell_y * scale as f32,
+        )
+    });
     
     // Renderizar jugador
-    let player_map_x = minimap_x + offset_x + ((player.pos.x / block_size as f32) * scale as f32) as u32;
-    let player_map_y = minimap_y + offset_y + ((player.pos.y / block_size as f32) * scale as f32) as u32;
+    let player_map_x = (map_left + offset_x + ((player.pos.x / block_size as f32) * scale as f32) as i32)
+        .clamp(map_left, map_right - 1);
+    let player_map_y = (map_top + offset_y + ((player.pos.y / block_size as f32) * scale as f32) as i32)
+        .clamp(map_top, map_bottom - 1);
     
-    framebuffer.set_current_color(Color::RED);
-    let player_size = 3.max(scale / 3);
+    framebuffer.set_current_color(palette.player);
+    let player_size = 3.max(scale / 3) as i32;
     for dy in 0..player_size {
         for dx in 0..player_size {
-            let px = player_map_x + dx - player_size/2;
-            let py = player_map_y + dy - player_size/2;
-            if px < framebuffer.width && py < framebuffer.height {
-                framebuffer.set_pixel(px, py);
+            let px = player_map_x + dx - player_size / 2;
+            let py = player_map_y + dy - player_size / 2;
+            if inside_minimap(px, py) {
+                framebuffer.set_pixel(px as u32, py as u32);
             }
         }
     }
     
     // Renderizar dirección del jugador
-    framebuffer.set_current_color(Color::BLUE);
-    let direction_length = (scale * 2) as f32;
-    let end_x = player_map_x as f32 + player.a.cos() * direction_length;
-    let end_y = player_map_y as f32 + player.a.sin() * direction_length;
-    
-    for i in 0..((direction_length as u32).max(8)) {
-        let t = i as f32 / direction_length;
-        let px = (player_map_x as f32 + t * (end_x - player_map_x as f32)) as u32;
-        let py = (player_map_y as f32 + t * (end_y - player_map_y as f32)) as u32;
-        
-        for dy in 0..2 {
-            for dx in 0..2 {
-                if px + dx < framebuffer.width && py + dy < framebuffer.height {
-                    framebuffer.set_pixel(px + dx, py + dy);
-                }
+    framebuffer.set_current_color(palette.player_direction);
+    let direction_length = (scale * 2).max(8) as f32;
+    let end_x = player_map_x + (player.a.cos() * direction_length) as i32;
+    let end_y = player_map_y + (player.a.sin() * direction_length) as i32;
+    draw_line_clipped(framebuffer, player_map_x, player_map_y, end_x, end_y, 2, (map_left, map_top, map_right, map_bottom));
+}
+
+fn render_minimap_label(framebuffer: &mut Framebuffer, zoom: MinimapZoom, minimap_x: u32, minimap_y: u32, palette: &Palette) {
+    framebuffer.set_current_color(palette.text);
+    let label = match zoom {
+        MinimapZoom::Full => "MAPA",
+        MinimapZoom::Local => "MAPA LOCAL",
+    };
+    render_text(framebuffer, label, minimap_x + 5, minimap_y + 5);
+}
+
+/// Texto del contador de llaves del minimapa: recogidas/pedidas y las que
+/// siguen en el laberinto. None si el nivel no tiene llaves
+pub fn minimap_key_badge(maze: &Maze, objectives: &MinimapObjectives) -> Option<String> {
+    let remaining = maze.iter().flatten().filter(|&&cell| cell == 'k').count();
+    if objectives.keys_needed <= 0 && remaining == 0 {
+        return None;
+    }
+
+    let mut badge = format!("LLAVES {}/{}", objectives.keys_collected, objectives.keys_needed);
+    if remaining > 0 {
+        badge.push_str(&format!(" - {} EN MAPA", remaining));
+    }
+    Some(badge)
+}
+
+// Contador de llaves en la esquina inferior izquierda del minimapa
+fn render_minimap_key_badge(
+    framebuffer: &mut Framebuffer,
+    maze: &Maze,
+    objectives: &MinimapObjectives,
+    minimap_x: u32,
+    minimap_bottom: u32,
+    palette: &Palette,
+) {
+    let Some(badge) = minimap_key_badge(maze, objectives) else {
+        return;
+    };
+
+    let y = minimap_bottom.saturating_sub(13);
+    render_translucent_rect(framebuffer, minimap_x + 2, y - 3, text_width(&badge, 1) + 6, FONT_HEIGHT + 6, Color::new(0, 0, 0, 180));
+    framebuffer.set_current_color(palette.text);
+    render_text(framebuffer, &badge, minimap_x + 5, y);
+}
+
+// Forma de la casilla dentro de su celda (fx, fy en 0..1) para no depender
+// solo del color: trampas en X y salida como marco hueco
+fn minimap_cell_shape(cell: char, fx: f32, fy: f32, scale: u32) -> bool {
+    if scale < 4 {
+        return true;
+    }
+
+    match MinimapTile::of(cell) {
+        Some(MinimapTile::Hazard) => (fx - fy).abs() < 0.25 || (fx + fy - 1.0).abs() < 0.25,
+        Some(MinimapTile::Exit) => !(0.3..0.7).contains(&fx) || !(0.3..0.7).contains(&fy),
+        // Dos hojas con una rendija en el centro; abierta, solo el marco
+        Some(MinimapTile::Door) => !(0.45..0.55).contains(&fx),
+        Some(MinimapTile::OpenDoor) => !(0.2..0.8).contains(&fx),
+        _ => true,
+    }
+}
+
+// Rayos del abanico del cono de visión y alcance máximo en casillas
+const VIEW_CONE_RAYS: usize = 24;
+const VIEW_CONE_RANGE_CELLS: f32 = 4.0;
+
+// Cono tenue con el campo de visión del jugador, recortado por las paredes
+// con rayos del caster. `origin` y `heading` son la posición y dirección del
+// jugador en pantalla; el ángulo relativo a la mirada es igual en el mundo
+// y en el minimapa, también en la vista rotada
+fn render_view_cone(
+    framebuffer: &mut Framebuffer,
+    maze: &Maze,
+    player: &Player,
+    view: &MinimapView,
+    (origin_x, origin_y): (f32, f32),
+    heading: f32,
+    palette: &Palette,
+) {
+    use std::f32::consts::PI;
+
+    let (scale, block_size) = (view.scale, view.block_size);
+    let (left, top, right, bottom) = view.bounds;
+
+    let half_fov = player.fov * 0.5;
+    let pixels_per_unit = scale as f32 / block_size.max(1) as f32;
+    let range = VIEW_CONE_RANGE_CELLS * block_size as f32;
+    let reach: Vec<f32> = (0..VIEW_CONE_RAYS)
+        .map(|i| {
+            let angle = player.a - half_fov + player.fov * i as f32 / (VIEW_CONE_RAYS - 1) as f32;
+            let hit = cast_ray_textured(maze, None, player, angle, block_size, 0.0, &mut Vec::new());
+            hit.distance.min(range) * pixels_per_unit
+        })
+        .collect();
+
+    let color = palette.player_direction;
+    framebuffer.set_current_color(Color::new(color.r, color.g, color.b, 60));
+
+    // Las casillas no pisan el marco
+    let radius = range * pixels_per_unit;
+    let x_range = ((origin_x - radius) as i32).max(left + 1)..((origin_x + radius) as i32 + 1).min(right - 1);
+    let y_range = ((origin_y - radius) as i32).max(top + 1)..((origin_y + radius) as i32 + 1).min(bottom - 1);
+    for y in y_range {
+        for x in x_range.clone() {
+            let (dx, dy) = (x as f32 + 0.5 - origin_x, y as f32 + 0.5 - origin_y);
+            let relative = (dy.atan2(dx) - heading + PI).rem_euclid(2.0 * PI) - PI;
+            if relative.abs() > half_fov {
+                continue;
+            }
+
+            let ray = ((relative + half_fov) / player.fov * (VIEW_CONE_RAYS - 1) as f32).round() as usize;
+            if (dx * dx + dy * dy).sqrt() <= reach[ray.min(VIEW_CONE_RAYS - 1)] {
+                framebuffer.blend_pixel(x as u32, y as u32);
             }
         }
     }
 }
 
-pub fn render_text(framebuffer: &mut Framebuffer, text: &str, x: u32, y: u32) {
-    for (i, c) in text.chars().enumerate() {
-        let char_x = x + (i as u32 * (FONT_WIDTH + 1));
-        if char_x < framebuffer.width {
-            render_char(framebuffer, c, char_x, y);
+// Pings que laten sobre las llaves que quedan y la salida. `to_screen` pasa
+// de coordenadas de casilla a pixeles; un objetivo fuera de la vista queda
+// pegado al borde del minimapa, señalando hacia dónde está
+fn render_objective_markers(
+    framebuffer: &mut Framebuffer,
+    maze: &Maze,
+    objectives: &MinimapObjectives,
+    (left, top, right, bottom): (i32, i32, i32, i32),
+    palette: &Palette,
+    to_screen: impl Fn(f32, f32) -> (f32, f32),
+) {
+    // Pulso de 0 a 1, un poco más de una vez por segundo
+    let pulse = (objectives.animation_time * 7.0).sin() * 0.5 + 0.5;
+    // Las marcas no pisan el marco
+    let clip = (left + 1, top + 1, right - 1, bottom - 1);
+
+    for (row_index, row) in maze.iter().enumerate() {
+        for (col_index, &cell) in row.iter().enumerate() {
+            let (color, radius, thickness) = match cell {
+                'k' => (palette.key, 3.0 + pulse * 2.0, 1),
+                'e' if objectives.exit_open => (palette.exit, 5.0 + pulse * 4.0, 2),
+                'e' => (palette.exit, 3.0 + pulse, 1),
+                _ => continue,
+            };
+
+            let (x, y) = to_screen(col_index as f32 + 0.5, row_index as f32 + 0.5);
+            let radius = radius as i32;
+            let x = (x as i32).clamp(clip.0 + radius, (clip.2 - 1 - radius).max(clip.0 + radius));
+            let y = (y as i32).clamp(clip.1 + radius, (clip.3 - 1 - radius).max(clip.1 + radius));
+
+            // Rombo hueco alrededor del objetivo
+            framebuffer.set_current_color(color);
+            if cell == 'k' {
+                // Centro sólido: la llave se ve aunque su casilla mida 2px
+                // o la tape la etiqueta o el cono de visión
+                for py in (y - 1).max(clip.1)..(y + 2).min(clip.3) {
+                    for px in (x - 1).max(clip.0)..(x + 2).min(clip.2) {
+                        framebuffer.set_pixel(px as u32, py as u32);
+                    }
+                }
+            }
+            let corners = [(x, y - radius), (x + radius, y), (x, y + radius), (x - radius, y)];
+            for i in 0..corners.len() {
+                let (x0, y0) = corners[i];
+                let (x1, y1) = corners[(i + 1) % corners.len()];
+                draw_line_clipped(framebuffer, x0, y0, x1, y1, thickness, clip);
+            }
         }
     }
 }
 
-fn render_text_with_scale(framebuffer: &mut Framebuffer, text: &str, x: u32, y: u32, scale: u32) {
-    for (i, c) in text.chars().enumerate() {
-        let bitmap = get_char_bitmap(c);
-        let char_x = x + (i as u32 * (FONT_WIDTH * scale + 2));
-        
-        for row in 0..FONT_HEIGHT {
-            for col in 0..FONT_WIDTH {
-                let index = (row * FONT_WIDTH + col) as usize;
-                if bitmap[index] == 1 {
-                    for dy in 0..scale {
-                        for dx in 0..scale {
-                            let px = char_x + col * scale + dx;
-                            let py = y + row * scale + dy;
-                            if px < framebuffer.width && py < framebuffer.height {
-                                framebuffer.set_pixel(px, py);
-                            }
-                        }
-                    }
+// Minimapa centrado en el jugador y rotado para que siempre mire hacia arriba
+fn render_minimap_rotated(
+    framebuffer: &mut Framebuffer,
+    maze: &Maze,
+    player: &Player,
+    visited: &HashSet<(usize, usize)>,
+    objectives: &MinimapObjectives,
+    view: &MinimapView,
+    palette: &Palette,
+) {
+    let (minimap_x, minimap_y) = view.origin;
+    let (minimap_size, scale, block_size) = (view.size, view.scale, view.block_size);
+    let (center_x, center_y) = view.center();
+    let player_cell_x = player.pos.x / block_size as f32;
+    let player_cell_y = player.pos.y / block_size as f32;
+    
+    // Rotación que lleva la dirección del jugador a "arriba" en pantalla
+    let rotation = -player.a - std::f32::consts::FRAC_PI_2;
+    let (sin_r, cos_r) = rotation.sin_cos();
+    
+    // Recorrer cada pixel del minimapa y aplicar la rotación inversa (sin huecos)
+    for py in (minimap_y + 1)..(minimap_y + minimap_size) {
+        for px in (minimap_x + 1)..(minimap_x + minimap_size) {
+            if px >= framebuffer.width || py >= framebuffer.height {
+                continue;
+            }
+            
+            let dx = px as f32 + 0.5 - center_x;
+            let dy = py as f32 + 0.5 - center_y;
+            let map_dx = dx * cos_r + dy * sin_r;
+            let map_dy = -dx * sin_r + dy * cos_r;
+            
+            let cell_x = player_cell_x + map_dx / scale as f32;
+            let cell_y = player_cell_y + map_dy / scale as f32;
+            if cell_x < 0.0 || cell_y < 0.0 {
+                continue;
+            }
+            
+            let cell = maze.get(cell_y as usize)
+                .and_then(|row| row.get(cell_x as usize))
+                .copied();
+            
+            let Some(cell) = cell else {
+                continue;
+            };
+            if let Some(color) = palette.minimap_cell_color(cell) {
+                if minimap_cell_shape(cell, cell_x.fract(), cell_y.fract(), scale) {
+                    framebuffer.set_current_color(color);
+                    framebuffer.set_pixel(px, py);
                 }
+            } else if visited.contains(&(cell_x as usize, cell_y as usize)) {
+                framebuffer.set_current_color(palette.visited_floor());
+                framebuffer.set_pixel(px, py);
+            }
+        }
+    }
+    
+    // Rotación directa (de casilla a pantalla) para las marcas de objetivos
+    let bounds = view.bounds;
+    // En esta vista el jugador siempre mira hacia arriba
+    let heading = -std::f32::consts::FRAC_PI_2;
+    render_view_cone(framebuffer, maze, player, view, (center_x, center_y), heading, palette);
+    render_objective_markers(framebuffer, maze, objectives, bounds, palette, |cell_x, cell_y| {
+        let map_dx = (cell_x - player_cell_x) * scale as f32;
+        let map_dy = (cell_y - player_cell_y) * scale as f32;
+        (
+            center_x + map_dx * cos_r - map_dy * sin_r,
+            center_y + map_dx * sin_r + map_dy * cos_r,
+        )
+    });
+
+    // Jugador en el centro
+    framebuffer.set_current_color(palette.player);
+    let player_size = 3.max(scale / 3) as i32;
+    for dy in 0..player_size {
+        for dx in 0..player_size {
+            let px = center_x as i32 + dx - player_size / 2;
+            let py = center_y as i32 + dy - player_size / 2;
+            if px >= 0 && py >= 0 {
+                framebuffer.set_pixel(px as u32, py as u32);
             }
         }
     }
+    
+    // Dirección del jugador: siempre hacia arriba
+    framebuffer.set_current_color(palette.player_direction);
+    let direction_length = (scale * 2).max(8) as i32;
+    let (cx, cy) = (center_x as i32, center_y as i32);
+    draw_line(framebuffer, cx, cy, cx, cy - direction_length + 1, 2);
+}
+
+pub fn render_text(framebuffer: &mut Framebuffer, text: &str, x: u32, y: u32) {
+    render_text_with_scale(framebuffer, text, x, y, 1);
+}
+
+/// Dibuja texto sin pasar de `max_x` (exclusivo); si no cabe se corta con "..."
+pub fn render_text_clipped(framebuffer: &mut Framebuffer, text: &str, x: u32, y: u32, max_x: u32, scale: u32) {
+    const ELLIPSIS: &str = "...";
+
+    // n caracteres ocupan n * avance - scale pixeles (sin espacio tras el último)
+    let max_chars = ((max_x.saturating_sub(x) + scale) / (FONT_ADVANCE * scale)) as usize;
+    let char_count = text.chars().count();
+
+    if char_count <= max_chars {
+        render_text_with_scale(framebuffer, text, x, y, scale);
+    } else if max_chars <= ELLIPSIS.len() {
+        render_text_with_scale(framebuffer, &ELLIPSIS[..max_chars], x, y, scale);
+    } else {
+        let visible: String = text.chars().take(max_chars - ELLIPSIS.len()).collect();
+        render_text_with_scale(framebuffer, &format!("{}{}", visible.trim_end(), ELLIPSIS), x, y, scale);
+    }
+}
+
+/// Texto con cada pixel del glifo agrandado `scale` veces; el avance entre
+/// caracteres crece igual para que no se pisen
+pub fn render_text_with_scale(framebuffer: &mut Framebuffer, text: &str, x: u32, y: u32, scale: u32) {
+    for (i, c) in text.chars().enumerate() {
+        let char_x = x + i as u32 * FONT_ADVANCE * scale;
+        if char_x >= framebuffer.width {
+            break;
+        }
+        render_char(framebuffer, c, char_x, y, scale);
+    }
 }
 
 fn render_text_centered(framebuffer: &mut Framebuffer, text: &str, y: u32, scale: u32) {
-    let text_width = text.len() as u32 * (FONT_WIDTH * scale + 2);
-    let center_x = (framebuffer.width - text_width) / 2;
+    let center_x = framebuffer.width.saturating_sub(text_width(text, scale)) / 2;
     render_text_with_scale(framebuffer, text, center_x, y, scale);
 }
 
@@ -529,6 +1598,17 @@ fn render_gradient_background(framebuffer: &mut Framebuffer, color1: Color, colo
     }
 }
 
+// Rectángulo mezclado con alfa sobre lo que ya hay en el framebuffer
+fn render_translucent_rect(framebuffer: &mut Framebuffer, x: u32, y: u32, width: u32, height: u32, color: Color) {
+    framebuffer.set_current_color(color);
+    
+    for py in y..(y + height).min(framebuffer.height) {
+        for px in x..(x + width).min(framebuffer.width) {
+            framebuffer.blend_pixel(px, py);
+        }
+    }
+}
+
 fn render_border_frame(framebuffer: &mut Framebuffer, x: u32, y: u32, width: u32, height: u32, color: Color) {
     framebuffer.set_current_color(color);
     
@@ -555,4 +1635,414 @@ fn render_border_frame(framebuffer: &mut Framebuffer, x: u32, y: u32, width: u32
             }
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::palette::PaletteKind;
+
+    fn maze(rows: &[&str]) -> Maze {
+        rows.iter().map(|row| row.chars().collect()).collect()
+    }
+
+    // Cuarto vacío de `size` casillas de lado rodeado de pared
+    fn open_room(size: usize) -> Maze {
+        let mut room = vec![vec!['#'; size]; size];
+        for row in room.iter_mut().take(size - 1).skip(1) {
+            row[1..size - 1].fill(' ');
+        }
+        room
+    }
+
+    // Minimapa como lo coloca el juego sin HUD encima, con casillas de 100 y sin rastro
+    fn draw_minimap(
+        framebuffer: &mut Framebuffer,
+        maze: &Maze,
+        player: &Player,
+        options: &MinimapOptions,
+        objectives: &MinimapObjectives,
+        palette: &Palette,
+    ) {
+        let view = MinimapView::place(framebuffer, options, 0, maze, 100).unwrap();
+        render_minimap(framebuffer, maze, player, &HashSet::new(), &view, objectives, palette);
+    }
+
+    #[test]
+    fn arrow_keys_win_over_a_cursor_resting_on_an_entry() {
+        // Cursor quieto sobre la entrada 1: las flechas siguen moviendo la selección
+        let resting = Some(1);
+        let mut selected = 1;
+        selected = menu_selection(selected, 4, false, true, resting, false);
+        assert_eq!(selected, 2);
+        selected = menu_selection(selected, 4, false, false, resting, false);
+        assert_eq!(selected, 2);
+        selected = menu_selection(selected, 4, true, false, resting, false);
+        selected = menu_selection(selected, 4, true, false, resting, false);
+        assert_eq!(selected, 0);
+        assert_eq!(menu_selection(0, 4, true, false, resting, false), 3);
+
+        // Mover el mouse sobre una entrada sí la selecciona
+        assert_eq!(menu_selection(selected, 4, false, false, resting, true), 1);
+        assert_eq!(menu_selection(selected, 4, false, false, None, true), 0);
+    }
+
+    #[test]
+    fn scaled_text_advances_by_the_scaled_width_without_overlapping() {
+        assert_eq!(text_width("", 2), 0);
+        assert_eq!(text_width("AB", 1), 11);
+        assert_eq!(text_width("AB", 3), 33);
+
+        // Dos 'I' a escala 2: cada glifo ocupa 10px y queda un hueco de 2px
+        let mut framebuffer = Framebuffer::new(40, 20);
+        framebuffer.set_current_color(Color::WHITE);
+        render_text_with_scale(&mut framebuffer, "II", 0, 0, 2);
+        let top_row: Vec<bool> = (0..24)
+            .map(|x| framebuffer.color_buffer.get_color(x, 0) == Color::WHITE)
+            .collect();
+        assert!(top_row[..10].iter().all(|&lit| lit));
+        assert!(!top_row[10] && !top_row[11]);
+        assert!(top_row[12..22].iter().all(|&lit| lit));
+
+        // Minúsculas y signos nuevos tienen glifo propio en vez de la caja
+        let unknown = get_char_bitmap('~');
+        assert_ne!(get_char_bitmap('a'), get_char_bitmap('A'));
+        for c in "abcdefghijklmnopqrstuvwxyzJQÑñ,;?¿¡%+=<>_#*[]'\"".chars() {
+            assert_ne!(get_char_bitmap(c), unknown, "'{}'", c);
+        }
+        assert_eq!(get_char_bitmap('á'), get_char_bitmap('a'));
+    }
+
+    #[test]
+    fn level_times_are_formatted_as_minutes_seconds_and_millis() {
+        assert_eq!(format_level_time(0.0), "00:00.000");
+        assert_eq!(format_level_time(83.4567), "01:23.457");
+        assert_eq!(format_level_time(59.9996), "01:00.000");
+        assert_eq!(format_level_time(-2.0), "00:00.000");
+    }
+
+    #[test]
+    fn player_dot_at_grid_origin_stays_inside_minimap() {
+        let mut framebuffer = Framebuffer::new(320, 240);
+        let maze = maze(&["#####", "#   #", "#   #", "#####"]);
+        let mut player = Player::new();
+        player.pos = Vector2::new(0.0, 0.0);
+        player.a = std::f32::consts::PI * 1.25;
+
+        let palette = Palette::of(PaletteKind::Default);
+        draw_minimap(&mut framebuffer, &maze, &player, &MinimapOptions::default(), &MinimapObjectives::default(), &palette);
+
+        // Mismo rectángulo que calcula render_minimap
+        let minimap_x = framebuffer.width - 215;
+        let (minimap_y, minimap_size) = (15, 200);
+        let mut dot_pixels = 0;
+
+        for y in 0..framebuffer.height {
+            for x in 0..framebuffer.width {
+                let color = framebuffer.color_buffer.get_color(x as i32, y as i32);
+                if color == Color::RED || color == Color::BLUE {
+                    assert!(x >= minimap_x && x < minimap_x + minimap_size, "x fuera del minimapa: {}", x);
+                    assert!(y >= minimap_y && y < minimap_y + minimap_size, "y fuera del minimapa: {}", y);
+                    dot_pixels += 1;
+                }
+            }
+        }
+
+        assert!(dot_pixels > 0);
+    }
+
+    #[test]
+    fn local_minimap_scrolls_to_keep_the_player_centered() {
+        let mut framebuffer = Framebuffer::new(320, 240);
+        let maze = open_room(30);
+        let palette = Palette::of(PaletteKind::Default);
+        let options = MinimapOptions { zoom: MinimapZoom::Local, ..MinimapOptions::default() };
+        let (center_x, center_y) = (framebuffer.width - 215 + 100, 15 + 100);
+        let pixel = |framebuffer: &mut Framebuffer, x: u32, y: u32| framebuffer.color_buffer.get_color(x as i32, y as i32);
+
+        // Junto a la pared oeste y en medio del laberinto el jugador sigue en el centro
+        let mut player = Player::new();
+        player.a = 0.0;
+        for (x, y) in [(150.0, 150.0), (1550.0, 1450.0)] {
+            framebuffer.clear();
+            player.pos = Vector2::new(x, y);
+            draw_minimap(&mut framebuffer, &maze, &player, &options, &MinimapObjectives::default(), &palette);
+            assert_eq!(pixel(&mut framebuffer, center_x - 2, center_y - 2), palette.player);
+        }
+
+        // Casilla a cuatro celdas al oeste del jugador: la pared exterior solo
+        // entra en la vista cuando el jugador está cerca de ella
+        let wall = palette.minimap_cell_color('#').unwrap();
+        let west = center_x - 4 * LOCAL_MINIMAP_SCALE;
+        assert_ne!(pixel(&mut framebuffer, west, center_y + 1), wall);
+        framebuffer.clear();
+        player.pos = Vector2::new(450.0, 1450.0);
+        draw_minimap(&mut framebuffer, &maze, &player, &options, &MinimapObjectives::default(), &palette);
+        assert_eq!(pixel(&mut framebuffer, west, center_y + 1), wall);
+    }
+
+    #[test]
+    fn objectives_out_of_view_are_pinged_at_the_minimap_edge() {
+        let mut maze = open_room(30);
+        maze[15][27] = 'k';
+        maze[20][27] = 'e';
+
+        let palette = Palette::of(PaletteKind::Default);
+        let options = MinimapOptions { zoom: MinimapZoom::Local, ..MinimapOptions::default() };
+        let mut player = Player::new();
+        player.pos = Vector2::new(150.0, 1550.0);
+        let (minimap_x, minimap_y) = (320 - 215, 15);
+
+        let count = |objectives: MinimapObjectives, color: Color| {
+            let mut framebuffer = Framebuffer::new(320, 240);
+            draw_minimap(&mut framebuffer, &maze, &player, &options, &objectives, &palette);
+            let mut pixels = Vec::new();
+            for y in minimap_y..minimap_y + 200 {
+                for x in minimap_x..minimap_x + 200 {
+                    if framebuffer.get_pixel(x, y) == Some(color) {
+                        pixels.push(x);
+                    }
+                }
+            }
+            pixels
+        };
+
+        // La llave está 26 casillas al este: su marca queda en el borde derecho
+        let key_pixels = count(MinimapObjectives::default(), palette.key);
+        assert!(!key_pixels.is_empty());
+        assert!(key_pixels.iter().all(|&x| x > minimap_x + 180));
+
+        // Con los requisitos cumplidos la salida se marca más grande
+        let locked = count(MinimapObjectives { exit_open: false, ..MinimapObjectives::default() }, palette.exit);
+        let open = count(MinimapObjectives { exit_open: true, ..MinimapObjectives::default() }, palette.exit);
+        assert!(!locked.is_empty() && open.len() > locked.len());
+    }
+
+    #[test]
+    fn minimap_counts_keys_and_draws_them_above_its_label() {
+        // 90 casillas de ancho: a zoom completo cada una mide 2px
+        let mut maze: Maze = vec![vec![' '; 90]; 90];
+        maze[0][3] = 'k';
+        maze[40][40] = 'k';
+        let objectives = MinimapObjectives { keys_collected: 1, keys_needed: 3, ..MinimapObjectives::default() };
+        assert_eq!(minimap_key_badge(&maze, &objectives).as_deref(), Some("LLAVES 1/3 - 2 EN MAPA"));
+
+        let palette = Palette::of(PaletteKind::Default);
+        let mut framebuffer = Framebuffer::new(320, 240);
+        let player = Player::new();
+        draw_minimap(&mut framebuffer, &maze, &player, &MinimapOptions::default(), &objectives, &palette);
+
+        // La llave de la fila 0 cae bajo el texto "MAPA" y aun así se ve
+        let (minimap_x, minimap_y) = (320 - 215, 15);
+        assert_eq!(framebuffer.get_pixel(minimap_x + 17, minimap_y + 11), Some(palette.key));
+
+        let empty: Maze = vec![vec![' '; 4]; 4];
+        assert_eq!(minimap_key_badge(&empty, &MinimapObjectives::default()), None);
+        let done = MinimapObjectives { keys_collected: 3, keys_needed: 3, ..MinimapObjectives::default() };
+        assert_eq!(minimap_key_badge(&empty, &done).as_deref(), Some("LLAVES 3/3"));
+    }
+
+    #[test]
+    fn player_up_minimap_turns_what_is_ahead_towards_the_top() {
+        let mut maze = open_room(30);
+        // Llave 3 casillas al este del jugador, que mira al este
+        maze[15][18] = 'k';
+
+        let palette = Palette::of(PaletteKind::Default);
+        let mut player = Player::new();
+        player.pos = Vector2::new(1550.0, 1550.0);
+        player.a = 0.0;
+        let (center_x, center_y) = (320.0 - 215.0 + 100.0, 15.0 + 100.0);
+
+        let key_offset = |orientation: MinimapOrientation| {
+            let options = MinimapOptions { zoom: MinimapZoom::Local, orientation, ..MinimapOptions::default() };
+            let mut framebuffer = Framebuffer::new(320, 240);
+            draw_minimap(&mut framebuffer, &maze, &player, &options, &MinimapObjectives::default(), &palette);
+            let mut pixels = Vec::new();
+            for y in 15..215 {
+                for x in 105..305 {
+                    if framebuffer.get_pixel(x, y) == Some(palette.key) {
+                        pixels.push((x as f32, y as f32));
+                    }
+                }
+            }
+            assert!(!pixels.is_empty());
+            let count = pixels.len() as f32;
+            let (sum_x, sum_y) = pixels.iter().fold((0.0, 0.0), |(sx, sy), &(x, y)| (sx + x, sy + y));
+            (sum_x / count - center_x, sum_y / count - center_y)
+        };
+
+        // Norte arriba la llave queda a la derecha; con el jugador arriba, encima
+        let (dx, dy) = key_offset(MinimapOrientation::NorthUp);
+        assert!(dx > 20.0 && dy.abs() < 5.0, "({}, {})", dx, dy);
+        let (dx, dy) = key_offset(MinimapOrientation::PlayerUp);
+        assert!(dy < -20.0 && dx.abs() < 5.0, "({}, {})", dx, dy);
+    }
+
+    #[test]
+    fn missing_asset_list_grows_with_each_path_and_stays_on_screen() {
+        let palette = Palette::of(PaletteKind::Default);
+        let rows_with_text = |missing: &[String]| {
+            let mut framebuffer = Framebuffer::new(200, 120);
+            render_missing_assets(&mut framebuffer, missing, &palette);
+            (0..120)
+                .filter(|&y| (0..200).any(|x| framebuffer.get_pixel(x, y) == Some(palette.text)))
+                .max()
+        };
+
+        let paths: Vec<String> = (0..10).map(|i| format!("assets/sprites/falta_{}.png", i)).collect();
+        let one = rows_with_text(&paths[..1]).unwrap();
+        let two = rows_with_text(&paths[..2]).unwrap();
+        assert!(two > one);
+        assert!(rows_with_text(&paths).unwrap() < 120);
+    }
+
+    #[test]
+    fn fps_graph_plots_recent_frames_with_stutters_as_peaks() {
+        let mut framebuffer = Framebuffer::new(320, 240);
+        let palette = Palette::of(PaletteKind::Default);
+        // Frames a 60 FPS con un tirón de 50 ms en medio
+        let mut frame_times: VecDeque<f32> = std::iter::repeat_n(1.0 / 60.0, FPS_GRAPH_FRAMES).collect();
+        frame_times[50] = 0.05;
+        render_fps_graph(&mut framebuffer, &frame_times, &palette);
+
+        let column = |framebuffer: &mut Framebuffer, x: u32, color: Color| {
+            (27..77).filter(|&y| framebuffer.get_pixel(x, y) == Some(color)).min()
+        };
+        // El pico llega al techo del gráfico en la columna del frame lento
+        assert_eq!(column(&mut framebuffer, 5 + 50 * 2, palette.danger), Some(27));
+        // El resto va sobre la marca de 60 FPS, a un tercio del techo
+        let sixty = column(&mut framebuffer, 5 + 20 * 2, palette.good).unwrap();
+        assert!((55..=63).contains(&sixty), "{}", sixty);
+        assert_eq!(column(&mut framebuffer, 5 + 20 * 2, palette.danger), None);
+    }
+
+    #[test]
+    fn view_cone_spreads_ahead_of_the_player_and_stops_at_walls() {
+        // Pasillo horizontal largo: el cono hacia el este no atraviesa la pared de arriba
+        let maze = maze(&["##########", "#        #", "##########"]);
+        let palette = Palette::of(PaletteKind::Default);
+        let options = MinimapOptions { zoom: MinimapZoom::Local, ..MinimapOptions::default() };
+        let (center_x, center_y) = (320 - 215 + 100, 15 + 100);
+        let render = |angle: f32| {
+            let mut framebuffer = Framebuffer::new(320, 240);
+            let mut player = Player::new();
+            player.pos = Vector2::new(350.0, 150.0);
+            player.a = angle;
+            draw_minimap(&mut framebuffer, &maze, &player, &options, &MinimapObjectives::default(), &palette);
+            framebuffer
+        };
+
+        let mut east = render(0.0);
+        let mut west = render(std::f32::consts::PI);
+        let ahead = (center_x + 2 * LOCAL_MINIMAP_SCALE, center_y);
+        let behind = (center_x - 2 * LOCAL_MINIMAP_SCALE, center_y);
+        assert_ne!(east.get_pixel(ahead.0, ahead.1), west.get_pixel(ahead.0, ahead.1));
+        assert_ne!(east.get_pixel(behind.0, behind.1), west.get_pixel(behind.0, behind.1));
+
+        // Más allá de la pared norte del pasillo no llega el cono
+        let wall = palette.minimap_cell_color('#').unwrap();
+        let past_wall = center_y - LOCAL_MINIMAP_SCALE;
+        assert_eq!(east.get_pixel(ahead.0, past_wall), west.get_pixel(ahead.0, past_wall));
+        assert_eq!(east.get_pixel(ahead.0, past_wall), Some(wall));
+    }
+
+    #[test]
+    fn minimap_settings_rows_cycle_their_option_and_show_the_value() {
+        let mut options = MinimapOptions::default();
+        assert_eq!(MinimapSetting::Corner.row_text(&options), "POSICION: ARRIBA DERECHA");
+
+        // La posición recorre las cuatro esquinas y el oculto antes de volver
+        let mut corners = Vec::new();
+        for _ in 0..MinimapCorner::ALL.len() {
+            MinimapSetting::Corner.apply(&mut options);
+            corners.push(options.corner);
+        }
+        assert_eq!(corners.last(), Some(&MinimapCorner::TopRight));
+        assert!(corners.contains(&MinimapCorner::Hidden));
+
+        MinimapSetting::Zoom.apply(&mut options);
+        MinimapSetting::Orientation.apply(&mut options);
+        assert_eq!(MinimapSetting::Zoom.row_text(&options), "VISTA: LOCAL");
+        assert_eq!(MinimapSetting::Orientation.row_text(&options), "ROTACION: GIRA CON EL JUGADOR");
+
+        let before = options;
+        MinimapSetting::Back.apply(&mut options);
+        assert_eq!(options, before);
+
+        // Todas las filas caben en el recuadro de la pausa
+        for corner in MinimapCorner::ALL {
+            options.corner = corner;
+            for setting in MinimapSetting::ALL {
+                assert!(text_width(&setting.row_text(&options), 1) < 300);
+            }
+        }
+    }
+
+    #[test]
+    fn minimap_corners_stay_on_screen_and_clear_of_the_hud() {
+        let hud_bottom = 140;
+
+        // Tamaño inicial, la ventana más chica permitida y una más grande
+        for (width, height) in [(1300, 900), (MIN_WINDOW_WIDTH, MIN_WINDOW_HEIGHT), (1920, 1080)] {
+            for corner in MinimapCorner::ALL {
+                let Some((x, y)) = corner.origin(width, height, hud_bottom) else {
+                    assert_eq!(corner, MinimapCorner::Hidden);
+                    continue;
+                };
+
+                assert!(x + MINIMAP_SIZE <= width && y + MINIMAP_SIZE <= height, "{:?} se sale de pantalla", corner);
+                // El HUD ocupa de (5, 5) a (240, hud_bottom)
+                let overlaps_hud = x < 240 && y < hud_bottom;
+                assert!(!overlaps_hud, "{:?} tapa el HUD", corner);
+            }
+        }
+    }
+
+    /// Caja (x0, y0, x1, y1) de los pixeles de un color, si hay alguno
+    fn lit_bounds(framebuffer: &mut Framebuffer, color: Color) -> Option<(u32, u32, u32, u32)> {
+        let mut bounds: Option<(u32, u32, u32, u32)> = None;
+        for y in 0..framebuffer.height {
+            for x in 0..framebuffer.width {
+                if framebuffer.get_pixel(x, y) == Some(color) {
+                    bounds = Some(match bounds {
+                        None => (x, y, x, y),
+                        Some((x0, y0, x1, y1)) => (x0.min(x), y0.min(y), x1.max(x), y1.max(y)),
+                    });
+                }
+            }
+        }
+        bounds
+    }
+
+    #[test]
+    fn centered_screens_fit_the_smallest_and_larger_windows() {
+        let title = Color::new(220, 200, 100, 255);
+
+        for (width, height) in [(MIN_WINDOW_WIDTH, MIN_WINDOW_HEIGHT), (1920, 1080)] {
+            // Cada texto queda entero dentro del frame y centrado en horizontal
+            let inside_and_centered = |(x0, y0, x1, y1): (u32, u32, u32, u32), tolerance: u32| {
+                assert!(x0 > 0 && y0 > 0 && x1 < width - 1 && y1 < height - 1, "{}x{}: {:?}", width, height, (x0, y0, x1, y1));
+                assert!(((x0 + x1) / 2).abs_diff(width / 2) <= tolerance, "{}x{}: {:?}", width, height, (x0, x1));
+            };
+
+            let mut loading = Framebuffer::new(width, height);
+            render_loading_screen(&mut loading, "CARGANDO NIVEL", 0.5);
+            inside_and_centered(lit_bounds(&mut loading, title).unwrap(), 3);
+            // La barra va centrada y a medio llenar
+            let (bar_x0, _, bar_x1, _) = lit_bounds(&mut loading, Color::new(100, 255, 100, 255)).unwrap();
+            assert_eq!((bar_x0, bar_x1 - bar_x0 + 1), (width / 2 - 198, 198));
+
+            let mut intro = Framebuffer::new(width, height);
+            render_level_intro(&mut intro, 3);
+            inside_and_centered(lit_bounds(&mut intro, title).unwrap(), 4);
+            inside_and_centered(lit_bounds(&mut intro, Color::WHITE).unwrap(), 8);
+
+            let palette = Palette::of(PaletteKind::Default);
+            let mut graph = Framebuffer::new(width, height);
+            render_fps_graph(&mut graph, &VecDeque::from(vec![0.016; FPS_GRAPH_FRAMES]), &palette);
+            let (x0, y0, x1, y1) = lit_bounds(&mut graph, palette.muted).unwrap();
+            assert!(x0 >= 5 && y0 >= 27 && x1 < width && y1 < 27 + FPS_GRAPH_HEIGHT, "{}x{}", width, height);
+        }
+    }
+}
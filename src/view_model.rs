@@ -0,0 +1,97 @@
+// view_model.rs - Mano con linterna en primera persona
+
+use raylib::prelude::*;
+use crate::framebuffer::Framebuffer;
+use crate::player::{Player, MAX_MOVE_SPEED};
+
+// Avance de la fase de balanceo por unidad recorrida
+const BOB_RATE: f32 = 0.012;
+// Desplazamiento máximo del balanceo, en fracción de la altura de pantalla
+const BOB_AMPLITUDE: f32 = 0.012;
+
+/// Linterna fija en pantalla que se balancea al caminar. Se dibuja después
+/// del mundo y antes del HUD.
+pub struct ViewModel {
+    bob_phase: f32,
+}
+
+impl ViewModel {
+    pub fn new() -> Self {
+        ViewModel { bob_phase: 0.0 }
+    }
+
+    /// La fase avanza con la distancia recorrida en el frame
+    pub fn update(&mut self, player: &Player) {
+        self.bob_phase = (self.bob_phase + player.velocity.length() * BOB_RATE) % std::f32::consts::TAU;
+    }
+
+    // Desplazamiento (x, y) en pixeles: un ocho suave que se apaga al detenerse
+    fn bob_offset(&self, player: &Player, screen_height: u32) -> (f32, f32) {
+        let speed_ratio = (player.velocity.length() / MAX_MOVE_SPEED).min(1.0);
+        let amplitude = screen_height as f32 * BOB_AMPLITUDE * speed_ratio;
+
+        (self.bob_phase.sin() * amplitude, self.bob_phase.sin().abs() * amplitude * 0.6)
+    }
+
+    pub fn render(&self, framebuffer: &mut Framebuffer, player: &Player) {
+        let (offset_x, offset_y) = self.bob_offset(player, framebuffer.height);
+        let unit = framebuffer.height as f32 / 100.0;
+        let base_x = framebuffer.width as f32 * 0.72 + offset_x;
+        let bottom = framebuffer.height as f32 + offset_y;
+
+        // Cuerpo de la linterna con un reflejo vertical
+        let body_top = bottom - unit * 22.0;
+        fill_rect(framebuffer, base_x - unit * 3.0, body_top, unit * 6.0, unit * 22.0, Color::new(45, 45, 50, 255));
+        fill_rect(framebuffer, base_x - unit * 1.5, body_top, unit * 1.0, unit * 22.0, Color::new(90, 90, 100, 255));
+
+        // Cabezal más ancho con el lente encendido
+        let head_top = body_top - unit * 6.0;
+        fill_rect(framebuffer, base_x - unit * 4.5, head_top, unit * 9.0, unit * 6.0, Color::new(60, 60, 66, 255));
+        fill_rect(framebuffer, base_x - unit * 3.5, head_top - unit * 1.0, unit * 7.0, unit * 1.5, Color::new(255, 245, 200, 255));
+
+        // Mano agarrando la linterna, con líneas entre los dedos
+        let hand_top = bottom - unit * 12.0;
+        let skin = Color::new(205, 160, 125, 255);
+        fill_rect(framebuffer, base_x - unit * 5.0, hand_top, unit * 10.0, unit * 12.0, skin);
+        for finger in 1..4 {
+            let finger_y = hand_top + finger as f32 * unit * 2.5;
+            fill_rect(framebuffer, base_x - unit * 5.0, finger_y, unit * 6.0, unit * 0.4, Color::new(160, 115, 90, 255));
+        }
+    }
+}
+
+// Rectángulo recortado a la pantalla; acepta coordenadas fuera de ella
+fn fill_rect(framebuffer: &mut Framebuffer, x: f32, y: f32, width: f32, height: f32, color: Color) {
+    let start_x = x.max(0.0) as u32;
+    let start_y = y.max(0.0) as u32;
+    let end_x = (x + width).clamp(0.0, framebuffer.width as f32) as u32;
+    let end_y = (y + height).clamp(0.0, framebuffer.height as f32) as u32;
+
+    framebuffer.set_current_color(color);
+    for py in start_y..end_y {
+        for px in start_x..end_x {
+            framebuffer.set_pixel(px, py);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bob_stops_at_rest_and_stays_small_while_walking() {
+        let mut view_model = ViewModel::new();
+        let mut player = Player::new();
+        assert_eq!(view_model.bob_offset(&player, 900), (0.0, 0.0));
+
+        player.velocity = Vector2::new(MAX_MOVE_SPEED, 0.0);
+        let limit = 900.0 * BOB_AMPLITUDE;
+        for _ in 0..200 {
+            view_model.update(&player);
+            let (x, y) = view_model.bob_offset(&player, 900);
+            assert!(x.abs() <= limit && (0.0..=limit).contains(&y));
+        }
+        assert!(view_model.bob_phase > 0.0);
+    }
+}